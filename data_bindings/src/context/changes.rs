@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+/// Set of keys that have been modified since the last
+/// time oil has refreshed the bindings.
+///
+/// A key is considered dirty if it has been marked itself, or
+/// if one of its parent or child paths has been marked.
+/// For instance, marking `"player"` makes `"player.hp"` dirty
+/// and marking `"player.hp"` makes `"player"` dirty.
+#[derive(Default, Debug)]
+pub struct DirtyKeys {
+    keys: HashSet<String>,
+    // The parent paths of the marked keys, so that a key is
+    // checked without going through every marked key.
+    parents: HashSet<String>,
+}
+
+impl DirtyKeys {
+
+    pub fn new() -> DirtyKeys {
+        DirtyKeys::default()
+    }
+
+    /// Mark the given key as dirty.
+    pub fn mark(&mut self, key: &str) {
+        if !self.keys.contains(key) {
+            for parent in parent_paths(key) {
+                if !self.parents.contains(parent) {
+                    self.parents.insert(parent.to_string());
+                }
+            }
+            self.keys.insert(key.to_string());
+        }
+    }
+
+    /// Returns true if the given key, or a key overlapping with it,
+    /// has been marked as dirty.
+    pub fn is_dirty(&self, key: &str) -> bool {
+        self.keys.contains(key)
+            || self.parents.contains(key)
+            || parent_paths(key).any(|parent| self.keys.contains(parent))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.parents.clear();
+    }
+}
+
+// ======================================== //
+//                  HELPERS                 //
+// ======================================== //
+
+/// Returns the parent paths of `key`: `"a.b[0]"` gives `"a"` and `"a.b"`.
+fn parent_paths<'a>(key: &'a str) -> Box<Iterator<Item=&'a str> + 'a> {
+    Box::new(key.char_indices()
+        .filter(|&(i, c)| i > 0 && (c == '.' || c == '['))
+        .map(move |(i, _)| &key[..i]))
+}
+
+/// Returns true if `a` and `b` are equal or if
+/// one is a path prefix of the other.
+pub fn overlaps(a: &str, b: &str) -> bool {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    long.starts_with(short)
+        && (long.len() == short.len()
+            || long[short.len()..].starts_with('.')
+            || long[short.len()..].starts_with('['))
+}
+
+// ======================================== //
+//                   TESTS                  //
+// ======================================== //
+
+#[cfg(test)]
+mod test {
+
+    use super::DirtyKeys;
+
+    #[test]
+    fn marked_key_should_be_dirty() {
+        let mut d = DirtyKeys::new();
+        assert!(!d.is_dirty("player.hp"));
+        d.mark("player.hp");
+        assert!(d.is_dirty("player.hp"));
+        d.clear();
+        assert!(!d.is_dirty("player.hp"));
+    }
+
+    #[test]
+    fn parent_and_child_paths_should_be_dirty() {
+        let mut d = DirtyKeys::new();
+        d.mark("player");
+        assert!(d.is_dirty("player.hp"));
+        assert!(!d.is_dirty("players.hp"));
+        d.clear();
+        d.mark("player.hp");
+        assert!(d.is_dirty("player"));
+        assert!(!d.is_dirty("player.xp"));
+        d.mark("party.members");
+        assert!(d.is_dirty("party.members[0].name"));
+        assert!(d.is_dirty("party"));
+        assert!(!d.is_dirty("party.member"));
+        d.mark("enemies[2].hp");
+        assert!(d.is_dirty("enemies[2]"));
+        assert!(d.is_dirty("enemies"));
+        assert!(!d.is_dirty("enemies[2].xp"));
+    }
+}
@@ -0,0 +1,102 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use store::StoreValueStatic;
+use super::changes::overlaps;
+use super::ViewContext;
+
+/// A property whose value is derived from other properties.
+struct Computed {
+    dependencies: Vec<String>,
+    compute: Box<Fn(&ViewContext) -> StoreValueStatic>,
+    // The value is cached per view, as it can depend on a view store.
+    cache: RefCell<HashMap<String, StoreValueStatic>>,
+}
+
+/// The computed properties of a `ContextManager`.
+///
+/// With `cache_values`, a value is computed when it is first read from
+/// a view and then cached for that view, until one of its dependencies
+/// is modified. Otherwise, it is computed at each read.
+#[derive(Default)]
+pub struct ComputedProperties {
+    properties: HashMap<String, Computed>,
+    cached: bool,
+}
+
+impl ComputedProperties {
+
+    pub fn insert(&mut self,
+                  name: String,
+                  dependencies: Vec<String>,
+                  compute: Box<Fn(&ViewContext) -> StoreValueStatic>)
+    {
+        self.properties.insert(name, Computed {
+            dependencies: dependencies,
+            compute: compute,
+            cache: RefCell::new(HashMap::new()),
+        });
+    }
+
+    /// Cache the values from now on: every modification of a
+    /// dependency must then go through `invalidate`.
+    pub fn cache_values(&mut self) {
+        self.cached = true;
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.properties.contains_key(name)
+    }
+
+    /// Returns the value of the property `name` for `view`, computed
+    /// with the given context if it isn't cached.
+    pub fn get(&self, name: &str, view: &str, context: &ViewContext) -> Option<StoreValueStatic> {
+        self.properties.get(name).map(|p| {
+            if let Some(value) = p.cache.borrow().get(view) {
+                return value.clone();
+            }
+            let value = (p.compute)(context);
+            if self.cached {
+                p.cache.borrow_mut().insert(view.to_string(), value.clone());
+            }
+            value
+        })
+    }
+
+    /// Returns true if one of the `dependencies` is `name`, or if
+    /// it is a computed property depending on `name`, directly or not.
+    pub fn depends_on(&self, dependencies: &[String], name: &str) -> bool {
+        let mut visited: Vec<&str> = Vec::new();
+        let mut keys: Vec<&str> = dependencies.iter().map(|d| &d[..]).collect();
+        while let Some(key) = keys.pop() {
+            if overlaps(key, name) {
+                return true;
+            }
+            for (other, p) in self.properties.iter() {
+                if overlaps(key, other) && !visited.contains(&&other[..]) {
+                    visited.push(other);
+                    keys.extend(p.dependencies.iter().map(|d| &d[..]));
+                }
+            }
+        }
+        false
+    }
+
+    /// Drop the cached values depending on `key`, directly or through
+    /// another computed property. Returns the names of the properties
+    /// whose value was cached.
+    pub fn invalidate(&self, key: &str) -> Vec<String> {
+        let mut invalidated = Vec::new();
+        let mut keys = vec![key.to_string()];
+        while let Some(key) = keys.pop() {
+            for (name, p) in self.properties.iter() {
+                if !p.cache.borrow().is_empty() && p.dependencies.iter().any(|d| overlaps(d, &key)) {
+                    p.cache.borrow_mut().clear();
+                    invalidated.push(name.clone());
+                    keys.push(name.clone());
+                }
+            }
+        }
+        invalidated
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::VecDeque;
+
+use store::StoreValueStatic;
+
+/// A value modified during a transaction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub view: String,
+    pub key: String,
+    pub old: StoreValueStatic,
+    pub new: StoreValueStatic,
+}
+
+/// The pending transaction and the committed ones,
+/// that can be undone and redone.
+///
+/// The history is bounded: once `limit` transactions have been
+/// committed, the oldest one is forgotten.
+#[derive(Debug)]
+pub struct History {
+    pending: Option<Vec<Change>>,
+    undo: VecDeque<Vec<Change>>,
+    redo: Vec<Vec<Change>>,
+    limit: usize,
+}
+
+/// Default number of transactions that can be undone.
+pub const DEFAULT_HISTORY_LIMIT: usize = 32;
+
+impl Default for History {
+    fn default() -> History {
+        History::new(DEFAULT_HISTORY_LIMIT)
+    }
+}
+
+impl History {
+
+    pub fn new(limit: usize) -> History {
+        History {
+            pending: None,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit: limit,
+        }
+    }
+
+    /// Start recording. Does nothing if a transaction is already pending.
+    pub fn begin(&mut self) {
+        if self.pending.is_none() {
+            self.pending = Some(Vec::new());
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Returns true if `record` needs the old value of that key.
+    /// Only the value before the first write matters.
+    pub fn needs(&self, view: &str, key: &str) -> bool {
+        match self.pending {
+            Some(ref changes) => !changes.iter().any(|c| c.view == view && c.key == key),
+            None => false,
+        }
+    }
+
+    /// Record the value of a key before it is written.
+    /// The new value is read at commit time.
+    pub fn record(&mut self, view: &str, key: &str, old: StoreValueStatic) {
+        if self.needs(view, key) {
+            if let Some(ref mut changes) = self.pending {
+                changes.push(Change {
+                    view: view.to_string(),
+                    key: key.to_string(),
+                    new: old.clone(),
+                    old: old,
+                });
+            }
+        }
+    }
+
+    /// Stop recording and returns the pending changes.
+    pub fn take_pending(&mut self) -> Vec<Change> {
+        self.pending.take().unwrap_or_else(Vec::new)
+    }
+
+    /// Add a committed transaction. This clears the redo history.
+    pub fn push(&mut self, changes: Vec<Change>) {
+        if changes.is_empty() || self.limit == 0 {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push_back(changes);
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Returns the transaction to undo. Once its values have been
+    /// restored, `undone` moves it in the redo history.
+    pub fn last_undo(&self) -> Option<&[Change]> {
+        self.undo.back().map(|c| &c[..])
+    }
+
+    pub fn undone(&mut self) {
+        if let Some(changes) = self.undo.pop_back() {
+            self.redo.push(changes);
+        }
+    }
+
+    /// Returns the transaction to redo. Once its values have been
+    /// written again, `redone` moves it back in the undo history.
+    pub fn last_redo(&self) -> Option<&[Change]> {
+        self.redo.last().map(|c| &c[..])
+    }
+
+    pub fn redone(&mut self) {
+        if let Some(changes) = self.redo.pop() {
+            self.undo.push_back(changes);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+// ======================================== //
+//                   TESTS                  //
+// ======================================== //
+
+#[cfg(test)]
+mod test {
+
+    use super::{History, Change};
+    use store::StoreValueStatic;
+
+    fn change(key: &str, old: i64, new: i64) -> Vec<Change> {
+        vec![Change {
+            view: "main".to_string(),
+            key: key.to_string(),
+            old: StoreValueStatic::Integer(old),
+            new: StoreValueStatic::Integer(new),
+        }]
+    }
+
+    #[test]
+    fn only_the_first_write_should_be_recorded() {
+        let mut h = History::default();
+        h.record("main", "hp", StoreValueStatic::Integer(1));
+        assert!(h.take_pending().is_empty());
+        h.begin();
+        h.record("main", "hp", StoreValueStatic::Integer(1));
+        h.record("main", "hp", StoreValueStatic::Integer(2));
+        let pending = h.take_pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].old, StoreValueStatic::Integer(1));
+        assert!(!h.is_recording());
+    }
+
+    #[test]
+    fn history_should_be_bounded() {
+        let mut h = History::new(2);
+        h.push(change("a", 0, 1));
+        h.push(change("b", 0, 1));
+        h.push(change("c", 0, 1));
+        assert_eq!(h.last_undo().unwrap()[0].key, "c");
+        h.undone();
+        assert_eq!(h.last_undo().unwrap()[0].key, "b");
+        h.undone();
+        assert!(h.last_undo().is_none());
+        assert_eq!(h.last_redo().unwrap()[0].key, "b");
+        h.redone();
+        assert_eq!(h.last_undo().unwrap()[0].key, "b");
+        h.push(change("d", 0, 1));
+        assert!(!h.can_redo());
+    }
+}
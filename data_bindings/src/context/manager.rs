@@ -8,8 +8,14 @@ use {
     AttributeGetResult,
     AttributeMutResult
 };
-use store::StoreValueStatic;
+use store::{StoreValueStatic, AsStoreValue, Cast};
 use super::Context;
+use super::DirtyKeys;
+use super::History;
+use super::updates::{Update, UpdateQueue, UpdateSender};
+use super::computed::ComputedProperties;
+use filters::Filters;
+use error::{BindingError, BindingResult, WrongTypePolicy};
 
 use store::AssignFromCast;
 use DataBindingsContext;
@@ -17,10 +23,26 @@ use DataBindingsContext;
 /// The `ContextManager` is templated by a class implementing
 /// the `Store` trait. It will then be your only way to store
 /// values that can be bind and used in views.
+///
+/// By default, oil queries every binding at each update. If change
+/// tracking is enabled with `track_changes`, only the bindings whose
+/// keys have been modified through the `ContextManager` (or notified
+/// with `notify`) are queried again.
+///
+/// Writes can be grouped in transactions, see `begin_transaction`.
+/// Other threads can send updates, see `update_sender`.
+/// Values derived from other properties can be registered
+/// with `register_computed`.
 #[derive(Default)]
 pub struct ContextManager<G: Default, V> {
     global: G,
     views: HashMap<String, V>,
+    changes: Option<DirtyKeys>,
+    filters: Filters,
+    wrong_type_policy: WrongTypePolicy,
+    history: History,
+    updates: Option<UpdateQueue<G>>,
+    computed: ComputedProperties,
 }
 
 impl<G, V> ContextManager<G, V>
@@ -33,10 +55,25 @@ impl<G, V> ContextManager<G, V>
     pub fn new(store: G) -> ContextManager<G, V> {
         ContextManager {
             global: store,
-            views: HashMap::new()
+            views: HashMap::new(),
+            changes: None,
+            filters: Filters::new(),
+            wrong_type_policy: WrongTypePolicy::default(),
+            history: History::default(),
+            updates: None,
+            computed: ComputedProperties::default(),
         }
     }
 
+    /// Register a filter usable in binding expressions such
+    /// as `{{player.gold | my_filter(2)}}`.
+    /// See `Filters` for the list of built-in filters.
+    pub fn register_filter<F>(&mut self, name: String, filter: F)
+        where F: Fn(&StoreValueStatic, &[StoreValueStatic]) -> StoreValueStatic + 'static
+    {
+        self.filters.register(name, filter);
+    }
+
     /// Insert a root `Store` for the given view.
     /// **Note:**
     ///     With the `ContextManager`, all views have a store of the same type.
@@ -61,38 +98,235 @@ impl<G, V> ContextManager<G, V>
         self.views.get_mut(view_name)
     }
 
-    /// Equivalent to the get_attribute of the `Store` trait.
-    pub fn get_attribute<'a>(&'a self, view: &str, key: &str) -> Option<StoreValue<'a>> {
-        match self.views.get(view) {
-            Some(store) =>
-                if let AttributeGetResult::PrimitiveType(sv) = store
-                    .get_attribute(PropertyAccessor::new(key)) {
-                    return Some(sv);
-                },
-            _ => ()
-        }
-        if let AttributeGetResult::PrimitiveType(sv) = self.global
-            .get_attribute(PropertyAccessor::new(key)) {
-            Some(sv)
-        } else {
-            None
-        }
+    /// Equivalent to the get_attribute of the `Store` trait: the view
+    /// store is looked up first, then the global store.
+    /// Computed properties are not held by a store,
+    /// use `get_value` to read them.
+    pub fn get_attribute<'a>(&'a self, view: &str, key: &str) -> AttributeGetResult<'a> {
+        lookup_attribute(self.views.get(view).map(|vs| vs as &Store), &self.global, key)
     }
 
     /// Equivalent to the `set_attribute` of the `Store` trait.
-    /// TODO(Nemikolh): Move that comment for the trait `...`
     /// The view argument can be ignored. It offers the possibility
-    /// To have different root stores per view.
-    pub fn set_attribute<'a>(&mut self, view: &str, key: &str, value: StoreValue<'a>) {
-        if let AttributeSetResult::NoSuchProperty(sv) = self.views.get_mut(view)
-            .unwrap()
-            .set_attribute(PropertyAccessor::new(key), value) {
-            if let AttributeSetResult::NoSuchProperty(_) = self.global
-                .set_attribute(PropertyAccessor::new(key), sv) {
-                // Insertion failed.
+    /// To have different root stores per view: the view store is
+    /// looked up first, then the global store.
+    ///
+    /// Fails with `BindingError::UnknownView` if no store has been
+    /// inserted for that view with `insert_view_level_store`, rather
+    /// than writing a global value by mistake. A value of the wrong
+    /// type is reported according to the `WrongTypePolicy`.
+    pub fn set_attribute<'a>(&mut self, view: &str, key: &str, value: StoreValue<'a>)
+        -> BindingResult<()>
+    {
+        if !self.views.contains_key(view) {
+            return Err(BindingError::UnknownView(view.to_string()));
+        }
+        if self.history.needs(view, key) {
+            if let Some(old) = self.get_value(view, key) {
+                self.history.record(view, key, old);
+            }
+        }
+        self.write(view, key, value)
+    }
+
+    /// Returns a copy of the value, whether it is borrowed
+    /// from a store or computed.
+    pub fn get_value(&self, view: &str, key: &str) -> Option<StoreValueStatic> {
+        self.get_view_context(view).get_value(key)
+    }
+
+    /// Register a property whose value is computed from other
+    /// properties of the view and global stores, such as:
+    ///
+    /// ```ignore
+    /// context.register_computed("player.hp_ratio".to_string(), &["player.hp", "player.max_hp"], |ctx| {
+    ///     ctx.get::<f64>("player.hp").unwrap_or(0.0) / ctx.get::<f64>("player.max_hp").unwrap_or(1.0)
+    /// });
+    /// ```
+    ///
+    /// Once change tracking is enabled, the value is cached per view until
+    /// one of the dependencies is modified through the `ContextManager`, or
+    /// notified with `notify`. Without it, the value is computed at each
+    /// read, so the stores can be modified directly.
+    /// A computed property can depend on another one, but fails with
+    /// `BindingError::ComputedCycle` if it would then depend on itself.
+    pub fn register_computed<F, R>(&mut self, name: String, dependencies: &[&str], compute: F)
+        -> BindingResult<()>
+        where F: Fn(&ViewContext) -> R + 'static,
+              R: Into<StoreValueStatic>
+    {
+        let dependencies: Vec<String> = dependencies.iter().map(|d| d.to_string()).collect();
+        if self.computed.depends_on(&dependencies, &name) {
+            return Err(BindingError::ComputedCycle(name));
+        }
+        self.notify(&name);
+        self.computed.insert(name, dependencies, Box::new(move |ctx| compute(ctx).into()));
+        Ok(())
+    }
+
+    /// Start a transaction. Until `commit` or `rollback` is called,
+    /// the previous value of every key written with `set_attribute` or
+    /// through a `ViewContextMut` is recorded. The writes are applied
+    /// immediately, so that bound inputs can be edited speculatively.
+    ///
+    /// Transactions don't nest: this does nothing if
+    /// a transaction is already pending.
+    pub fn begin_transaction(&mut self) {
+        self.history.begin();
+    }
+
+    /// Returns true if a transaction is pending.
+    pub fn in_transaction(&self) -> bool {
+        self.history.is_recording()
+    }
+
+    /// Keep the writes of the pending transaction. The transaction
+    /// can then be undone. Keys whose value hasn't changed are dropped.
+    pub fn commit(&mut self) {
+        let mut changes = self.history.take_pending();
+        for change in changes.iter_mut() {
+            if let Some(new) = self.get_value(&change.view, &change.key) {
+                change.new = new;
+            }
+        }
+        changes.retain(|c| c.old != c.new);
+        self.history.push(changes);
+    }
+
+    /// Restore the values written during the pending transaction.
+    pub fn rollback(&mut self) -> BindingResult<()> {
+        let changes = self.history.take_pending();
+        let mut result = Ok(());
+        for c in changes.iter().rev() {
+            result = result.and(self.write(&c.view, &c.key, c.old.as_store_value()));
+        }
+        result
+    }
+
+    /// Undo the last committed transaction.
+    /// Returns false if there's nothing to undo.
+    ///
+    /// If a value can't be restored, the error is returned and the
+    /// transaction stays the one to undo.
+    pub fn undo(&mut self) -> BindingResult<bool> {
+        let changes = match self.history.last_undo() {
+            Some(changes) => changes.to_vec(),
+            None => return Ok(false),
+        };
+        let mut result = Ok(());
+        for c in changes.iter().rev() {
+            result = result.and(self.write(&c.view, &c.key, c.old.as_store_value()));
+        }
+        try!(result);
+        self.history.undone();
+        Ok(true)
+    }
+
+    /// Redo the last undone transaction.
+    /// Returns false if there's nothing to redo.
+    ///
+    /// As with `undo`, the transaction is moved back in the undo
+    /// history only once all its values have been written.
+    pub fn redo(&mut self) -> BindingResult<bool> {
+        let changes = match self.history.last_redo() {
+            Some(changes) => changes.to_vec(),
+            None => return Ok(false),
+        };
+        let mut result = Ok(());
+        for c in changes.iter() {
+            result = result.and(self.write(&c.view, &c.key, c.new.as_store_value()));
+        }
+        try!(result);
+        self.history.redone();
+        Ok(true)
+    }
+
+    /// Set the number of committed transactions that can be undone.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history.set_limit(limit);
+    }
+
+    /// `set_attribute` without recording.
+    fn write<'a>(&mut self, view: &str, key: &str, value: StoreValue<'a>) -> BindingResult<()> {
+        let result = match self.views.get_mut(view) {
+            Some(store) => store.set_attribute(PropertyAccessor::new(key), value),
+            None => return Err(BindingError::UnknownView(view.to_string())),
+        };
+        self.write_global(key, result)
+    }
+
+    /// Write the value in the global store if the view didn't have it.
+    /// The key is marked as dirty only if the value has been stored.
+    fn write_global<'a>(&mut self, key: &str, result: AttributeSetResult<'a>) -> BindingResult<()> {
+        let result = match result {
+            AttributeSetResult::NoSuchProperty(value) =>
+                self.global.set_attribute(PropertyAccessor::new(key), value),
+            other => other,
+        };
+        match result {
+            AttributeSetResult::Stored => {
+                self.notify(key);
+                Ok(())
             }
+            AttributeSetResult::WrongType => self.wrong_type_policy.apply(key),
+            AttributeSetResult::NoSuchProperty(_) =>
+                Err(BindingError::NoSuchProperty(key.to_string())),
+            AttributeSetResult::DanglingReference =>
+                Err(BindingError::DanglingReference(key.to_string())),
+            AttributeSetResult::Shared =>
+                Err(BindingError::SharedStore(key.to_string())),
         }
     }
+
+    fn apply_update(&mut self, queue: &UpdateQueue<G>, update: Update) -> BindingResult<()> {
+        match update {
+            Update::Value { view: Some(view), key, value } =>
+                self.set_attribute(&view, &key, value.as_store_value()),
+            Update::Value { view: None, key, value } =>
+                self.write_global(&key, AttributeSetResult::NoSuchProperty(value.as_store_value())),
+            Update::Snapshot { name, store } => {
+                self.notify(&name);
+                queue.register(&mut self.global, name, store);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<G: Default, V> ContextManager<G, V> {
+
+    /// Enable change tracking. Once enabled, you must call `notify`
+    /// for every modification made to a store without going through
+    /// the `ContextManager`. The computed properties are cached too.
+    pub fn track_changes(&mut self) {
+        if self.changes.is_none() {
+            self.changes = Some(DirtyKeys::new());
+            self.computed.cache_values();
+        }
+    }
+
+    /// Mark the given key as modified. Bindings using that key,
+    /// or a key below or above it (such as `player` for `player.hp`)
+    /// will be refreshed on the next update.
+    ///
+    /// The computed properties depending on that key are computed
+    /// again when read. Apart from that, this does nothing if change
+    /// tracking isn't enabled.
+    pub fn notify(&mut self, key: &str) {
+        let invalidated = self.computed.invalidate(key);
+        if let Some(ref mut changes) = self.changes {
+            changes.mark(key);
+            for name in invalidated.iter() {
+                changes.mark(name);
+            }
+        }
+    }
+
+    /// Choose what `set_attribute` does when a value
+    /// can't be cast to the type of the property.
+    pub fn set_wrong_type_policy(&mut self, policy: WrongTypePolicy) {
+        self.wrong_type_policy = policy;
+    }
 }
 
 /// Implement `Context` equivalent method per view,
@@ -101,20 +335,35 @@ impl<G, V> ContextManager<G, V>
     where V: Context,
           G: Default
 {
+    /// Fails with `BindingError::UnknownView` if no store has been
+    /// inserted for that view with `insert_view_level_store`.
     pub fn register_store_for_view<S: Store>(
         &mut self,
         view_name: String,
         store_name: String,
-        store: S) {
+        store: S) -> BindingResult<()>
+    {
+        if !self.views.contains_key(&view_name) {
+            return Err(BindingError::UnknownView(view_name));
+        }
+        self.notify(&store_name);
         self.views.get_mut(&view_name).unwrap().register_store(store_name, store);
+        Ok(())
     }
 
+    /// See `register_store_for_view`.
     pub fn register_value_for_view<M: Into<StoreValueStatic>>(
         &mut self,
         view_name: String,
         store_name: String,
-        value: M) {
+        value: M) -> BindingResult<()>
+    {
+        if !self.views.contains_key(&view_name) {
+            return Err(BindingError::UnknownView(view_name));
+        }
+        self.notify(&store_name);
         self.views.get_mut(&view_name).unwrap().register_value(store_name, value);
+        Ok(())
     }
 }
 
@@ -128,6 +377,7 @@ impl<G, V> ContextManager<G, V>
         store_name: String,
         store: S)
     {
+        self.notify(&store_name);
         self.global.register_store(store_name, store);
     }
 
@@ -137,8 +387,20 @@ impl<G, V> ContextManager<G, V>
         store_name: String,
         value: M)
     {
+        self.notify(&store_name);
         self.global.register_value(store_name, value);
     }
+
+    /// Returns a sender that can be moved to another thread, such as
+    /// a game simulation. The updates are applied by `Router::update`,
+    /// before the bindings are refreshed. Only the updates sent for
+    /// a view go through `set_attribute` and the pending transaction.
+    pub fn update_sender(&mut self) -> UpdateSender {
+        if self.updates.is_none() {
+            self.updates = Some(UpdateQueue::new());
+        }
+        self.updates.as_ref().unwrap().sender()
+    }
 }
 
 // ======================================== //
@@ -149,69 +411,249 @@ impl<G, V> DataBindingsContext for ContextManager<G, V>
     where G: Store + Default,
           V: Store
 {
-    fn get_view_context<'a>(&'a self, view: &String) -> ViewContext<'a> {
+    fn get_view_context<'a>(&'a self, view: &'a str) -> ViewContext<'a> {
         ViewContext {
-            view_context: self.get_view_store(view).map(|vs| vs as &Store),
-            global: &self.global as &Store
+            view: view,
+            view_context: self.views.get(view).map(|vs| vs as &Store),
+            global: &self.global as &Store,
+            computed: Some(&self.computed),
         }
     }
 
-    fn get_view_context_mut<'a>(&'a mut self, view: &String) -> ViewContextMut<'a> {
+    fn get_view_context_mut<'a>(&'a mut self, view: &'a str) -> ViewContextMut<'a> {
+        let history = if self.history.is_recording() { Some(&mut self.history) } else { None };
         ViewContextMut {
+            view: view,
             view_context: self.views.get_mut(view).map(|vs| vs as &mut Store),
-            global: &mut self.global as &mut Store
+            global: &mut self.global as &mut Store,
+            changes: self.changes.as_mut(),
+            history: history,
+            computed: &self.computed,
+        }
+    }
+
+    fn dirty_keys(&self) -> Option<&DirtyKeys> {
+        self.changes.as_ref()
+    }
+
+    fn clear_dirty_keys(&mut self) {
+        if let Some(ref mut changes) = self.changes {
+            changes.clear();
         }
     }
+
+    fn apply_updates(&mut self) -> Vec<BindingError> {
+        let mut errors = Vec::new();
+        if let Some(queue) = self.updates.take() {
+            for update in queue.pending() {
+                if let Err(e) = self.apply_update(&queue, update) {
+                    errors.push(e);
+                }
+            }
+            self.updates = Some(queue);
+        }
+        errors
+    }
+
+    fn apply_filter(&self, name: &str, value: &StoreValueStatic, args: &[StoreValueStatic])
+        -> Option<StoreValueStatic>
+    {
+        self.filters.apply(name, value, args)
+    }
 }
 
 pub struct ViewContext<'a> {
+    view: &'a str,
     view_context: Option<&'a Store>,
     global: &'a Store,
+    computed: Option<&'a ComputedProperties>,
 }
 
 pub struct ViewContextMut<'a> {
+    view: &'a str,
     view_context: Option<&'a mut Store>,
     global: &'a mut Store,
+    changes: Option<&'a mut DirtyKeys>,
+    history: Option<&'a mut History>,
+    computed: &'a ComputedProperties,
 }
 
 impl<'a> ViewContext<'a> {
-    pub fn get_attribute(&'a self, property_path: &str) -> Option<StoreValue<'a>> {
-        match self.view_context {
-            Some(ref store) =>
-                if let AttributeGetResult::PrimitiveType(sv) = store
-                    .get_attribute(PropertyAccessor::new(property_path)) {
-                    return Some(sv);
-                },
-            _ => ()
-        }
-        if let AttributeGetResult::PrimitiveType(sv) = self.global
-            .get_attribute(PropertyAccessor::new(property_path)) {
-            Some(sv)
-        } else {
-            None
+
+    /// Look for the property in the computed properties, the view store
+    /// and then the global store. Unlike `get_attribute`, computed
+    /// values are included.
+    pub fn lookup(&'a self, property_path: &str) -> AttributeGetResult<'a> {
+        if let Some(computed) = self.computed {
+            if computed.contains(property_path) {
+                return match computed.get(property_path, self.view, self) {
+                    Some(value) => AttributeGetResult::OwnedPrimitiveType(value),
+                    None => AttributeGetResult::NoSuchProperty,
+                };
+            }
         }
+        self.get_attribute(property_path)
+    }
+
+    /// Same as `lookup`, but the result is given to `f` and can borrow
+    /// from a shared store: a list behind a `RefCell` or a `Mutex` can
+    /// be iterated. See `Store::with_attribute`.
+    pub fn with_lookup(&self, property_path: &str, f: &mut FnMut(AttributeGetResult)) {
+        if let Some(computed) = self.computed {
+            if computed.contains(property_path) {
+                return f(match computed.get(property_path, self.view, self) {
+                    Some(value) => AttributeGetResult::OwnedPrimitiveType(value),
+                    None => AttributeGetResult::NoSuchProperty,
+                });
+            }
+        }
+        if let Some(store) = self.view_context {
+            let mut found = false;
+            store.with_attribute(PropertyAccessor::new(property_path), &mut |result| match result {
+                AttributeGetResult::NoSuchProperty => (),
+                result => {
+                    found = true;
+                    f(result)
+                }
+            });
+            if found {
+                return;
+            }
+        }
+        self.global.with_attribute(PropertyAccessor::new(property_path), f)
+    }
+
+    /// Returns a copy of the value, see `lookup`.
+    pub fn get_value(&self, property_path: &str) -> Option<StoreValueStatic> {
+        self.lookup(property_path).value()
+    }
+
+    /// Returns the value cast to `T`, see `lookup`.
+    pub fn get<T: Cast>(&self, property_path: &str) -> Option<T> {
+        self.get_value(property_path).and_then(|v| T::cast(v.as_store_value()))
+    }
+
+    /// Look for the property in the view store and then the global store.
+    pub fn get_attribute(&'a self, property_path: &str) -> AttributeGetResult<'a> {
+        lookup_attribute(self.view_context, self.global, property_path)
     }
 }
 
 impl<'a> ViewContextMut<'a> {
-    pub fn get_attribute(&'a mut self, property_path: &str) -> Option<&'a mut AssignFromCast> {
-        match self.view_context {
-            Some(ref mut store) =>
-                if let AttributeMutResult::PrimitiveType(sv) = store
-                    .get_attribute_mut(PropertyAccessor::new(property_path)) {
-                    return Some(sv);
-                },
-            _ => ()
-        }
-        if let AttributeMutResult::PrimitiveType(sv) = self.global
-            .get_attribute_mut(PropertyAccessor::new(property_path)) {
-            Some(sv)
-        } else {
-            None
+    /// The mutable access is assumed to be used for an assignment,
+    /// so the property is marked as dirty if changes are tracked, and its
+    /// current value is recorded if a transaction is pending.
+    ///
+    /// A value held by a shared store, such as a `Rc<RefCell<T>>`, can't
+    /// be borrowed past this call and fails with `SharedStore`: use
+    /// `with_attribute` to assign it.
+    pub fn get_attribute(&'a mut self, property_path: &str) -> BindingResult<&'a mut AssignFromCast> {
+        // The property is only marked once it is known to be assignable.
+        try!(lookup_attribute_mut(
+            self.view_context.as_mut().map(|v| &mut **v as &mut Store),
+            &mut *self.global,
+            property_path).map(|_| ()));
+        let old = self.old_value(property_path);
+        self.will_assign(property_path, old);
+        lookup_attribute_mut(
+            self.view_context.as_mut().map(|v| &mut **v as &mut Store),
+            &mut *self.global,
+            property_path)
+    }
+
+    /// Same as `get_attribute`, but the value is given to `f` for the
+    /// duration of the call, which works with shared stores too.
+    /// Returns false if there's no such value: `f` isn't called
+    /// and the property isn't marked as dirty.
+    pub fn with_attribute(&mut self, property_path: &str, f: &mut FnMut(&mut AssignFromCast)) -> bool {
+        let old = self.old_value(property_path);
+        let found = match self.view_context {
+            Some(ref mut store) => assign_with(&mut **store, property_path, f),
+            None => false,
+        } || assign_with(&mut *self.global, property_path, f);
+        if found {
+            self.will_assign(property_path, old);
+        }
+        found
+    }
+
+    /// Returns the current value if a pending transaction needs it.
+    fn old_value(&self, property_path: &str) -> Option<StoreValueStatic> {
+        match self.history {
+            Some(ref history) if history.needs(self.view, property_path) => {
+                let view_context = self.view_context.as_ref().map(|v| &**v as &Store);
+                lookup_attribute(view_context, &*self.global, property_path).value()
+            }
+            _ => None,
+        }
+    }
+
+    fn will_assign(&mut self, property_path: &str, old: Option<StoreValueStatic>) {
+        let invalidated = self.computed.invalidate(property_path);
+        if let Some(ref mut changes) = self.changes {
+            changes.mark(property_path);
+            for name in invalidated.iter() {
+                changes.mark(name);
+            }
+        }
+        if let (Some(ref mut history), Some(old)) = (self.history.as_mut(), old) {
+            history.record(self.view, property_path, old);
+        }
+    }
+}
+
+
+// ======================================== //
+//                  HELPERS                 //
+// ======================================== //
+
+/// Give the primitive value to `f`, returns false if there's none.
+fn assign_with(store: &mut Store, key: &str, f: &mut FnMut(&mut AssignFromCast)) -> bool {
+    let mut found = false;
+    store.with_attribute_mut(PropertyAccessor::new(key), &mut |result| {
+        if let AttributeMutResult::PrimitiveType(value) = result {
+            found = true;
+            f(value)
+        }
+    });
+    found
+}
+
+/// Same as `lookup_attribute` with a mutable access to a primitive value.
+fn lookup_attribute_mut<'a>(view: Option<&'a mut Store>, global: &'a mut Store, key: &str)
+    -> BindingResult<&'a mut AssignFromCast>
+{
+    if let Some(store) = view {
+        match store.get_attribute_mut(PropertyAccessor::new(key)) {
+            AttributeMutResult::NoSuchProperty => (),
+            result => return assignable(result, key),
         }
     }
+    assignable(global.get_attribute_mut(PropertyAccessor::new(key)), key)
 }
 
+fn assignable<'a>(result: AttributeMutResult<'a>, key: &str) -> BindingResult<&'a mut AssignFromCast> {
+    match result {
+        AttributeMutResult::PrimitiveType(value) => Ok(value),
+        AttributeMutResult::IterableType(_) => Err(BindingError::WrongType(key.to_string())),
+        AttributeMutResult::NoSuchProperty => Err(BindingError::NoSuchProperty(key.to_string())),
+        AttributeMutResult::DanglingReference => Err(BindingError::DanglingReference(key.to_string())),
+        AttributeMutResult::Shared => Err(BindingError::SharedStore(key.to_string())),
+    }
+}
+
+/// Look for the property in the view store first, then in the global store.
+fn lookup_attribute<'a>(view: Option<&'a Store>, global: &'a Store, key: &str)
+    -> AttributeGetResult<'a>
+{
+    if let Some(store) = view {
+        match store.get_attribute(PropertyAccessor::new(key)) {
+            AttributeGetResult::NoSuchProperty => (),
+            result => return result,
+        }
+    }
+    global.get_attribute(PropertyAccessor::new(key))
+}
 
 // ======================================== //
 //                   TESTS                  //
@@ -219,4 +661,332 @@ impl<'a> ViewContextMut<'a> {
 
 #[cfg(test)]
 mod test {
+
+    use std::rc::Rc;
+    use std::cell::{Cell, RefCell};
+    use std::thread;
+    use super::super::{DefaultContextManager, Proxy};
+    use {StoreValue, BindingError, WrongTypePolicy};
+    use store::StoreValueStatic;
+    use DataBindingsContext;
+
+    #[test]
+    fn changes_should_not_be_tracked_by_default() {
+        let mut context = DefaultContextManager::default();
+        context.register_global_value("player.hp".to_string(), 12);
+        assert!(context.dirty_keys().is_none());
+    }
+
+    #[test]
+    fn set_attribute_should_mark_key_as_dirty() {
+        let mut context = DefaultContextManager::default();
+        context.track_changes();
+        context.register_global_value("player.hp".to_string(), 12);
+        context.clear_dirty_keys();
+        context.insert_view_level_store("main".to_string(), Default::default());
+        context.set_attribute("main", "player.hp", StoreValue::Integer(10)).unwrap();
+        assert!(context.dirty_keys().unwrap().is_dirty("player.hp"));
+        context.clear_dirty_keys();
+        assert!(!context.dirty_keys().unwrap().is_dirty("player.hp"));
+    }
+
+    #[test]
+    fn failed_set_attribute_should_not_mark_key_as_dirty() {
+        let mut context = DefaultContextManager::default();
+        context.track_changes();
+        context.register_global_store("hp".to_string(), 12);
+        context.clear_dirty_keys();
+        context.insert_view_level_store("main".to_string(), Default::default());
+        assert!(context.set_attribute("main", "hp", StoreValue::String("a")).is_err());
+        assert!(!context.dirty_keys().unwrap().is_dirty("hp"));
+    }
+
+    #[test]
+    fn set_attribute_for_unknown_view_should_fail() {
+        let mut context = DefaultContextManager::default();
+        context.track_changes();
+        context.register_global_value("player.hp".to_string(), 12);
+        context.clear_dirty_keys();
+        assert_eq!(context.set_attribute("mian", "player.hp", StoreValue::Integer(10)),
+                   Err(BindingError::UnknownView("mian".to_string())));
+        assert_eq!(context.get_value("main", "player.hp"), Some(StoreValueStatic::Integer(12)));
+        assert!(!context.dirty_keys().unwrap().is_dirty("player.hp"));
+    }
+
+    #[test]
+    fn set_attribute_missing_from_view_store_should_use_global() {
+        let mut context = DefaultContextManager::default();
+        context.insert_view_level_store("main".to_string(), Default::default());
+        context.register_global_value("player.hp".to_string(), 12);
+        assert_eq!(context.set_attribute("main", "player.hp", StoreValue::Integer(10)), Ok(()));
+        assert_eq!(context.get_attribute("main", "player.hp").unwrap(), StoreValue::Integer(10));
+        assert_eq!(context.set_attribute("main", "player.xp", StoreValue::Integer(10)),
+                   Err(BindingError::NoSuchProperty("player.xp".to_string())));
+    }
+
+    #[test]
+    fn register_for_unknown_view_should_fail() {
+        let mut context = DefaultContextManager::default();
+        context.track_changes();
+        assert_eq!(context.register_value_for_view("main".to_string(), "hp".to_string(), 1),
+                   Err(BindingError::UnknownView("main".to_string())));
+        assert!(!context.dirty_keys().unwrap().is_dirty("hp"));
+        context.insert_view_level_store("main".to_string(), Default::default());
+        assert_eq!(context.register_value_for_view("main".to_string(), "hp".to_string(), 1), Ok(()));
+    }
+
+    #[test]
+    fn wrong_type_should_follow_policy() {
+        let mut context = DefaultContextManager::default();
+        context.insert_view_level_store("main".to_string(), Default::default());
+        context.register_global_store("hp".to_string(), 12);
+        assert_eq!(context.set_attribute("main", "hp", StoreValue::String("a")),
+                   Err(BindingError::WrongType("hp".to_string())));
+        context.set_wrong_type_policy(WrongTypePolicy::Ignore);
+        assert_eq!(context.set_attribute("main", "hp", StoreValue::String("a")), Ok(()));
+    }
+
+    #[test]
+    fn values_copied_from_a_shared_store_should_be_returned() {
+        let mut context = DefaultContextManager::default();
+        let name = Rc::new(RefCell::new("Bob".to_string()));
+        context.register_global_store("name".to_string(), name.clone());
+        assert_eq!(context.get_attribute("main", "name").unwrap(), StoreValue::String("Bob"));
+        let view = context.get_view_context("main");
+        assert_eq!(view.get_attribute("name").value(),
+                   Some(StoreValueStatic::String("Bob".to_string())));
+    }
+
+    #[test]
+    fn values_of_a_shared_store_should_be_assigned() {
+        let mut context = DefaultContextManager::default();
+        context.track_changes();
+        let name = Rc::new(RefCell::new("Bob".to_string()));
+        context.register_global_store("name".to_string(), name.clone());
+        context.clear_dirty_keys();
+        {
+            let mut view = context.get_view_context_mut("main");
+            assert!(view.with_attribute("name", &mut |v| v.assign(StoreValue::String("Alice"))));
+            assert!(!view.with_attribute("age", &mut |v| v.assign(StoreValue::Integer(3))));
+        }
+        assert_eq!(*name.borrow(), "Alice");
+        assert!(context.dirty_keys().unwrap().is_dirty("name"));
+        assert!(!context.dirty_keys().unwrap().is_dirty("age"));
+    }
+
+    #[test]
+    fn values_of_a_shared_store_should_not_be_borrowed() {
+        let mut context = DefaultContextManager::default();
+        context.track_changes();
+        let name = Rc::new(RefCell::new("Bob".to_string()));
+        context.register_global_store("name".to_string(), name.clone());
+        context.register_global_store("hp".to_string(), Proxy::new(&name));
+        context.clear_dirty_keys();
+        {
+            let mut view = context.get_view_context_mut("main");
+            assert_eq!(view.get_attribute("name").err(),
+                       Some(BindingError::SharedStore("name".to_string())));
+        }
+        {
+            let mut view = context.get_view_context_mut("main");
+            assert_eq!(view.get_attribute("hp").err(),
+                       Some(BindingError::SharedStore("hp".to_string())));
+        }
+        {
+            let mut view = context.get_view_context_mut("main");
+            assert_eq!(view.get_attribute("age").err(),
+                       Some(BindingError::NoSuchProperty("age".to_string())));
+        }
+        assert!(context.dirty_keys().unwrap().is_empty());
+    }
+
+    #[test]
+    fn dangling_reference_should_be_reported() {
+        let mut context = DefaultContextManager::default();
+        context.insert_view_level_store("main".to_string(), Default::default());
+        let player = Rc::new(RefCell::new(12));
+        context.register_global_store("hp".to_string(), Proxy::new(&player));
+        drop(player);
+        assert_eq!(context.set_attribute("main", "hp", StoreValue::Integer(1)),
+                   Err(BindingError::DanglingReference("hp".to_string())));
+    }
+
+    #[test]
+    fn rollback_should_restore_values() {
+        let mut context = DefaultContextManager::default();
+        context.insert_view_level_store("main".to_string(), Default::default());
+        context.register_global_value("volume".to_string(), 5);
+        context.begin_transaction();
+        context.set_attribute("main", "volume", StoreValue::Integer(8)).unwrap();
+        context.set_attribute("main", "volume", StoreValue::Integer(9)).unwrap();
+        assert_eq!(context.get_value("main", "volume"), Some(StoreValueStatic::Integer(9)));
+        context.rollback().unwrap();
+        assert!(!context.in_transaction());
+        assert_eq!(context.get_value("main", "volume"), Some(StoreValueStatic::Integer(5)));
+        assert_eq!(context.undo(), Ok(false));
+    }
+
+    #[test]
+    fn failed_undo_should_keep_the_transaction() {
+        let mut context = DefaultContextManager::default();
+        let hp = Rc::new(RefCell::new(12));
+        context.register_global_store("hp".to_string(), Proxy::new(&hp));
+        context.insert_view_level_store("main".to_string(), Default::default());
+        context.begin_transaction();
+        context.set_attribute("main", "hp", StoreValue::Integer(8)).unwrap();
+        context.commit();
+        drop(hp);
+        assert_eq!(context.undo(), Err(BindingError::DanglingReference("hp".to_string())));
+        assert_eq!(context.redo(), Ok(false));
+        assert_eq!(context.undo(), Err(BindingError::DanglingReference("hp".to_string())));
+    }
+
+    #[test]
+    fn committed_transactions_should_be_undoable() {
+        let mut context = DefaultContextManager::default();
+        context.insert_view_level_store("main".to_string(), Default::default());
+        context.register_global_value("volume".to_string(), 5);
+        context.register_global_value("name".to_string(), "Bob".to_string());
+        context.begin_transaction();
+        context.set_attribute("main", "volume", StoreValue::Integer(8)).unwrap();
+        {
+            let mut view = context.get_view_context_mut("main");
+            view.get_attribute("name").unwrap().assign(StoreValue::String("Alice"));
+        }
+        context.commit();
+        assert_eq!(context.undo(), Ok(true));
+        assert_eq!(context.get_value("main", "volume"), Some(StoreValueStatic::Integer(5)));
+        assert_eq!(context.get_value("main", "name"), Some(StoreValueStatic::String("Bob".to_string())));
+        assert_eq!(context.redo(), Ok(true));
+        assert_eq!(context.get_value("main", "volume"), Some(StoreValueStatic::Integer(8)));
+        assert_eq!(context.get_value("main", "name"), Some(StoreValueStatic::String("Alice".to_string())));
+        assert_eq!(context.redo(), Ok(false));
+    }
+
+    #[test]
+    fn updates_from_another_thread_should_be_applied() {
+        let mut context = DefaultContextManager::default();
+        context.track_changes();
+        context.register_global_value("player.hp".to_string(), 10);
+        let sender = context.update_sender();
+        thread::spawn(move || {
+            sender.set("player.hp", 7).unwrap();
+            sender.snapshot("enemies", vec![3i64, 4]).unwrap();
+        }).join().unwrap();
+        assert_eq!(context.get_value("main", "player.hp"), Some(StoreValueStatic::Integer(10)));
+        assert!(context.apply_updates().is_empty());
+        assert_eq!(context.get_value("main", "player.hp"), Some(StoreValueStatic::Integer(7)));
+        assert_eq!(context.get_value("main", "enemies[1]"), Some(StoreValueStatic::Integer(4)));
+        let dirty = context.dirty_keys().unwrap();
+        assert!(dirty.is_dirty("player.hp"));
+        assert!(dirty.is_dirty("enemies.count"));
+    }
+
+    #[test]
+    fn updates_sent_while_applying_should_wait_for_the_next_frame() {
+        let mut context = DefaultContextManager::default();
+        context.register_global_value("tick".to_string(), 0);
+        let sender = context.update_sender();
+        sender.set("tick", 1).unwrap();
+        let producer = thread::spawn(move || {
+            let mut tick = 2;
+            while sender.set("tick", tick).is_ok() {
+                tick += 1;
+            }
+        });
+        // Returns even though the producer never stops.
+        assert!(context.apply_updates().is_empty());
+        assert!(context.get_value("main", "tick").is_some());
+        drop(context);
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn updates_for_a_view_should_be_part_of_the_transaction() {
+        let mut context = DefaultContextManager::default();
+        context.insert_view_level_store("main".to_string(), Default::default());
+        context.register_global_value("volume".to_string(), 5);
+        let sender = context.update_sender();
+        context.begin_transaction();
+        sender.set_for_view("main", "volume", 8).unwrap();
+        sender.set("missing", 1).unwrap();
+        assert_eq!(context.apply_updates(),
+                   vec![BindingError::NoSuchProperty("missing".to_string())]);
+        assert_eq!(context.get_value("main", "volume"), Some(StoreValueStatic::Integer(8)));
+        context.rollback().unwrap();
+        assert_eq!(context.get_value("main", "volume"), Some(StoreValueStatic::Integer(5)));
+    }
+
+    #[test]
+    fn computed_property_should_be_cached_until_a_dependency_changes() {
+        let mut context = DefaultContextManager::default();
+        context.insert_view_level_store("main".to_string(), Default::default());
+        context.track_changes();
+        context.register_global_value("player.hp".to_string(), 5);
+        context.register_global_value("player.max_hp".to_string(), 20);
+        let calls = Rc::new(Cell::new(0));
+        let c = calls.clone();
+        context.register_computed("player.hp_ratio".to_string(), &["player.hp", "player.max_hp"], move |ctx| {
+            c.set(c.get() + 1);
+            ctx.get::<f64>("player.hp").unwrap() / ctx.get::<f64>("player.max_hp").unwrap()
+        }).unwrap();
+        context.register_computed("player.hp_percent".to_string(), &["player.hp_ratio"], |ctx| {
+            (ctx.get::<f64>("player.hp_ratio").unwrap() * 100.0) as i64
+        }).unwrap();
+        assert_eq!(context.get_value("main", "player.hp_percent"), Some(StoreValueStatic::Integer(25)));
+        assert_eq!(context.get_value("main", "player.hp_ratio"), Some(StoreValueStatic::Float(0.25)));
+        assert_eq!(calls.get(), 1);
+        context.clear_dirty_keys();
+        context.set_attribute("main", "player.hp", StoreValue::Integer(10)).unwrap();
+        assert!(context.dirty_keys().unwrap().is_dirty("player.hp_percent"));
+        assert_eq!(context.get_value("main", "player.hp_percent"), Some(StoreValueStatic::Integer(50)));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn computed_property_should_not_be_cached_without_change_tracking() {
+        let mut context = DefaultContextManager::default();
+        let hp = Rc::new(RefCell::new(5i64));
+        context.register_global_store("player.hp".to_string(), hp.clone());
+        context.register_computed("player.hp_double".to_string(), &["player.hp"], |ctx| {
+            ctx.get::<i64>("player.hp").unwrap() * 2
+        }).unwrap();
+        assert_eq!(context.get_value("main", "player.hp_double"), Some(StoreValueStatic::Integer(10)));
+        // Modified without going through the context
+        *hp.borrow_mut() = 7;
+        assert_eq!(context.get_value("main", "player.hp_double"), Some(StoreValueStatic::Integer(14)));
+    }
+
+    #[test]
+    fn computed_property_should_read_the_view_store() {
+        let mut context = DefaultContextManager::default();
+        context.insert_view_level_store("main".to_string(), Default::default());
+        context.register_value_for_view("main".to_string(), "zoom".to_string(), 2).unwrap();
+        context.register_global_value("zoom".to_string(), 1);
+        context.register_computed("scale".to_string(), &["zoom"], |ctx| {
+            ctx.get::<i64>("zoom").unwrap() * 10
+        }).unwrap();
+        assert_eq!(context.get_value("main", "scale"), Some(StoreValueStatic::Integer(20)));
+        assert_eq!(context.get_value("other", "scale"), Some(StoreValueStatic::Integer(10)));
+    }
+
+    #[test]
+    fn computed_property_cycles_should_be_rejected() {
+        let mut context = DefaultContextManager::default();
+        context.register_computed("a".to_string(), &["b"], |_| 1).unwrap();
+        context.register_computed("b".to_string(), &["c.d"], |_| 1).unwrap();
+        assert_eq!(context.register_computed("c".to_string(), &["a"], |_| 1),
+                   Err(BindingError::ComputedCycle("c".to_string())));
+        assert_eq!(context.register_computed("e".to_string(), &["e"], |_| 1),
+                   Err(BindingError::ComputedCycle("e".to_string())));
+        assert_eq!(context.register_computed("c".to_string(), &["f"], |_| 1), Ok(()));
+    }
+
+    #[test]
+    fn notify_should_mark_key_as_dirty() {
+        let mut context = DefaultContextManager::default();
+        context.track_changes();
+        context.notify("player");
+        assert!(context.dirty_keys().unwrap().is_dirty("player.hp"));
+    }
 }
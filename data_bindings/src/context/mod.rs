@@ -1,9 +1,19 @@
 pub use self::manager::ContextManager;
 pub use self::manager::ViewContext;
 pub use self::manager::ViewContextMut;
+pub use self::changes::DirtyKeys;
+pub use self::proxies::{Proxy, RepeatProxy};
+pub use self::history::{Change, History};
+pub use self::updates::{Update, UpdateSender};
+pub use self::scope::ScopedContext;
 
 mod manager;
-//mod proxies;
+mod changes;
+mod proxies;
+mod history;
+mod updates;
+mod computed;
+mod scope;
 
 //use mopa;
 use std::collections::HashMap;
@@ -56,20 +66,16 @@ pub struct AmbientModel {
 impl Store for AmbientModel {
 
     fn get_attribute<'a>(&'a self, k: PropertyAccessor) -> AttributeGetResult<'a> {
-        let value = self.stores.get_attribute(k.clone());
-        if value.is_found() {
-            value
-        } else {
-            self.values.get_attribute(k)
+        match self.stores.get_attribute(k.clone()) {
+            AttributeGetResult::NoSuchProperty => self.values.get_attribute(k),
+            value => value,
         }
     }
 
     fn get_attribute_mut<'a>(&'a mut self, k: PropertyAccessor) -> AttributeMutResult<'a> {
-        let value = self.stores.get_attribute_mut(k.clone());
-        if value.is_found() {
-            value
-        } else {
-            self.values.get_attribute_mut(k)
+        match self.stores.get_attribute_mut(k.clone()) {
+            AttributeMutResult::NoSuchProperty => self.values.get_attribute_mut(k),
+            value => value,
         }
     }
 
@@ -78,7 +84,35 @@ impl Store for AmbientModel {
             AttributeSetResult::NoSuchProperty(v) => {
                 self.values.set_attribute(k, v)
             }
-            _ => AttributeSetResult::Stored
+            result => result
+        }
+    }
+
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        let mut found = false;
+        self.stores.with_attribute(k.clone(), &mut |result| match result {
+            AttributeGetResult::NoSuchProperty => (),
+            value => {
+                found = true;
+                f(value)
+            }
+        });
+        if !found {
+            self.values.with_attribute(k, f)
+        }
+    }
+
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        let mut found = false;
+        self.stores.with_attribute_mut(k.clone(), &mut |result| match result {
+            AttributeMutResult::NoSuchProperty => (),
+            value => {
+                found = true;
+                f(value)
+            }
+        });
+        if !found {
+            self.values.with_attribute_mut(k, f)
         }
     }
 }
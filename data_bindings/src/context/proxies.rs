@@ -1,91 +1,156 @@
-use std::rc::{Rc,Weak};
+use std::rc::{Rc, Weak};
 use std::cell::RefCell;
-use std::ops::Deref;
-use data_bindings::{
-    BindingResult,
-    DataBindingError,
-    StoreValue,
-    IsRepeatable,
+use {
     Store,
-    ArrStore,
-    IteratingClosure,
+    StoreValue,
     PropertyAccessor,
+    AttributeGetResult,
+    AttributeMutResult,
     AttributeSetResult
 };
 
-
+/// A `Proxy` gives access to a store shared with the application
+/// without keeping it alive.
+///
+/// Once the store has been dropped, lookups return `DanglingReference`
+/// so that oil can report the binding instead of failing.
 #[derive(Debug)]
 pub struct Proxy<T: Store> {
     data: Weak<RefCell<T>>,
 }
 
-impl <T> Store for Proxy<T>
-where T: Store {
+impl<T: Store> Proxy<T> {
+    pub fn new(value: &Rc<RefCell<T>>) -> Proxy<T> {
+        Proxy {
+            data: Rc::downgrade(value),
+        }
+    }
+
+    /// Returns true if the proxied store has been dropped.
+    pub fn is_dangling(&self) -> bool {
+        self.data.upgrade().is_none()
+    }
+}
+
+impl<T> Store for Proxy<T>
+    where T: Store
+{
     fn get_attribute(&self, k: PropertyAccessor) -> AttributeGetResult {
         match self.data.upgrade() {
-            None => NoSuchProperty,
-            Some(p) => p.borrow().get_attribute(k),
+            None => AttributeGetResult::DanglingReference,
+            Some(p) => {
+                let result = p.get_attribute(k).into_owned();
+                result
+            }
         }
     }
 
-    fn set_attribute(&mut self, k: PropertyAccessor, value: StoreValue) -> AttributeSetResult {
+    fn get_attribute_mut(&mut self, _: PropertyAccessor) -> AttributeMutResult {
+        // The store is shared, a mutable access can't outlive
+        // the borrow: `with_attribute_mut` gives one.
         match self.data.upgrade() {
-            None => NoSuchProperty(value),
-            Some(p) => p.borrow_mut().set_attribute(k, value),
+            None => AttributeMutResult::DanglingReference,
+            Some(_) => AttributeMutResult::Shared,
         }
     }
-}
 
-impl <T: Store> Proxy<T> {
-    pub fn new(value: &Rc<RefCell<T>>) -> Proxy<T> {
-        Proxy {
-            data: value.downgrade(),
+    fn set_attribute<'a>(&mut self, k: PropertyAccessor, value: StoreValue<'a>) -> AttributeSetResult<'a> {
+        match self.data.upgrade() {
+            None => AttributeSetResult::DanglingReference,
+            Some(mut p) => p.set_attribute(k, value),
         }
     }
-}
 
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        match self.data.upgrade() {
+            None => f(AttributeGetResult::DanglingReference),
+            Some(p) => p.with_attribute(k, f),
+        }
+    }
 
-pub struct RepeatProxy<T> {
-    weak_values: Weak<RefCell<Vec<T>>>,
-}
-
-impl <T> RepeatProxy<T> {
-    pub fn new(values: &Rc<RefCell<Vec<T>>>) -> RepeatProxy<T> {
-        RepeatProxy {
-            weak_values: values.downgrade(),
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        match self.data.upgrade() {
+            None => f(AttributeMutResult::DanglingReference),
+            Some(mut p) => p.with_attribute_mut(k, f),
         }
     }
 }
 
-impl <T> IsRepeatable for RepeatProxy<T>
-where T: Store + ArrStore + 'static {
-
-    fn iter(&self, closure: &mut IteratingClosure) -> bool {
-        let ref_values = match self.weak_values.upgrade() {
-            Some(r) => r,
-            None => return false,
-        };
-        let mut values = ref_values.borrow_mut();
-        let mut iter = values.iter_mut().map(|item| item as &mut Store);
-        closure(&mut iter);
-        true
+/// A `Proxy` to a list shared with the application, such as
+/// the items of a `repeat` tag.
+///
+/// The items are iterated with `with_attribute`, while the list
+/// is borrowed.
+pub type RepeatProxy<T> = Proxy<Vec<T>>;
+
+// ======================================== //
+//                   TESTS                  //
+// ======================================== //
+
+#[cfg(test)]
+mod test {
+
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use super::{Proxy, RepeatProxy};
+    use {Store, StoreValue, PropertyAccessor};
+    use {AttributeGetResult, AttributeMutResult, AttributeSetResult};
+
+    #[test]
+    fn proxy_should_read_and_write_shared_store() {
+        let shared = Rc::new(RefCell::new(vec![1, 2]));
+        let mut proxy = Proxy::new(&shared);
+        assert_eq!(proxy.get_attribute(PropertyAccessor::new("1")).value(),
+                   Some(2.into()));
+        assert!(proxy.set_attribute(PropertyAccessor::new("0"), StoreValue::Integer(5)).is_stored());
+        assert_eq!(shared.borrow()[0], 5);
     }
 
-    fn compare_and_update(&self, k: &str, output: &mut Vec<StoreValue>) -> BindingResult<bool> {
-        let ref_values = match self.weak_values.upgrade() {
-            Some(r) => r,
-            None => return Err(DataBindingError::DanglingReference(format!(": {}", k))),
-        };
-        let values = ref_values.borrow_mut();
-        ArrStore::compare_and_update((*values).deref(), k, output)
+    #[test]
+    fn proxy_should_give_a_mutable_access_while_shared() {
+        let shared = Rc::new(RefCell::new(vec![1, 2]));
+        let mut proxy = Proxy::new(&shared);
+        let mut found = false;
+        proxy.with_attribute_mut(PropertyAccessor::new("1"), &mut |result| {
+            if let AttributeMutResult::PrimitiveType(value) = result {
+                value.assign(StoreValue::Integer(7));
+                found = true;
+            }
+        });
+        assert!(found);
+        assert_eq!(shared.borrow()[1], 7);
     }
 
-    fn len(&self) -> BindingResult<u32> {
-        let ref_values = match self.weak_values.upgrade() {
-            Some(r) => r,
-            None => return Err(DataBindingError::DanglingReference("".to_string())),
-        };
-        let values = ref_values.borrow();
-        Ok((*values).len() as u32)
+    #[test]
+    fn repeat_proxy_should_iterate_the_items() {
+        let shared = Rc::new(RefCell::new(vec![1, 2, 3]));
+        let proxy = RepeatProxy::new(&shared);
+        match proxy.get_attribute(PropertyAccessor::new("")) {
+            AttributeGetResult::SharedIterableType => (),
+            _ => panic!(),
+        }
+        let mut values = Vec::new();
+        proxy.with_attribute(PropertyAccessor::new(""), &mut |result| {
+            for item in result.unwrap_iter() {
+                values.extend(item.get_attribute(PropertyAccessor::new("")).value());
+            }
+        });
+        assert_eq!(values, vec![1.into(), 2.into(), 3.into()]);
+    }
+
+    #[test]
+    fn dropped_store_should_be_reported() {
+        let shared = Rc::new(RefCell::new(vec![1, 2]));
+        let mut proxy = Proxy::new(&shared);
+        drop(shared);
+        assert!(proxy.is_dangling());
+        match proxy.get_attribute(PropertyAccessor::new("0")) {
+            AttributeGetResult::DanglingReference => (),
+            _ => panic!(),
+        }
+        match proxy.set_attribute(PropertyAccessor::new("0"), StoreValue::Integer(5)) {
+            AttributeSetResult::DanglingReference => (),
+            _ => panic!(),
+        }
     }
 }
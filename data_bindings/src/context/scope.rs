@@ -0,0 +1,247 @@
+use {Store, AttributeGetResult};
+use lookup::PropertyAccessor;
+use store::StoreValueStatic;
+use super::ViewContext;
+
+/// The context of a binding inside a repeated template.
+///
+/// A key is looked up in the current item first, then in the items
+/// of the enclosing `repeat` tags, then in the view and global stores.
+/// The following keys are also available:
+///
+///  * `$index`: the position of the current item, starting at 0.
+///  * `$first` and `$last`: true for the first and last items.
+///  * `$parent.xxx`: looks up `xxx` from the enclosing repeat scope,
+///    skipping the current item.
+pub struct ScopedContext<'a> {
+    view: &'a ViewContext<'a>,
+    parent: Option<&'a ScopedContext<'a>>,
+    scope: Option<RepeatScope<'a>>,
+}
+
+struct RepeatScope<'a> {
+    item: &'a Store,
+    // The prefix of the keys of the item, if it's named.
+    name: Option<&'a str>,
+    index: usize,
+    len: usize,
+}
+
+const PARENT_PREFIX: &'static str = "$parent.";
+
+impl<'a> ScopedContext<'a> {
+
+    /// The root scope: keys are looked up in the view context.
+    pub fn new(view: &'a ViewContext<'a>) -> ScopedContext<'a> {
+        ScopedContext {
+            view: view,
+            parent: None,
+            scope: None,
+        }
+    }
+
+    /// Returns the scope of the item at `index`, in a list of `len` items.
+    pub fn child(&'a self, item: &'a Store, index: usize, len: usize) -> ScopedContext<'a> {
+        ScopedContext {
+            view: self.view,
+            parent: Some(self),
+            scope: Some(RepeatScope {
+                item: item,
+                name: None,
+                index: index,
+                len: len,
+            }),
+        }
+    }
+
+    /// Same as `child` but the keys of the item are prefixed with `name`,
+    /// such as `item.price`. Other keys are looked up in the parent scope.
+    pub fn named_child(&'a self, item: &'a Store, index: usize, len: usize, name: &'a str)
+        -> ScopedContext<'a>
+    {
+        ScopedContext {
+            view: self.view,
+            parent: Some(self),
+            scope: Some(RepeatScope {
+                item: item,
+                name: Some(name),
+                index: index,
+                len: len,
+            }),
+        }
+    }
+
+    pub fn lookup(&'a self, property_path: &str) -> AttributeGetResult<'a> {
+        let scope = match self.scope {
+            Some(ref scope) => scope,
+            None => return self.view.lookup(property_path),
+        };
+        let parent = self.parent.expect("A repeat scope always has a parent");
+        if property_path.starts_with(PARENT_PREFIX) {
+            return parent.lookup(&property_path[PARENT_PREFIX.len()..]);
+        }
+        let value = match property_path {
+            "$index" => StoreValueStatic::Integer(scope.index as i64),
+            "$first" => StoreValueStatic::Boolean(scope.index == 0),
+            "$last" => StoreValueStatic::Boolean(scope.index + 1 == scope.len),
+            _ => {
+                let item_path = match scope.name {
+                    Some(name) => strip_name(property_path, name),
+                    None => Some(property_path),
+                };
+                let result = match item_path {
+                    Some(path) => scope.item.get_attribute(PropertyAccessor::new(path)),
+                    None => AttributeGetResult::NoSuchProperty,
+                };
+                match result {
+                    AttributeGetResult::NoSuchProperty => return parent.lookup(property_path),
+                    result => return result,
+                }
+            }
+        };
+        AttributeGetResult::OwnedPrimitiveType(value)
+    }
+
+    /// Same as `lookup`, but the result is given to `f` and can borrow
+    /// from a shared store, see `ViewContext::with_lookup`.
+    pub fn with_lookup(&self, property_path: &str, f: &mut FnMut(AttributeGetResult)) {
+        let scope = match self.scope {
+            Some(ref scope) => scope,
+            None => return self.view.with_lookup(property_path, f),
+        };
+        let parent = self.parent.expect("A repeat scope always has a parent");
+        if property_path.starts_with(PARENT_PREFIX) {
+            return parent.with_lookup(&property_path[PARENT_PREFIX.len()..], f);
+        }
+        let value = match property_path {
+            "$index" => StoreValueStatic::Integer(scope.index as i64),
+            "$first" => StoreValueStatic::Boolean(scope.index == 0),
+            "$last" => StoreValueStatic::Boolean(scope.index + 1 == scope.len),
+            _ => {
+                let item_path = match scope.name {
+                    Some(name) => strip_name(property_path, name),
+                    None => Some(property_path),
+                };
+                let mut found = false;
+                if let Some(path) = item_path {
+                    scope.item.with_attribute(PropertyAccessor::new(path), &mut |result| match result {
+                        AttributeGetResult::NoSuchProperty => (),
+                        result => {
+                            found = true;
+                            f(result)
+                        }
+                    });
+                }
+                if !found {
+                    parent.with_lookup(property_path, f);
+                }
+                return;
+            }
+        };
+        f(AttributeGetResult::OwnedPrimitiveType(value))
+    }
+
+    /// Returns a copy of the value, see `lookup`.
+    pub fn get_value(&self, property_path: &str) -> Option<StoreValueStatic> {
+        self.lookup(property_path).value()
+    }
+}
+
+/// Returns the rest of `property_path` if it starts with `name.`.
+fn strip_name<'b>(property_path: &'b str, name: &str) -> Option<&'b str> {
+    if property_path.len() > name.len() + 1
+        && property_path.starts_with(name)
+        && property_path[name.len()..].starts_with('.')
+    {
+        Some(&property_path[name.len() + 1..])
+    } else {
+        None
+    }
+}
+
+// ======================================== //
+//                   TESTS                  //
+// ======================================== //
+
+#[cfg(test)]
+mod test {
+
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use super::ScopedContext;
+    use DataBindingsContext;
+    use context::DefaultContextManager;
+    use store::{DynamicValue, StoreValueStatic};
+
+    fn context() -> DefaultContextManager {
+        let mut context = DefaultContextManager::default();
+        let guild = DynamicValue::from_json(r#"{
+            "name": "Rangers",
+            "parties": [
+                { "name": "North", "members": [{ "name": "Bob" }, { "name": "Alice" }] },
+                { "name": "South", "members": [{ "name": "Eve" }] }
+            ]
+        }"#).unwrap();
+        context.register_global_store("guild".to_string(), guild);
+        context
+    }
+
+    #[test]
+    fn item_keys_should_shadow_enclosing_scopes() {
+        let context = context();
+        let view = context.get_view_context("main");
+        let root = ScopedContext::new(&view);
+        let parties: Vec<_> = root.lookup("guild.parties").unwrap_iter().collect();
+        let party = root.child(parties[1], 1, parties.len());
+        let members: Vec<_> = party.lookup("members").unwrap_iter().collect();
+        let member = party.child(members[0], 0, members.len());
+        assert_eq!(member.get_value("name"), Some(StoreValueStatic::String("Eve".to_string())));
+        assert_eq!(member.get_value("$parent.name"), Some(StoreValueStatic::String("South".to_string())));
+        assert_eq!(member.get_value("guild.name"), Some(StoreValueStatic::String("Rangers".to_string())));
+        assert_eq!(member.get_value("$parent.$index"), Some(StoreValueStatic::Integer(1)));
+        assert_eq!(root.get_value("$index"), None);
+    }
+
+    #[test]
+    fn index_first_and_last_should_be_set() {
+        let context = context();
+        let view = context.get_view_context("main");
+        let root = ScopedContext::new(&view);
+        let parties: Vec<_> = root.lookup("guild.parties").unwrap_iter().collect();
+        let first = root.child(parties[0], 0, 2);
+        let last = root.child(parties[1], 1, 2);
+        assert_eq!(first.get_value("$index"), Some(StoreValueStatic::Integer(0)));
+        assert_eq!(first.get_value("$first"), Some(StoreValueStatic::Boolean(true)));
+        assert_eq!(first.get_value("$last"), Some(StoreValueStatic::Boolean(false)));
+        assert_eq!(last.get_value("$last"), Some(StoreValueStatic::Boolean(true)));
+    }
+
+    #[test]
+    fn shared_lists_should_be_iterated() {
+        let mut context = context();
+        let names = Rc::new(RefCell::new(vec!["Bob".to_string(), "Alice".to_string()]));
+        context.register_global_store("names".to_string(), names.clone());
+        let view = context.get_view_context("main");
+        let root = ScopedContext::new(&view);
+        let mut found = Vec::new();
+        root.with_lookup("names", &mut |result| {
+            let items: Vec<_> = result.unwrap_iter().collect();
+            for (i, item) in items.iter().enumerate() {
+                found.extend(root.child(*item, i, items.len()).get_value("$index"));
+            }
+        });
+        assert_eq!(found, vec![StoreValueStatic::Integer(0), StoreValueStatic::Integer(1)]);
+    }
+
+    #[test]
+    fn named_item_keys_should_be_prefixed() {
+        let context = context();
+        let view = context.get_view_context("main");
+        let root = ScopedContext::new(&view);
+        let parties: Vec<_> = root.lookup("guild.parties").unwrap_iter().collect();
+        let party = root.named_child(parties[0], 0, parties.len(), "item");
+        assert_eq!(party.get_value("item.name"), Some(StoreValueStatic::String("North".to_string())));
+        assert_eq!(party.get_value("guild.name"), Some(StoreValueStatic::String("Rangers".to_string())));
+        assert_eq!(party.get_value("name"), None);
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::mpsc::{self, Sender, Receiver, SendError, TryRecvError};
+
+use Store;
+use store::StoreValueStatic;
+use super::Context;
+
+/// An update sent from another thread,
+/// applied by the `ContextManager` at the next frame.
+pub enum Update {
+    /// Write a value with `ContextManager::set_attribute`.
+    /// Without a view, only the global store is written,
+    /// and the pending transaction doesn't record it.
+    Value {
+        view: Option<String>,
+        key: String,
+        value: StoreValueStatic,
+    },
+    /// Replace a global store by a copy of it.
+    Snapshot {
+        name: String,
+        store: Box<Store + Send>,
+    },
+}
+
+/// What goes through the channel. The end of a frame is only sent
+/// by the `UpdateQueue`, to know where the pending updates stop.
+enum Message {
+    Update(Update),
+    EndOfFrame,
+}
+
+/// The sending half of the update queue of a `ContextManager`.
+/// It can be cloned and moved to a simulation thread.
+///
+/// Every method fails once the `ContextManager` has been dropped.
+#[derive(Clone)]
+pub struct UpdateSender {
+    sender: Sender<Message>,
+}
+
+impl UpdateSender {
+
+    /// Write a value of the global store.
+    pub fn set<V: Into<StoreValueStatic>>(&self, key: &str, value: V)
+        -> Result<(), SendError<Update>>
+    {
+        self.send(Update::Value {
+            view: None,
+            key: key.to_string(),
+            value: value.into(),
+        })
+    }
+
+    /// Write a value, looking in the store of the view first.
+    /// As with `ContextManager::set_attribute`, the view must have a store.
+    pub fn set_for_view<V: Into<StoreValueStatic>>(&self, view: &str, key: &str, value: V)
+        -> Result<(), SendError<Update>>
+    {
+        self.send(Update::Value {
+            view: Some(view.to_string()),
+            key: key.to_string(),
+            value: value.into(),
+        })
+    }
+
+    /// Replace the global store `name`. The bindings starting
+    /// with `name` are all refreshed.
+    pub fn snapshot<S: Store + Send>(&self, name: &str, store: S)
+        -> Result<(), SendError<Update>>
+    {
+        self.send(Update::Snapshot {
+            name: name.to_string(),
+            store: Box::new(store),
+        })
+    }
+
+    pub fn send(&self, update: Update) -> Result<(), SendError<Update>> {
+        match self.sender.send(Message::Update(update)) {
+            Ok(()) => Ok(()),
+            Err(SendError(Message::Update(update))) => Err(SendError(update)),
+            Err(SendError(Message::EndOfFrame)) => unreachable!(),
+        }
+    }
+}
+
+/// The receiving half, owned by the `ContextManager`.
+///
+/// Registering a snapshot requires `G: Context`, which isn't required
+/// by the rest of the `ContextManager`, so the function doing it is
+/// picked when the queue is created.
+pub struct UpdateQueue<G> {
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+    register: fn(&mut G, String, Box<Store + Send>),
+}
+
+impl<G: Context> UpdateQueue<G> {
+
+    pub fn new() -> UpdateQueue<G> {
+        let (sender, receiver) = mpsc::channel();
+        UpdateQueue {
+            sender: sender,
+            receiver: receiver,
+            register: register_snapshot::<G>,
+        }
+    }
+}
+
+impl<G> UpdateQueue<G> {
+
+    pub fn sender(&self) -> UpdateSender {
+        UpdateSender { sender: self.sender.clone() }
+    }
+
+    /// Returns the updates sent before this call. The ones sent
+    /// meanwhile are left for the next call: a thread sending
+    /// updates continuously can't hold up a frame.
+    pub fn pending(&self) -> Vec<Update> {
+        // The queue holds a sender, so this can't fail.
+        let _ = self.sender.send(Message::EndOfFrame);
+        let mut updates = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Message::Update(update)) => updates.push(update),
+                Ok(Message::EndOfFrame) |
+                Err(TryRecvError::Empty) |
+                Err(TryRecvError::Disconnected) => return updates,
+            }
+        }
+    }
+
+    pub fn register(&self, global: &mut G, name: String, store: Box<Store + Send>) {
+        (self.register)(global, name, store)
+    }
+}
+
+// ======================================== //
+//                  HELPERS                 //
+// ======================================== //
+
+fn register_snapshot<G: Context>(global: &mut G, name: String, store: Box<Store + Send>) {
+    global.register_store(name, store);
+}
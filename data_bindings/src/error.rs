@@ -0,0 +1,90 @@
+use std::error::Error;
+use std::fmt;
+
+/// Error returned by the `ContextManager` when a binding
+/// can't be resolved. Each variant holds the key or the view
+/// name that caused the error.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BindingError {
+    /// No store has been inserted for that view.
+    UnknownView(String),
+    /// No store has such a property.
+    NoSuchProperty(String),
+    /// The property exists but the value couldn't be cast to its type.
+    WrongType(String),
+    /// The property is held by a store that has been dropped.
+    DanglingReference(String),
+    /// The property is held by a shared store that can't be modified,
+    /// see `AttributeSetResult::Shared`. The `WrongTypePolicy`
+    /// doesn't apply to this error.
+    SharedStore(String),
+    /// The computed property would depend on itself.
+    ComputedCycle(String),
+}
+
+pub type BindingResult<T> = Result<T, BindingError>;
+
+/// What the `ContextManager` does when a value
+/// of the wrong type is assigned to a property.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WrongTypePolicy {
+    /// Return `BindingError::WrongType`. This is the default.
+    Error,
+    /// Print a warning and succeed.
+    Log,
+    /// Silently succeed.
+    Ignore,
+    /// Panic, useful to catch mistakes early in debug builds.
+    Panic,
+}
+
+impl Default for WrongTypePolicy {
+    fn default() -> WrongTypePolicy {
+        WrongTypePolicy::Error
+    }
+}
+
+impl WrongTypePolicy {
+
+    /// Apply the policy for the given key.
+    pub fn apply(&self, key: &str) -> BindingResult<()> {
+        match *self {
+            WrongTypePolicy::Error => Err(BindingError::WrongType(key.to_string())),
+            WrongTypePolicy::Log => {
+                println!("WARNING: Wrong type assigned to `{}`", key);
+                Ok(())
+            }
+            WrongTypePolicy::Ignore => Ok(()),
+            WrongTypePolicy::Panic => panic!("Wrong type assigned to `{}`", key),
+        }
+    }
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BindingError::UnknownView(ref v) => write!(f, "Unknown view `{}`", v),
+            BindingError::NoSuchProperty(ref k) => write!(f, "No such property `{}`", k),
+            BindingError::WrongType(ref k) => write!(f, "Wrong type assigned to `{}`", k),
+            BindingError::DanglingReference(ref k) =>
+                write!(f, "The store holding `{}` has been dropped", k),
+            BindingError::SharedStore(ref k) =>
+                write!(f, "The store holding `{}` is shared and can't be modified", k),
+            BindingError::ComputedCycle(ref k) =>
+                write!(f, "The computed property `{}` depends on itself", k),
+        }
+    }
+}
+
+impl Error for BindingError {
+    fn description(&self) -> &str {
+        match *self {
+            BindingError::UnknownView(_) => "unknown view",
+            BindingError::NoSuchProperty(_) => "no such property",
+            BindingError::WrongType(_) => "wrong type",
+            BindingError::DanglingReference(_) => "dangling reference",
+            BindingError::SharedStore(_) => "shared store",
+            BindingError::ComputedCycle(_) => "computed cycle",
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use store::StoreValueStatic;
+
+/// A filter transforms the value on its left in a binding such
+/// as `{{player.gold | thousands}}`. It receives the value and the
+/// evaluated arguments given between parenthesis, if any.
+pub type Filter = Box<Fn(&StoreValueStatic, &[StoreValueStatic]) -> StoreValueStatic>;
+
+/// Registry of the filters defined by the application.
+///
+/// Filters registered here take precedence over the built-in ones:
+///
+///  * `thousands(sep = ",")` groups the digits of the integer part.
+///  * `percent(decimals = 0)` multiplies by 100 and appends `%`.
+///  * `round(decimals = 0)` rounds a number.
+///  * `upper` and `lower` change the case of a string.
+///  * `default(value)` replaces `Null` and empty strings.
+#[derive(Default)]
+pub struct Filters {
+    filters: HashMap<String, Filter>,
+}
+
+impl Filters {
+
+    pub fn new() -> Filters {
+        Filters::default()
+    }
+
+    /// Register a filter under the given name.
+    /// A previous filter with the same name is replaced.
+    pub fn register<F>(&mut self, name: String, filter: F)
+        where F: Fn(&StoreValueStatic, &[StoreValueStatic]) -> StoreValueStatic + 'static
+    {
+        self.filters.insert(name, Box::new(filter) as Filter);
+    }
+
+    /// Apply the filter named `name`. Returns `None` if no
+    /// filter, registered or built-in, has such a name.
+    pub fn apply(&self, name: &str, value: &StoreValueStatic, args: &[StoreValueStatic])
+        -> Option<StoreValueStatic>
+    {
+        match self.filters.get(name) {
+            Some(filter) => Some(filter(value, args)),
+            None => apply_builtin(name, value, args),
+        }
+    }
+}
+
+/// Apply one of the built-in filters listed in `Filters`.
+pub fn apply_builtin(name: &str, value: &StoreValueStatic, args: &[StoreValueStatic])
+    -> Option<StoreValueStatic>
+{
+    match name {
+        "thousands" => {
+            let sep = match args.first() {
+                Some(&StoreValueStatic::String(ref s)) => s.clone(),
+                _ => ",".to_string(),
+            };
+            Some(match *value {
+                StoreValueStatic::Integer(i) => StoreValueStatic::String(
+                    group_digits(&i.to_string(), &sep)),
+                StoreValueStatic::Float(f) => StoreValueStatic::String(
+                    group_digits(&f.to_string(), &sep)),
+                ref other => other.clone(),
+            })
+        }
+        "percent" => {
+            let decimals = decimals_arg(args);
+            Some(match as_f64(value) {
+                Some(f) => StoreValueStatic::String(format!("{:.*}%", decimals, f * 100f64)),
+                None => StoreValueStatic::Null,
+            })
+        }
+        "round" => {
+            let decimals = decimals_arg(args);
+            Some(match as_f64(value) {
+                Some(f) if decimals == 0 => StoreValueStatic::Integer(f.round() as i64),
+                Some(f) => {
+                    let p = 10f64.powi(decimals as i32);
+                    StoreValueStatic::Float((f * p).round() / p)
+                }
+                None => StoreValueStatic::Null,
+            })
+        }
+        "upper" => Some(match *value {
+            StoreValueStatic::String(ref s) => StoreValueStatic::String(s.to_uppercase()),
+            ref other => other.clone(),
+        }),
+        "lower" => Some(match *value {
+            StoreValueStatic::String(ref s) => StoreValueStatic::String(s.to_lowercase()),
+            ref other => other.clone(),
+        }),
+        "default" => Some(match *value {
+            StoreValueStatic::Null => args.first().cloned().unwrap_or(StoreValueStatic::Null),
+            StoreValueStatic::String(ref s) if s.is_empty() =>
+                args.first().cloned().unwrap_or(value.clone()),
+            ref other => other.clone(),
+        }),
+        _ => None,
+    }
+}
+
+/// Returns the value of a number as a float, `None` for other values.
+pub fn as_f64(value: &StoreValueStatic) -> Option<f64> {
+    match *value {
+        StoreValueStatic::Integer(i) => Some(i as f64),
+        StoreValueStatic::Float(f) => Some(f),
+        _ => None,
+    }
+}
+
+// ======================================== //
+//                  HELPERS                 //
+// ======================================== //
+
+fn decimals_arg(args: &[StoreValueStatic]) -> usize {
+    match args.first() {
+        Some(&StoreValueStatic::Integer(i)) if i > 0 => i as usize,
+        _ => 0,
+    }
+}
+
+/// Insert `sep` every three digits in the integer part of `number`.
+fn group_digits(number: &str, sep: &str) -> String {
+    let (sign, unsigned) = if number.starts_with('-') {
+        ("-", &number[1..])
+    } else {
+        ("", number)
+    };
+    let (int_part, rest) = match unsigned.find('.') {
+        Some(i) => (&unsigned[..i], &unsigned[i..]),
+        None => (unsigned, ""),
+    };
+    let mut grouped = String::with_capacity(number.len() + int_part.len() / 3);
+    for (i, c) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push_str(sep);
+        }
+        grouped.push(c);
+    }
+    format!("{}{}{}", sign, grouped, rest)
+}
+
+// ======================================== //
+//                   TESTS                  //
+// ======================================== //
+
+#[cfg(test)]
+mod test {
+
+    use super::Filters;
+    use store::StoreValueStatic;
+
+    #[test]
+    fn thousands_should_group_digits() {
+        let f = Filters::new();
+        assert_eq!(f.apply("thousands", &StoreValueStatic::Integer(1234567), &[]),
+                   Some(StoreValueStatic::String("1,234,567".to_string())));
+        assert_eq!(f.apply("thousands", &StoreValueStatic::Integer(-123), &[]),
+                   Some(StoreValueStatic::String("-123".to_string())));
+    }
+
+    #[test]
+    fn percent_should_use_decimals_argument() {
+        let f = Filters::new();
+        assert_eq!(f.apply("percent", &StoreValueStatic::Float(0.1234), &[StoreValueStatic::Integer(1)]),
+                   Some(StoreValueStatic::String("12.3%".to_string())));
+    }
+
+    #[test]
+    fn registered_filters_should_take_precedence() {
+        let mut f = Filters::new();
+        f.register("upper".to_string(), |_, _| StoreValueStatic::Boolean(true));
+        assert_eq!(f.apply("upper", &StoreValueStatic::Null, &[]),
+                   Some(StoreValueStatic::Boolean(true)));
+        assert_eq!(f.apply("unknown", &StoreValueStatic::Null, &[]), None);
+    }
+}
@@ -1,4 +1,5 @@
 #![feature(hashmap_hasher)]
+#![feature(borrow_state)]
 #![feature(test)]
 extern crate test as bench;
 
@@ -14,12 +15,17 @@ pub use self::store::StoreValue;
 pub use self::store::Cast;
 pub use self::context::DefaultContextManager;
 pub use self::lookup::PropertyAccessor;
+pub use self::filters::Filters;
+pub use self::error::{BindingError, BindingResult, WrongTypePolicy};
 
-//mod error;
+mod error;
 pub mod context;
 pub mod store;
+pub mod filters;
 mod lookup;
 
+use store::AsStoreValue;
+
 /// Key trait to create a model that support two-ways databindings
 /// with oil.
 ///
@@ -85,8 +91,8 @@ mod lookup;
 ///
 ///     // Checking that the player is correctly registered:
 ///     {
-///         let attribute = a.get_attribute(PropertyAccessor::new("player.name")).unwrap();
-///         match attribute {
+///         let attribute = a.get_attribute(PropertyAccessor::new("player.name"));
+///         match attribute.unwrap() {
 ///             StoreValue::String(s) => println!("Hello {}!", s),
 ///             _ => unreachable!(),
 ///         }
@@ -109,13 +115,36 @@ pub trait Store: mopa::Any {
 
     /// Return the value corresponding to the key 'k' with a mutable access.
     /// If no value is found with such a name, the trait implementer
-    /// will return `AttributeMutResult::NoSuchProperty`.
+    /// will return `AttributeMutResult::NoSuchProperty`. A value that
+    /// can't be borrowed past the call gives `AttributeMutResult::Shared`.
     fn get_attribute_mut(&mut self, k: PropertyAccessor) -> AttributeMutResult;
 
     /// This method set the value for the attribute named 'k'.
     /// For consistency the lookup algorithm should be the
     /// same as with `get_attribute`.
     fn set_attribute<'a>(&mut self, k: PropertyAccessor, value: StoreValue<'a>) -> AttributeSetResult<'a>;
+
+    /// Call `f` with the value corresponding to the key `k`.
+    ///
+    /// Unlike `get_attribute`, the result can borrow from a guard
+    /// that only lives for the call, such as the one of a `RefCell`
+    /// or a `Mutex`: a list behind such a guard can be iterated.
+    /// Stores holding other stores must forward the call to them.
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        f(self.get_attribute(k))
+    }
+
+    /// Same as `with_attribute` with a mutable access.
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        f(self.get_attribute_mut(k))
+    }
+
+    /// Build a store holding `value`. This is used to assign an absent
+    /// `Option<Self>`. Value types are built with `Cast`, other
+    /// stores can't be built from a value and return `None`.
+    fn from_store_value(_: StoreValue) -> Option<Self> where Self: Sized {
+        None
+    }
 }
 
 mopafy!(Store);
@@ -129,9 +158,20 @@ pub enum AttributeGetResult<'a> {
     /// This value is returned when the get has succeeded
     /// and the value is a `IterableType`.
     IterableType(Box<Iterator<Item=&'a Store> + 'a>),
+    /// This value is returned when the get has succeeded but the
+    /// value lives behind a guard (`RefCell`, `Mutex`, ...) that can't
+    /// outlive the call. The value is then copied.
+    OwnedPrimitiveType(store::StoreValueStatic),
+    /// This value is returned when the value is a list behind such
+    /// a guard: its iterator can't outlive the call. It can be
+    /// iterated with `Store::with_attribute`.
+    SharedIterableType,
     /// This value is returned to indicate that there's no such property
     /// accessible for the given PropertyAccessor.
     NoSuchProperty,
+    /// This value is returned when the property is held by a store
+    /// behind a weak reference that has been dropped.
+    DanglingReference,
 }
 
 /// Result type when calling `get_attribute_mut` on a `Store`
@@ -144,7 +184,15 @@ pub enum AttributeMutResult<'a> {
     /// and the value is an `IterableType`.
     IterableType(Box<Iterator<Item=&'a mut Store> + 'a>),
     /// Access didn't provide any result.
-    NoSuchProperty
+    NoSuchProperty,
+    /// The property is held by a store behind a weak reference that
+    /// has been dropped. It stops the lookup.
+    DanglingReference,
+    /// The property is held by a shared store that can't be borrowed
+    /// past this call: an `Rc` or an `Arc` with other owners, a `RefCell`
+    /// or a `Mutex`. `Store::with_attribute_mut` gives a mutable access
+    /// for the duration of a call. It stops the lookup too.
+    Shared,
 }
 
 /// Result type when calling `set_attribute` on a `Store`
@@ -158,15 +206,19 @@ pub enum AttributeSetResult<'a> {
     /// managed to find an existing property but there was a type error.
     /// It is different from NoSuchProperty because as with Stored it stops
     /// the lookup.
-    /// The main difference with `Stored` is that a warning log will be reported.
-    ///
-    /// TODO(Nemikolh): It could be nice if at the oil API level
-    /// there was a way to configure whether or not the application
-    /// should panic, log or do nothing.
+    /// The main difference with `Stored` is that the `ContextManager`
+    /// reports it according to its `WrongTypePolicy`.
     WrongType,
     /// If the lookup failed, the value argument of `set_attribute` must
     /// be returned unchanged via this enum value.
-    NoSuchProperty(StoreValue<'a>)
+    NoSuchProperty(StoreValue<'a>),
+    /// The property is held by a store behind a weak reference that
+    /// has been dropped. As with `WrongType`, it stops the lookup.
+    DanglingReference,
+    /// The property is held by a shared store that can't be modified
+    /// right now: an `Rc` or an `Arc` with other owners, or a `RefCell`
+    /// that is already borrowed. It stops the lookup too.
+    Shared,
 }
 
 /// The context that is going to be used by oil. This trait is only here
@@ -176,9 +228,35 @@ pub enum AttributeSetResult<'a> {
 pub trait DataBindingsContext {
     /// Returns a context for the given view that support lookup on `Store` registered in
     /// the `ContextManager`.
-    fn get_view_context<'a>(&'a self, view_name: &String) -> self::context::ViewContext<'a>;
+    fn get_view_context<'a>(&'a self, view_name: &'a str) -> self::context::ViewContext<'a>;
     /// Identical to `get_view_context` except that you have a mutable access to the context.
-    fn get_view_context_mut<'a>(&'a mut self, view_name: &String) -> self::context::ViewContextMut<'a>;
+    fn get_view_context_mut<'a>(&'a mut self, view_name: &'a str) -> self::context::ViewContextMut<'a>;
+    /// Returns the keys modified since the last call to `clear_dirty_keys`.
+    /// `None` means that changes aren't tracked: every binding is then
+    /// queried at each update.
+    fn dirty_keys(&self) -> Option<&self::context::DirtyKeys> {
+        None
+    }
+    /// Called by oil once all the views have been updated.
+    fn clear_dirty_keys(&mut self) {
+    }
+    /// Called by oil before the views are updated, to apply the
+    /// updates sent from other threads. Returns the errors
+    /// of the updates that couldn't be applied.
+    fn apply_updates(&mut self) -> Vec<BindingError> {
+        Vec::new()
+    }
+    /// Apply the filter named `name` used in a binding expression.
+    /// Returns `None` if there's no such filter.
+    /// By default, only the built-in filters are available.
+    fn apply_filter(&self,
+                    name: &str,
+                    value: &store::StoreValueStatic,
+                    args: &[store::StoreValueStatic])
+        -> Option<store::StoreValueStatic>
+    {
+        filters::apply_builtin(name, value, args)
+    }
 }
 
 
@@ -188,13 +266,42 @@ pub trait DataBindingsContext {
 
 impl<'a> AttributeGetResult<'a> {
 
-    pub fn unwrap(self) -> StoreValue<'a> {
-        match self {
-            AttributeGetResult::PrimitiveType(s) => s,
+    /// Returns the primitive value, borrowed from the store or from
+    /// the copy held by the result. Panics if there's none.
+    pub fn unwrap(&self) -> StoreValue {
+        match *self {
+            AttributeGetResult::PrimitiveType(ref s) => s.clone(),
+            AttributeGetResult::OwnedPrimitiveType(ref s) => s.as_store_value(),
             _ => panic!(),
         }
     }
 
+    /// Returns a copy of the primitive value, borrowed or owned.
+    pub fn value(self) -> Option<store::StoreValueStatic> {
+        match self {
+            AttributeGetResult::PrimitiveType(s) => store::StoreValueStatic::cast(s),
+            AttributeGetResult::OwnedPrimitiveType(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Detach the result from the store it was borrowed from.
+    /// Primitive values are copied, but iterators can't outlive
+    /// the store and are turned into `SharedIterableType`.
+    pub fn into_owned<'b>(self) -> AttributeGetResult<'b> {
+        match self {
+            AttributeGetResult::PrimitiveType(s) => match store::StoreValueStatic::cast(s) {
+                Some(v) => AttributeGetResult::OwnedPrimitiveType(v),
+                None => AttributeGetResult::NoSuchProperty,
+            },
+            AttributeGetResult::OwnedPrimitiveType(s) => AttributeGetResult::OwnedPrimitiveType(s),
+            AttributeGetResult::IterableType(_) |
+            AttributeGetResult::SharedIterableType => AttributeGetResult::SharedIterableType,
+            AttributeGetResult::DanglingReference => AttributeGetResult::DanglingReference,
+            AttributeGetResult::NoSuchProperty => AttributeGetResult::NoSuchProperty,
+        }
+    }
+
     pub fn unwrap_iter(self) -> Box<Iterator<Item=&'a Store> + 'a> {
         match self {
             AttributeGetResult::IterableType(it) => it,
@@ -205,12 +312,23 @@ impl<'a> AttributeGetResult<'a> {
     pub fn is_found(&self) -> bool {
         match self {
             &AttributeGetResult::PrimitiveType(_) => true,
+            &AttributeGetResult::OwnedPrimitiveType(_) => true,
             &AttributeGetResult::IterableType(_) => true,
             _ => false
         }
     }
 }
 
+impl<'a> AttributeSetResult<'a> {
+
+    pub fn is_stored(&self) -> bool {
+        match self {
+            &AttributeSetResult::Stored => true,
+            _ => false
+        }
+    }
+}
+
 impl<'a> AttributeMutResult<'a> {
 
     pub fn unwrap_iter(self) -> Box<Iterator<Item=&'a mut Store> + 'a> {
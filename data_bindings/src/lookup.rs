@@ -1,4 +1,5 @@
-/// A property accessor represent
+/// A property accessor represent a path to a property
+/// inside a `Store`.
 ///
 #[derive(Clone, Debug)]
 pub struct PropertyAccessor<'a> {
@@ -14,6 +15,13 @@ impl<'a> PropertyAccessor<'a> {
     ///     <name1>.<name2> [...] .<nameN>
     /// ```
     ///
+    /// A name can also be given between brackets, optionally quoted,
+    /// which allows indices and keys containing a `.`:
+    /// ```txt
+    ///     party.members[0].name
+    ///     stats["str"]
+    /// ```
+    /// `members[0]` and `members.0` are equivalent.
     pub fn new(path: &'a str) -> PropertyAccessor<'a> {
         PropertyAccessor {
             path: path,
@@ -21,20 +29,57 @@ impl<'a> PropertyAccessor<'a> {
     }
 
     /// Returns the name associated with that property accessor
-    /// or `None` if the end of the path has been reached.
+    /// or `""` if the end of the path has been reached.
     pub fn name(&self) -> &'a str {
-        self.path.find('.').map(|i| &self.path[..i]).unwrap_or(self.path)
+        if self.path.starts_with('[') {
+            split_bracket(self.path).0
+        } else {
+            let end = self.path.find(|c| c == '.' || c == '[').unwrap_or(self.path.len());
+            &self.path[..end]
+        }
     }
 
     /// Returns the next property accessor in the path.
     /// If the end is reached, then calling name on the property
-    /// accessor created with that function will return `None`.
+    /// accessor created with that function will return `""`.
     pub fn next(&self) -> PropertyAccessor<'a> {
-        let next = self.path.find('.').unwrap_or(self.path.len() - 1) + 1;
+        let end = if self.path.starts_with('[') {
+            split_bracket(self.path).1
+        } else {
+            self.path.find(|c| c == '.' || c == '[').unwrap_or(self.path.len())
+        };
+        let rest = &self.path[end..];
         PropertyAccessor {
-            path: &self.path[next..self.path.len()],
+            path: if rest.starts_with('.') { &rest[1..] } else { rest },
         }
     }
+
+    /// Returns true if the current name was given between brackets.
+    pub fn is_bracketed(&self) -> bool {
+        self.path.starts_with('[')
+    }
+}
+
+/// Split a path starting with `[` into the name between the
+/// brackets (without quotes) and the position right after the `]`.
+fn split_bracket(path: &str) -> (&str, usize) {
+    let inner = &path[1..];
+    for quote in ['"', '\''].iter() {
+        if inner.starts_with(*quote) {
+            if let Some(i) = inner[1..].find(*quote) {
+                // '[' + quote + name + quote
+                let after = i + 3;
+                let end = path[after..].find(']')
+                    .map(|j| after + j + 1)
+                    .unwrap_or(path.len());
+                return (&inner[1..i + 1], end);
+            }
+        }
+    }
+    match inner.find(']') {
+        Some(i) => (&inner[..i], i + 2),
+        None => (inner, path.len()),
+    }
 }
 
 /// This iterator generate from a PropertyAccessor
@@ -79,9 +124,22 @@ impl <'a> Iterator for PrefixKeyIter<'a> {
     fn next(&mut self) -> Option<<Self as Iterator>::Item> {
         if self.position == usize::max_value() {
             None
+        } else if self.property_full_path.starts_with('[') {
+            // A bracketed name is always a single key.
+            let property = PropertyAccessor::new(self.property_full_path);
+            self.position = usize::max_value();
+            Some((property.name(), property.next()))
         } else {
-            let offset = self.property_full_path[self.position..].find('.');
+            let offset = self.property_full_path[self.position..]
+                .find(|c| c == '.' || c == '[');
             match offset {
+                Some(i) if self.property_full_path[self.position + i..].starts_with('[') => {
+                    // Names stop at the first bracket.
+                    let prefix = &self.property_full_path[..self.position+i];
+                    let property = &self.property_full_path[self.position+i..];
+                    self.position = usize::max_value();
+                    Some((prefix, PropertyAccessor::new(property)))
+                }
                 Some(i) => {
                     let prefix = &self.property_full_path[..self.position+i];
                     let property = &self.property_full_path[self.position+i+1..];
@@ -142,4 +200,40 @@ mod test {
         assert_eq!(it.next().unwrap().name(), "bazz");
         assert_eq!(it.next().unwrap().name(), "");
     }
+
+    #[test]
+    fn brackets_should_be_equivalent_to_dots() {
+        let a = PropertyAccessor::new("party.members[0].name");
+        let b = a.next();
+        let c = b.next();
+        let d = c.next();
+        assert_eq!(a.name(), "party");
+        assert_eq!(b.name(), "members");
+        assert_eq!(c.name(), "0");
+        assert_eq!(d.name(), "name");
+        assert_eq!(d.next().name(), "");
+    }
+
+    #[test]
+    fn quoted_brackets_should_allow_dots_in_keys() {
+        let a = PropertyAccessor::new("stats[\"a.b\"].value");
+        let b = a.next();
+        assert_eq!(a.name(), "stats");
+        assert_eq!(b.name(), "a.b");
+        assert_eq!(b.next().name(), "value");
+        assert_eq!(PropertyAccessor::new("['str']").name(), "str");
+    }
+
+    #[test]
+    fn prefixkeyiter_should_stop_at_brackets() {
+        let a = PrefixKeyIter::new(PropertyAccessor::new("foo.bar[\"x.y\"].bazz"));
+        let mut prefixes = Vec::with_capacity(2);
+        let mut properties = Vec::with_capacity(2);
+        for (i, p) in a { prefixes.push(i); properties.push(p); }
+        assert_eq!(prefixes, vec!["foo", "foo.bar"]);
+        assert_eq!(properties[1].name(), "x.y");
+        let (key, rest) = PrefixKeyIter::new(PropertyAccessor::new("[\"x.y\"].z")).next().unwrap();
+        assert_eq!(key, "x.y");
+        assert_eq!(rest.name(), "z");
+    }
 }
@@ -31,6 +31,24 @@ macro_rules! declare_data_binding {
                     _ => $crate::AttributeSetResult::NoSuchProperty(value),
                 }
             }
+
+            fn with_attribute(&self, k: $crate::PropertyAccessor,
+                              f: &mut FnMut($crate::AttributeGetResult))
+            {
+                match k.name() {
+                    $(stringify!($field) => self.$field.with_attribute(k.next(), f),)*
+                    _ => f($crate::AttributeGetResult::NoSuchProperty),
+                }
+            }
+
+            fn with_attribute_mut(&mut self, k: $crate::PropertyAccessor,
+                                  f: &mut FnMut($crate::AttributeMutResult))
+            {
+                match k.name() {
+                    $(stringify!($field) => self.$field.with_attribute_mut(k.next(), f),)*
+                    _ => f($crate::AttributeMutResult::NoSuchProperty),
+                }
+            }
         }
         )
 }
@@ -77,6 +95,10 @@ macro_rules! impl_store_for_value_type_like {
                     _ => $crate::AttributeSetResult::NoSuchProperty(value)
                 }
             }
+
+            fn from_store_value(value: $crate::StoreValue) -> Option<Self> {
+                <Self as $crate::Cast>::cast(value)
+            }
         }
     )
 }
@@ -1,6 +1,7 @@
 use num::ToPrimitive;
 use StoreValue;
 use store::StoreValueStatic;
+use super::value::ToStoreFloat;
 use std::ops::Deref;
 
 /// A type that want to be converted into from a `StoreValue`
@@ -46,7 +47,9 @@ impl Cast for StoreValueStatic {
         match this {
             StoreValue::String(s) => Some(StoreValueStatic::String(s.to_string())),
             StoreValue::Integer(i) => Some(StoreValueStatic::Integer(i)),
+            StoreValue::Float(f) => Some(StoreValueStatic::Float(f)),
             StoreValue::Boolean(b) => Some(StoreValueStatic::Boolean(b)),
+            StoreValue::Null => Some(StoreValueStatic::Null),
         }
     }
 }
@@ -56,12 +59,17 @@ impl AsStoreValue for StoreValueStatic {
         match self {
             &StoreValueStatic::String(ref s) => StoreValue::String(s.deref()),
             &StoreValueStatic::Integer(i) => StoreValue::Integer(i),
+            &StoreValueStatic::Float(f) => StoreValue::Float(f),
             &StoreValueStatic::Boolean(b) => StoreValue::Boolean(b),
+            &StoreValueStatic::Null => StoreValue::Null,
         }
     }
 }
 
 /// Implementation for integers
+///
+/// A `Float` is accepted only if it has no fractional part
+/// and fits in the target type.
 macro_rules! impl_for_integer {
     ($type_ident:ident, $to_value:ident) => (
         impl Cast for $type_ident {
@@ -70,6 +78,9 @@ macro_rules! impl_for_integer {
                     StoreValue::Integer(i) => {
                         i.$to_value()
                     }
+                    StoreValue::Float(f) if f.fract() == 0f64 => {
+                        f.$to_value()
+                    }
                     _ => None
                 }
             }
@@ -91,6 +102,35 @@ impl_for_integer!(u32, to_u32);
 impl_for_integer!(u16, to_u16);
 impl_for_integer!(u8,  to_u8);
 
+/// Implementation for floats
+///
+/// An `Integer` is always accepted, possibly with a loss of precision.
+macro_rules! impl_for_float {
+    ($type_ident:ident, $to_value:ident) => (
+        impl Cast for $type_ident {
+            fn cast(this: StoreValue) -> Option<Self> {
+                match this {
+                    StoreValue::Float(f) => {
+                        f.$to_value()
+                    }
+                    StoreValue::Integer(i) => {
+                        i.$to_value()
+                    }
+                    _ => None
+                }
+            }
+        }
+        impl AsStoreValue for $type_ident {
+            fn as_store_value(&self) -> StoreValue {
+                StoreValue::Float(self.to_store_float())
+            }
+        }
+    )
+}
+
+impl_for_float!(f64, to_f64);
+impl_for_float!(f32, to_f32);
+
 impl Cast for String {
     fn cast(this: StoreValue) -> Option<Self> {
         match this {
@@ -100,9 +140,15 @@ impl Cast for String {
             StoreValue::Integer(i) => {
                 Some(i.to_string())
             }
+            StoreValue::Float(f) => {
+                Some(f.to_string())
+            }
             StoreValue::Boolean(b) => {
                 Some(b.to_string())
             }
+            StoreValue::Null => {
+                Some(String::new())
+            }
         }
     }
 }
@@ -124,6 +170,12 @@ impl Cast for bool {
             },
             StoreValue::Integer(i) => {
                 Some(i != 0)
+            },
+            StoreValue::Float(f) => {
+                Some(f != 0f64)
+            },
+            StoreValue::Null => {
+                Some(false)
             }
         }
     }
@@ -134,3 +186,27 @@ impl AsStoreValue for bool {
         StoreValue::Boolean(*self)
     }
 }
+
+/// `Null` is cast into `None`, any other value is cast
+/// using the rules of `T`.
+impl<T> Cast for Option<T>
+    where T: Cast
+{
+    fn cast(this: StoreValue) -> Option<Self> {
+        match this {
+            StoreValue::Null => Some(None),
+            other => <T as Cast>::cast(other).map(Some),
+        }
+    }
+}
+
+impl<T> AsStoreValue for Option<T>
+    where T: AsStoreValue
+{
+    fn as_store_value(&self) -> StoreValue {
+        match *self {
+            Some(ref v) => v.as_store_value(),
+            None => StoreValue::Null,
+        }
+    }
+}
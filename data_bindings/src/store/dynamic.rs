@@ -0,0 +1,677 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use lookup::PropertyAccessor;
+use store::cast::AsStoreValue;
+use {
+    Cast,
+    Store,
+    StoreValue,
+    AttributeGetResult,
+    AttributeMutResult,
+    AttributeSetResult,
+};
+
+/// A value tree that doesn't need a Rust type to be declared.
+///
+/// It is typically loaded from a JSON or RON document to mock up
+/// screens before the gameplay code exists:
+///
+/// ```rust
+/// # use oil_databindings::store::DynamicValue;
+/// # use oil_databindings::{Store, StoreValue, PropertyAccessor};
+/// let party = DynamicValue::from_json(r#"{
+///     "members": [{ "name": "Bob", "hp": 30 }]
+/// }"#).unwrap();
+/// assert_eq!(party.get_attribute(PropertyAccessor::new("members[0].name")).unwrap(),
+///            StoreValue::String("Bob"));
+/// ```
+///
+/// Paths map directly onto the tree: arrays behave like a `Vec`
+/// (`members.count`, `members[0]`) and objects like a `HashMap`.
+/// `null` behaves like `None` and resolves to `StoreValue::Null`
+/// whatever the remaining path is.
+///
+/// Setting a scalar replaces it with the new value, whatever its type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DynamicValue {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<DynamicValue>),
+    Map(HashMap<String, DynamicValue>),
+}
+
+/// Error returned when a document can't be loaded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    row: usize,
+    col: usize,
+    msg: String,
+}
+
+impl DynamicValue {
+
+    /// Load a JSON document.
+    pub fn from_json(text: &str) -> Result<DynamicValue, ParseError> {
+        Parser::new(text, Syntax::Json).parse_document()
+    }
+
+    /// Load a RON document. Structs are loaded as maps and
+    /// tuples as arrays; their names are ignored. `Some(v)` is `v`,
+    /// `None` is `null` and unit variants such as `Warrior`
+    /// are loaded as strings.
+    pub fn from_ron(text: &str) -> Result<DynamicValue, ParseError> {
+        Parser::new(text, Syntax::Ron).parse_document()
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.row + 1, self.col + 1, self.msg)
+    }
+}
+
+impl Error for ParseError {
+    fn description(&self) -> &str {
+        &self.msg
+    }
+}
+
+// ======================================== //
+//                   IMPLS                  //
+// ======================================== //
+
+impl Store for DynamicValue {
+
+    fn get_attribute<'a>(&'a self, k: PropertyAccessor) -> AttributeGetResult<'a> {
+        match *self {
+            DynamicValue::Null => AttributeGetResult::PrimitiveType(StoreValue::Null),
+            DynamicValue::Array(ref a) => a.get_attribute(k),
+            DynamicValue::Map(ref m) => m.get_attribute(k),
+            _ => match k.name() {
+                "" => AttributeGetResult::PrimitiveType(self.as_store_value()),
+                _ => AttributeGetResult::NoSuchProperty,
+            }
+        }
+    }
+
+    fn get_attribute_mut<'a>(&'a mut self, k: PropertyAccessor) -> AttributeMutResult<'a> {
+        match *self {
+            DynamicValue::Array(ref mut a) => a.get_attribute_mut(k),
+            DynamicValue::Map(ref mut m) => m.get_attribute_mut(k),
+            ref mut scalar => match k.name() {
+                "" => AttributeMutResult::PrimitiveType(scalar),
+                _ => AttributeMutResult::NoSuchProperty,
+            }
+        }
+    }
+
+    fn set_attribute<'a>(&mut self, k: PropertyAccessor, value: StoreValue<'a>) -> AttributeSetResult<'a> {
+        match *self {
+            DynamicValue::Array(ref mut a) => a.set_attribute(k, value),
+            DynamicValue::Map(ref mut m) => m.set_attribute(k, value),
+            ref mut scalar => match k.name() {
+                "" => {
+                    *scalar = DynamicValue::from(value);
+                    AttributeSetResult::Stored
+                }
+                _ => AttributeSetResult::NoSuchProperty(value),
+            }
+        }
+    }
+}
+
+impl<'a> From<StoreValue<'a>> for DynamicValue {
+    fn from(value: StoreValue<'a>) -> DynamicValue {
+        match value {
+            StoreValue::String(s) => DynamicValue::String(s.to_string()),
+            StoreValue::Integer(i) => DynamicValue::Integer(i),
+            StoreValue::Float(f) => DynamicValue::Float(f),
+            StoreValue::Boolean(b) => DynamicValue::Boolean(b),
+            StoreValue::Null => DynamicValue::Null,
+        }
+    }
+}
+
+/// Only scalars can be obtained by a cast.
+impl Cast for DynamicValue {
+    fn cast(this: StoreValue) -> Option<Self> {
+        Some(DynamicValue::from(this))
+    }
+}
+
+impl AsStoreValue for DynamicValue {
+    fn as_store_value(&self) -> StoreValue {
+        match *self {
+            DynamicValue::String(ref s) => StoreValue::String(s),
+            DynamicValue::Integer(i) => StoreValue::Integer(i),
+            DynamicValue::Float(f) => StoreValue::Float(f),
+            DynamicValue::Boolean(b) => StoreValue::Boolean(b),
+            _ => StoreValue::Null,
+        }
+    }
+}
+
+// ======================================== //
+//                  PARSER                  //
+// ======================================== //
+
+#[derive(Clone, Copy, PartialEq)]
+enum Syntax {
+    Json,
+    Ron,
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    syntax: Syntax,
+    row: usize,
+    col: usize,
+}
+
+impl<'a> Parser<'a> {
+
+    fn new(text: &'a str, syntax: Syntax) -> Parser<'a> {
+        Parser {
+            chars: text.chars().peekable(),
+            syntax: syntax,
+            row: 0,
+            col: 0,
+        }
+    }
+
+    fn parse_document(&mut self) -> Result<DynamicValue, ParseError> {
+        let value = try!(self.parse_value());
+        try!(self.skip_whitespace());
+        match self.peek() {
+            None => Ok(value),
+            Some(c) => Err(self.error(format!("Unexpected `{}` after the document", c))),
+        }
+    }
+
+    fn error(&self, msg: String) -> ParseError {
+        ParseError {
+            row: self.row,
+            col: self.col,
+            msg: msg,
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().cloned()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        match c {
+            Some('\n') => {
+                self.row += 1;
+                self.col = 0;
+            }
+            Some(_) => self.col += 1,
+            None => (),
+        }
+        c
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        try!(self.skip_whitespace());
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(format!("Expected `{}`, found `{}`", expected, c))),
+            None => Err(self.error(format!("Expected `{}`, found the end of the document", expected))),
+        }
+    }
+
+    /// Skip whitespaces, and comments with the RON syntax.
+    fn skip_whitespace(&mut self) -> Result<(), ParseError> {
+        loop {
+            match self.peek() {
+                Some(' ') | Some('\t') | Some('\n') | Some('\r') => { self.next(); }
+                Some(c) if c.is_whitespace() && self.syntax == Syntax::Ron => { self.next(); }
+                Some('/') if self.syntax == Syntax::Ron => {
+                    self.next();
+                    match self.next() {
+                        Some('/') => while let Some(c) = self.next() {
+                            if c == '\n' { break; }
+                        },
+                        Some('*') => {
+                            let mut star = false;
+                            loop {
+                                match self.next() {
+                                    Some('/') if star => break,
+                                    Some(c) => star = c == '*',
+                                    None => return Err(self.error("Unterminated comment".to_string())),
+                                }
+                            }
+                        }
+                        _ => return Err(self.error("Expected a comment after `/`".to_string())),
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<DynamicValue, ParseError> {
+        try!(self.skip_whitespace());
+        match self.peek() {
+            Some('{') => {
+                self.next();
+                self.parse_map('}')
+            }
+            Some('[') => {
+                self.next();
+                self.parse_array(']')
+            }
+            Some('(') if self.syntax == Syntax::Ron => {
+                self.next();
+                self.parse_parenthesis()
+            }
+            Some('"') => self.parse_string().map(DynamicValue::String),
+            Some(c) if c == '-' || c.is_digit(10) => self.parse_number(),
+            Some(c) if is_ident_char(c) => {
+                let ident = self.parse_ident();
+                self.parse_after_ident(ident)
+            }
+            Some(c) => Err(self.error(format!("Unexpected `{}`", c))),
+            None => Err(self.error("Unexpected end of the document".to_string())),
+        }
+    }
+
+    /// Keywords, `Some(...)`, named structs or tuples and unit variants.
+    fn parse_after_ident(&mut self, ident: String) -> Result<DynamicValue, ParseError> {
+        match &ident[..] {
+            "true" => return Ok(DynamicValue::Boolean(true)),
+            "false" => return Ok(DynamicValue::Boolean(false)),
+            "null" if self.syntax == Syntax::Json => return Ok(DynamicValue::Null),
+            _ if self.syntax == Syntax::Json =>
+                return Err(self.error(format!("Unexpected `{}`", ident))),
+            "None" => return Ok(DynamicValue::Null),
+            _ => (),
+        }
+        try!(self.skip_whitespace());
+        if self.peek() != Some('(') {
+            return Ok(DynamicValue::String(ident));
+        }
+        self.next();
+        if ident == "Some" {
+            let value = try!(self.parse_value());
+            try!(self.expect(')'));
+            Ok(value)
+        } else {
+            self.parse_parenthesis()
+        }
+    }
+
+    /// Parse a RON struct `(a: 1)` as a map or a tuple `(1, 2)` as
+    /// an array. The opening parenthesis has been consumed.
+    fn parse_parenthesis(&mut self) -> Result<DynamicValue, ParseError> {
+        try!(self.skip_whitespace());
+        match self.peek() {
+            Some(')') => {
+                self.next();
+                return Ok(DynamicValue::Null);
+            }
+            Some(c) if is_ident_char(c) && !c.is_digit(10) => (),
+            _ => return self.parse_array(')'),
+        }
+        let ident = self.parse_ident();
+        try!(self.skip_whitespace());
+        if self.peek() == Some(':') {
+            self.next();
+            let first = try!(self.parse_value());
+            let mut map = HashMap::new();
+            map.insert(ident, first);
+            try!(self.parse_entries(')', &mut map));
+            Ok(DynamicValue::Map(map))
+        } else {
+            let first = try!(self.parse_after_ident(ident));
+            let mut array = vec![first];
+            try!(self.parse_elements(')', &mut array));
+            Ok(DynamicValue::Array(array))
+        }
+    }
+
+    /// The opening bracket has been consumed.
+    fn parse_array(&mut self, close: char) -> Result<DynamicValue, ParseError> {
+        let mut array = Vec::new();
+        try!(self.skip_whitespace());
+        if self.peek() == Some(close) {
+            self.next();
+            return Ok(DynamicValue::Array(array));
+        }
+        array.push(try!(self.parse_value()));
+        try!(self.parse_elements(close, &mut array));
+        Ok(DynamicValue::Array(array))
+    }
+
+    /// Parse the elements after the first one.
+    fn parse_elements(&mut self, close: char, array: &mut Vec<DynamicValue>)
+        -> Result<(), ParseError>
+    {
+        while try!(self.separator(close)) {
+            array.push(try!(self.parse_value()));
+        }
+        Ok(())
+    }
+
+    /// The opening brace has been consumed.
+    fn parse_map(&mut self, close: char) -> Result<DynamicValue, ParseError> {
+        let mut map = HashMap::new();
+        try!(self.skip_whitespace());
+        if self.peek() == Some(close) {
+            self.next();
+            return Ok(DynamicValue::Map(map));
+        }
+        try!(self.parse_entry(&mut map));
+        try!(self.parse_entries(close, &mut map));
+        Ok(DynamicValue::Map(map))
+    }
+
+    /// Parse the entries after the first one.
+    fn parse_entries(&mut self, close: char, map: &mut HashMap<String, DynamicValue>)
+        -> Result<(), ParseError>
+    {
+        while try!(self.separator(close)) {
+            try!(self.parse_entry(map));
+        }
+        Ok(())
+    }
+
+    fn parse_entry(&mut self, map: &mut HashMap<String, DynamicValue>)
+        -> Result<(), ParseError>
+    {
+        try!(self.skip_whitespace());
+        let key = match self.peek() {
+            Some('"') => try!(self.parse_string()),
+            Some(c) if self.syntax == Syntax::Ron && is_ident_char(c) => self.parse_ident(),
+            Some(c) => return Err(self.error(format!("Expected a key, found `{}`", c))),
+            None => return Err(self.error("Unexpected end of the document".to_string())),
+        };
+        try!(self.expect(':'));
+        let value = try!(self.parse_value());
+        map.insert(key, value);
+        Ok(())
+    }
+
+    /// Consume either a `,` or the closing character. Returns true
+    /// if another element follows. A trailing comma is allowed with RON.
+    fn separator(&mut self, close: char) -> Result<bool, ParseError> {
+        try!(self.skip_whitespace());
+        match self.next() {
+            Some(',') => {
+                try!(self.skip_whitespace());
+                if self.syntax == Syntax::Ron && self.peek() == Some(close) {
+                    self.next();
+                    Ok(false)
+                } else {
+                    Ok(true)
+                }
+            }
+            Some(c) if c == close => Ok(false),
+            Some(c) => Err(self.error(format!("Expected `,` or `{}`, found `{}`", close, c))),
+            None => Err(self.error(format!("Expected `{}`, found the end of the document", close))),
+        }
+    }
+
+    fn parse_ident(&mut self) -> String {
+        let mut ident = String::new();
+        while let Some(c) = self.peek() {
+            if !is_ident_char(c) {
+                break;
+            }
+            ident.push(c);
+            self.next();
+        }
+        ident
+    }
+
+    fn parse_string(&mut self) -> Result<String, ParseError> {
+        self.next();
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\x08',
+                        Some('f') => '\x0c',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => try!(self.parse_unicode_escape()),
+                        Some(c) => return Err(self.error(format!("Unknown escape `\\{}`", c))),
+                        None => return Err(self.error("Unterminated string".to_string())),
+                    };
+                    s.push(c);
+                }
+                Some(c) if c < '\u{20}' && self.syntax == Syntax::Json =>
+                    return Err(self.error("Unescaped control character in string".to_string())),
+                Some(c) => s.push(c),
+                None => return Err(self.error("Unterminated string".to_string())),
+            }
+        }
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, ParseError> {
+        let mut code = try!(self.parse_hex4());
+        // A high surrogate must be followed by `\uDCxx` to form one char.
+        if code >= 0xD800 && code < 0xDC00 {
+            if self.next() != Some('\\') || self.next() != Some('u') {
+                return Err(self.error("Unpaired surrogate in unicode escape".to_string()));
+            }
+            let low = try!(self.parse_hex4());
+            if low < 0xDC00 || low >= 0xE000 {
+                return Err(self.error("Unpaired surrogate in unicode escape".to_string()));
+            }
+            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+        }
+        match ::std::char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => Err(self.error("Invalid unicode escape".to_string())),
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.next().and_then(|c| c.to_digit(16)) {
+                Some(d) => code = code * 16 + d,
+                None => return Err(self.error("Invalid unicode escape".to_string())),
+            }
+        }
+        Ok(code)
+    }
+
+    /// The grammar of the JSON numbers, for both syntaxes:
+    /// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`.
+    fn parse_number(&mut self) -> Result<DynamicValue, ParseError> {
+        let mut number = String::new();
+        let mut is_float = false;
+        if self.peek() == Some('-') {
+            number.push('-');
+            self.next();
+        }
+        if self.peek() == Some('0') {
+            number.push('0');
+            self.next();
+            if self.peek().map_or(false, |c| c.is_digit(10)) {
+                return Err(self.error(format!("Leading zero in number `{}`", number)));
+            }
+        } else if self.push_digits(&mut number) == 0 {
+            return Err(self.error(format!("Invalid number `{}`", number)));
+        }
+        if self.peek() == Some('.') {
+            is_float = true;
+            number.push('.');
+            self.next();
+            if self.push_digits(&mut number) == 0 {
+                return Err(self.error(format!("Missing digits after `{}`", number)));
+            }
+        }
+        if self.peek() == Some('e') || self.peek() == Some('E') {
+            is_float = true;
+            number.push('e');
+            self.next();
+            match self.peek() {
+                Some(c) if c == '+' || c == '-' => {
+                    number.push(c);
+                    self.next();
+                }
+                _ => (),
+            }
+            if self.push_digits(&mut number) == 0 {
+                return Err(self.error(format!("Missing digits after `{}`", number)));
+            }
+        }
+        if !is_float {
+            if let Ok(i) = number.parse::<i64>() {
+                return Ok(DynamicValue::Integer(i));
+            }
+        }
+        match number.parse::<f64>() {
+            Ok(f) => Ok(DynamicValue::Float(f)),
+            Err(_) => Err(self.error(format!("Invalid number `{}`", number))),
+        }
+    }
+}
+
+impl<'a> Parser<'a> {
+
+    /// Push the digits that follow to `number`, returns how many.
+    fn push_digits(&mut self, number: &mut String) -> usize {
+        let mut count = 0;
+        while let Some(c) = self.peek() {
+            if !c.is_digit(10) {
+                break;
+            }
+            number.push(c);
+            self.next();
+            count += 1;
+        }
+        count
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    match c {
+        'a'...'z' | 'A'...'Z' | '0'...'9' | '_' => true,
+        _ => false,
+    }
+}
+
+// ======================================== //
+//                   TESTS                  //
+// ======================================== //
+
+#[cfg(test)]
+mod test {
+
+    use super::DynamicValue;
+    use store::StoreValueStatic;
+    use {Store, StoreValue, PropertyAccessor};
+
+    fn get(value: &DynamicValue, path: &str) -> StoreValueStatic {
+        value.get_attribute(PropertyAccessor::new(path)).value().unwrap()
+    }
+
+    fn string(s: &str) -> StoreValueStatic {
+        StoreValueStatic::String(s.to_string())
+    }
+
+    #[test]
+    fn json_should_map_onto_paths() {
+        let doc = DynamicValue::from_json(r#"{
+            "party": { "members": [ { "name": "Bob", "hp": 30 }, { "name": "Alé", "hp": 2.5 } ] },
+            "stats": { "str": 12, "a.b": true },
+            "target": null
+        }"#).unwrap();
+        assert_eq!(get(&doc, "party.members[0].name"), string("Bob"));
+        assert_eq!(get(&doc, "party.members.1.name"), string("Alé"));
+        assert_eq!(get(&doc, "party.members[1].hp"), StoreValueStatic::Float(2.5));
+        assert_eq!(get(&doc, "party.members.count"), StoreValueStatic::Integer(2));
+        assert_eq!(get(&doc, "stats[\"a.b\"]"), StoreValueStatic::Boolean(true));
+        assert_eq!(get(&doc, "target.name"), StoreValueStatic::Null);
+        assert_eq!(doc.get_attribute(PropertyAccessor::new("party.members")).unwrap_iter().count(), 2);
+    }
+
+    #[test]
+    fn ron_should_map_structs_and_tuples() {
+        let doc = DynamicValue::from_ron(r#"
+            // The player
+            Player(
+                name: "Bob",
+                class: Warrior,
+                pet: None,
+                weapon: Some(Weapon(damage: 12)),
+                position: (3, -4),
+            )"#).unwrap();
+        assert_eq!(get(&doc, "name"), string("Bob"));
+        assert_eq!(get(&doc, "class"), string("Warrior"));
+        assert_eq!(get(&doc, "pet"), StoreValueStatic::Null);
+        assert_eq!(get(&doc, "weapon.damage"), StoreValueStatic::Integer(12));
+        assert_eq!(get(&doc, "position[1]"), StoreValueStatic::Integer(-4));
+    }
+
+    #[test]
+    fn set_attribute_should_replace_scalars() {
+        let mut doc = DynamicValue::from_json(r#"{ "hp": 30, "items": [1, 2] }"#).unwrap();
+        assert!(doc.set_attribute(PropertyAccessor::new("hp"), StoreValue::String("dead")).is_stored());
+        assert!(doc.set_attribute(PropertyAccessor::new("items[1]"), StoreValue::Integer(7)).is_stored());
+        assert!(!doc.set_attribute(PropertyAccessor::new("items"), StoreValue::Integer(7)).is_stored());
+        assert_eq!(get(&doc, "hp"), string("dead"));
+        assert_eq!(get(&doc, "items.1"), StoreValueStatic::Integer(7));
+    }
+
+    #[test]
+    fn surrogate_pairs_should_decode_to_one_char() {
+        let doc = DynamicValue::from_json(r#"{ "mood": "\uD83D\uDE00!", "e": "\u00e9" }"#).unwrap();
+        assert_eq!(get(&doc, "mood"), string("\u{1F600}!"));
+        assert_eq!(get(&doc, "e"), string("é"));
+        assert!(DynamicValue::from_json(r#""\uD83D""#).is_err());
+        assert!(DynamicValue::from_json(r#""\uD83Dx""#).is_err());
+        assert!(DynamicValue::from_json(r#""\uD83D\u0041""#).is_err());
+        assert!(DynamicValue::from_json(r#""\uDE00""#).is_err());
+    }
+
+    #[test]
+    fn invalid_documents_should_be_rejected() {
+        assert!(DynamicValue::from_json("[1, 2,]").is_err());
+        assert!(DynamicValue::from_json("{ hp: 1 }").is_err());
+        assert!(DynamicValue::from_json("(1, 2)").is_err());
+        assert!(DynamicValue::from_json("\"abc").is_err());
+        assert!(DynamicValue::from_json("1 2").is_err());
+        assert!(DynamicValue::from_json("\"a\tb\"").is_err());
+        assert!(DynamicValue::from_json("\u{a0}1").is_err());
+        assert!(DynamicValue::from_ron("[1, 2,]").is_ok());
+        let err = DynamicValue::from_json("{\n  \"a\": ?\n}").unwrap_err();
+        assert_eq!(err.to_string(), "2:8: Unexpected `?`");
+    }
+
+    #[test]
+    fn numbers_should_follow_the_json_grammar() {
+        let doc = DynamicValue::from_json("[0, -0.5, 10, 1e-3, 2E+2, -7]").unwrap();
+        assert_eq!(get(&doc, "[0]"), StoreValueStatic::Integer(0));
+        assert_eq!(get(&doc, "[1]"), StoreValueStatic::Float(-0.5));
+        assert_eq!(get(&doc, "[2]"), StoreValueStatic::Integer(10));
+        assert_eq!(get(&doc, "[3]"), StoreValueStatic::Float(0.001));
+        assert_eq!(get(&doc, "[4]"), StoreValueStatic::Float(200.0));
+        assert_eq!(get(&doc, "[5]"), StoreValueStatic::Integer(-7));
+        for invalid in &["012", "-012", "00", "-", "--1", "1.", "1.e3", ".5", "1e", "1e+", "+1", "1-2", "0x10"] {
+            assert!(DynamicValue::from_json(invalid).is_err(), "`{}` was accepted", invalid);
+            assert!(DynamicValue::from_ron(invalid).is_err(), "`{}` was accepted", invalid);
+        }
+        let err = DynamicValue::from_json("[012]").unwrap_err();
+        assert_eq!(err.to_string(), "1:3: Leading zero in number `0`");
+    }
+}
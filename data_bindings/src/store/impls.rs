@@ -3,6 +3,9 @@ use std::collections::{
 };
 use std::slice;
 use std::any::Any;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::cell::{RefCell, BorrowState};
 use std::collections::hash_state::HashState;
 
 use lookup::{
@@ -30,27 +33,149 @@ impl<T: ?Sized> Store for Box<T> where T: Store {
     fn set_attribute<'b>(&mut self, k: PropertyAccessor, value: StoreValue<'b>) -> AttributeSetResult<'b> {
         (**self).set_attribute(k, value)
     }
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        (**self).with_attribute(k, f)
+    }
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        (**self).with_attribute_mut(k, f)
+    }
 }
-/*
-impl<T> Store for Rc<RefCell<T>> where T: Store {
-    fn get_attribute<'b>(&'b self, k: PropertyAccessor) -> AttributeGetResult<'b> {
-        let b = self.borrow();
-        let a = b.get_attribute(k);
-        match
+
+/// Shared ownership without interior mutability: reads go through,
+/// but mutations are only possible while the `Rc` is not shared.
+impl<T> Store for Rc<T> where T: Store {
+    fn get_attribute(&self, k: PropertyAccessor) -> AttributeGetResult {
+        (**self).get_attribute(k)
+    }
+    fn get_attribute_mut(&mut self, k: PropertyAccessor) -> AttributeMutResult {
+        match Rc::get_mut(self) {
+            Some(store) => store.get_attribute_mut(k),
+            None => AttributeMutResult::Shared,
+        }
     }
     fn set_attribute<'b>(&mut self, k: PropertyAccessor, value: StoreValue<'b>) -> AttributeSetResult<'b> {
-        self.borrow_mut().set_attribute(k, value)
+        match Rc::get_mut(self) {
+            Some(store) => store.set_attribute(k, value),
+            None => AttributeSetResult::Shared,
+        }
+    }
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        (**self).with_attribute(k, f)
+    }
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        match Rc::get_mut(self) {
+            Some(store) => store.with_attribute_mut(k, f),
+            None => f(AttributeMutResult::Shared),
+        }
     }
 }
-impl<T> Store for Rc<T> where T: Store {
-    fn get_attribute<'b>(&'b self, k: PropertyAccessor) -> AttributeGetResult<'b> {
+
+/// Same as `Rc<T>`.
+impl<T> Store for Arc<T> where T: Store + Send + Sync {
+    fn get_attribute(&self, k: PropertyAccessor) -> AttributeGetResult {
         (**self).get_attribute(k)
     }
+    fn get_attribute_mut(&mut self, k: PropertyAccessor) -> AttributeMutResult {
+        match Arc::get_mut(self) {
+            Some(store) => store.get_attribute_mut(k),
+            None => AttributeMutResult::Shared,
+        }
+    }
     fn set_attribute<'b>(&mut self, k: PropertyAccessor, value: StoreValue<'b>) -> AttributeSetResult<'b> {
-        self.set_attribute(k, value)
+        match Arc::get_mut(self) {
+            Some(store) => store.set_attribute(k, value),
+            None => AttributeSetResult::Shared,
+        }
+    }
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        (**self).with_attribute(k, f)
+    }
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        match Arc::get_mut(self) {
+            Some(store) => store.with_attribute_mut(k, f),
+            None => f(AttributeMutResult::Shared),
+        }
+    }
+}
+
+/// `with_attribute` and `with_attribute_mut` borrow the value for the
+/// duration of the call, the data bindings go through them.
+///
+/// The borrow guard can't outlive `get_attribute`, so primitive
+/// values are copied (see `AttributeGetResult::into_owned`) and lists
+/// can't be iterated: `SharedIterableType` is returned instead.
+/// For the same reason, `get_attribute_mut` only succeeds while
+/// the `Rc` is not shared.
+///
+/// While the value is mutably borrowed elsewhere, its properties
+/// are not found and can't be set.
+impl<T> Store for Rc<RefCell<T>> where T: Store {
+    fn get_attribute(&self, k: PropertyAccessor) -> AttributeGetResult {
+        match self.borrow_state() {
+            BorrowState::Writing => AttributeGetResult::NoSuchProperty,
+            _ => {
+                let result = self.borrow().get_attribute(k).into_owned();
+                result
+            }
+        }
+    }
+    fn get_attribute_mut(&mut self, k: PropertyAccessor) -> AttributeMutResult {
+        match Rc::get_mut(self) {
+            Some(cell) => cell.get_mut().get_attribute_mut(k),
+            None => AttributeMutResult::Shared,
+        }
+    }
+    fn set_attribute<'b>(&mut self, k: PropertyAccessor, value: StoreValue<'b>) -> AttributeSetResult<'b> {
+        match self.borrow_state() {
+            BorrowState::Unused => self.borrow_mut().set_attribute(k, value),
+            _ => AttributeSetResult::Shared,
+        }
+    }
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        match self.borrow_state() {
+            BorrowState::Writing => f(AttributeGetResult::NoSuchProperty),
+            _ => self.borrow().with_attribute(k, f),
+        }
+    }
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        match self.borrow_state() {
+            BorrowState::Unused => self.borrow_mut().with_attribute_mut(k, f),
+            _ => f(AttributeMutResult::Shared),
+        }
+    }
+}
+
+/// Same as `Rc<RefCell<T>>`. A poisoned mutex is still used, as
+/// the data bindings never leave a store in an invalid state.
+impl<T> Store for Arc<Mutex<T>> where T: Store + Send {
+    fn get_attribute(&self, k: PropertyAccessor) -> AttributeGetResult {
+        let store = self.lock().unwrap_or_else(|e| e.into_inner());
+        let result = store.get_attribute(k).into_owned();
+        result
+    }
+    fn get_attribute_mut(&mut self, k: PropertyAccessor) -> AttributeMutResult {
+        match Arc::get_mut(self) {
+            Some(mutex) => match mutex.get_mut() {
+                Ok(store) => store.get_attribute_mut(k),
+                Err(e) => e.into_inner().get_attribute_mut(k),
+            },
+            None => AttributeMutResult::Shared,
+        }
+    }
+    fn set_attribute<'b>(&mut self, k: PropertyAccessor, value: StoreValue<'b>) -> AttributeSetResult<'b> {
+        let mut store = self.lock().unwrap_or_else(|e| e.into_inner());
+        let result = store.set_attribute(k, value);
+        result
+    }
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        let store = self.lock().unwrap_or_else(|e| e.into_inner());
+        store.with_attribute(k, f)
+    }
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        let mut store = self.lock().unwrap_or_else(|e| e.into_inner());
+        store.with_attribute_mut(k, f)
     }
 }
-*/
 
 // Implementation for i64, String and others.
 impl_store_for_value_type_like!(i64);
@@ -60,10 +185,73 @@ impl_store_for_value_type_like!(i8);
 impl_store_for_value_type_like!(u32);
 impl_store_for_value_type_like!(u16);
 impl_store_for_value_type_like!(u8);
+impl_store_for_value_type_like!(f64);
+impl_store_for_value_type_like!(f32);
 impl_store_for_value_type_like!(bool);
 impl_store_for_value_type_like!(String);
 impl_store_for_value_type_like!(StoreValueStatic);
 
+/// An absent value resolves to `StoreValue::Null` whatever the
+/// remaining path is, as we can't know the shape of what is missing.
+/// A present value is transparent.
+///
+/// An absent value can be set if `T` can be built from the value,
+/// see `Store::from_store_value`: this is the case of value types
+/// such as `f64` or `String`, but not of structs.
+impl<T> Store for Option<T>
+    where T: Store
+{
+    fn get_attribute<'a>(&'a self, k: PropertyAccessor) -> AttributeGetResult<'a> {
+        match *self {
+            Some(ref v) => v.get_attribute(k),
+            None => AttributeGetResult::PrimitiveType(StoreValue::Null),
+        }
+    }
+
+    fn get_attribute_mut<'a>(&'a mut self, k: PropertyAccessor) -> AttributeMutResult<'a> {
+        match *self {
+            Some(ref mut v) => v.get_attribute_mut(k),
+            None => AttributeMutResult::NoSuchProperty,
+        }
+    }
+
+    fn set_attribute<'a>(&mut self, k: PropertyAccessor, value: StoreValue<'a>) -> AttributeSetResult<'a> {
+        match (k.name(), value) {
+            ("", StoreValue::Null) => {
+                *self = None;
+                AttributeSetResult::Stored
+            }
+            (_, value) => match *self {
+                Some(ref mut v) => v.set_attribute(k, value),
+                None => match k.name() {
+                    "" => match T::from_store_value(value) {
+                        Some(v) => {
+                            *self = Some(v);
+                            AttributeSetResult::Stored
+                        }
+                        None => AttributeSetResult::WrongType,
+                    },
+                    _ => AttributeSetResult::NoSuchProperty(value),
+                }
+            }
+        }
+    }
+
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        match *self {
+            Some(ref v) => v.with_attribute(k, f),
+            None => f(AttributeGetResult::PrimitiveType(StoreValue::Null)),
+        }
+    }
+
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        match *self {
+            Some(ref mut v) => v.with_attribute_mut(k, f),
+            None => f(AttributeMutResult::NoSuchProperty),
+        }
+    }
+}
+
 struct WrapperIter<'a, T: 'a> {
     it: slice::Iter<'a, T>,
 }
@@ -104,46 +292,125 @@ impl<'a, T> Iterator for WrapperIterMut<'a, T>
 
 
 /// This implementation is used by the `repeat`
-/// tag. It doesn't allow for set_attribute to do any change
-/// to the array. (No tag actually allow that)
+/// tag. It doesn't allow for set_attribute to change the
+/// array itself, only its elements. (No tag actually allow that)
 /// It works similarly to the impl above for `T` when `T: Into<StoreValue> + Cast`.
 /// The get_attribute transforms `&'a [T]` into a `StoreValue::List`
+///
+/// Elements are reached with an index, `inventory.3.count` or
+/// `inventory[3].count`, and `inventory.count` is the length.
 impl<T> Store for Vec<T>
     where T: Store + Any
 {
     fn get_attribute<'a>(&'a self, k: PropertyAccessor) -> AttributeGetResult<'a> {
-        match k.name() {
-            "" => AttributeGetResult::IterableType(
-                Box::new(WrapperIter { it: self.iter() }) as Box<Iterator<Item=&'a Store> + 'a>
-            ),
-            _ => AttributeGetResult::NoSuchProperty
-        }
+        get_attribute_slice(self, k)
     }
 
     fn get_attribute_mut<'a>(&'a mut self, k: PropertyAccessor) -> AttributeMutResult<'a> {
-        match k.name() {
-            "" => AttributeMutResult::IterableType(
-                Box::new(WrapperIterMut { it: self.iter_mut() }) as Box<Iterator<Item=&'a mut Store> + 'a>
-            ),
-            _ => AttributeMutResult::NoSuchProperty
-        }
+        get_attribute_mut_slice(self, k)
+    }
+
+    fn set_attribute<'a>(&mut self, k: PropertyAccessor, value: StoreValue<'a>) -> AttributeSetResult<'a> {
+        set_attribute_slice(self, k, value)
+    }
+
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        with_attribute_slice(self, k, f)
+    }
+
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        with_attribute_mut_slice(self, k, f)
+    }
+}
+
+/// Same as `Vec<T>` for a boxed slice.
+impl<T> Store for Box<[T]>
+    where T: Store + Any
+{
+    fn get_attribute<'a>(&'a self, k: PropertyAccessor) -> AttributeGetResult<'a> {
+        get_attribute_slice(self, k)
+    }
+
+    fn get_attribute_mut<'a>(&'a mut self, k: PropertyAccessor) -> AttributeMutResult<'a> {
+        get_attribute_mut_slice(self, k)
     }
 
-    // TODO(Nemikolh): We should be able to modify the element of the array.
-    // But in order to have a nicer interface it would be better to have something like:
-    //
-    //      get_attribute_mut() -> AttributeMutResult
-    //
-    // where:
-    //
-    //      AttributeMutResult<'a> {
-    //          PrimitiveType(&'a mut Cast),
-    //          IterableType()
-    //      }
     fn set_attribute<'a>(&mut self, k: PropertyAccessor, value: StoreValue<'a>) -> AttributeSetResult<'a> {
-        match k.name() {
-            "" => AttributeSetResult::WrongType,
-            _ => AttributeSetResult::NoSuchProperty(value)
+        set_attribute_slice(self, k, value)
+    }
+
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        with_attribute_slice(self, k, f)
+    }
+
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        with_attribute_mut_slice(self, k, f)
+    }
+}
+
+fn get_attribute_slice<'a, T>(slice: &'a [T], k: PropertyAccessor) -> AttributeGetResult<'a>
+    where T: Store
+{
+    match k.name() {
+        "" => AttributeGetResult::IterableType(
+            Box::new(WrapperIter { it: slice.iter() }) as Box<Iterator<Item=&'a Store> + 'a>
+        ),
+        "count" if !k.is_bracketed() => match k.next().name() {
+            "" => AttributeGetResult::PrimitiveType(StoreValue::Integer(slice.len() as i64)),
+            _ => AttributeGetResult::NoSuchProperty,
+        },
+        name => match name.parse::<usize>().ok().and_then(|i| slice.get(i)) {
+            Some(store) => store.get_attribute(k.next()),
+            None => AttributeGetResult::NoSuchProperty,
+        }
+    }
+}
+
+fn get_attribute_mut_slice<'a, T>(slice: &'a mut [T], k: PropertyAccessor) -> AttributeMutResult<'a>
+    where T: Store
+{
+    match k.name() {
+        "" => AttributeMutResult::IterableType(
+            Box::new(WrapperIterMut { it: slice.iter_mut() }) as Box<Iterator<Item=&'a mut Store> + 'a>
+        ),
+        "count" if !k.is_bracketed() => AttributeMutResult::NoSuchProperty,
+        name => match name.parse::<usize>().ok().and_then(move |i| slice.get_mut(i)) {
+            Some(store) => store.get_attribute_mut(k.next()),
+            None => AttributeMutResult::NoSuchProperty,
+        }
+    }
+}
+
+/// Forward the call to the element, if any.
+fn with_attribute_slice<T>(slice: &[T], k: PropertyAccessor, f: &mut FnMut(AttributeGetResult))
+    where T: Store
+{
+    match k.name().parse::<usize>().ok().and_then(|i| slice.get(i)) {
+        Some(store) => store.with_attribute(k.next(), f),
+        None => f(get_attribute_slice(slice, k)),
+    }
+}
+
+fn with_attribute_mut_slice<T>(slice: &mut [T], k: PropertyAccessor, f: &mut FnMut(AttributeMutResult))
+    where T: Store
+{
+    let index = k.name().parse::<usize>().ok().and_then(|i| if i < slice.len() { Some(i) } else { None });
+    match index {
+        Some(i) => slice[i].with_attribute_mut(k.next(), f),
+        None => f(get_attribute_mut_slice(slice, k)),
+    }
+}
+
+fn set_attribute_slice<'a, T>(slice: &mut [T], k: PropertyAccessor, value: StoreValue<'a>)
+    -> AttributeSetResult<'a>
+    where T: Store
+{
+    match k.name() {
+        "" => AttributeSetResult::WrongType,
+        "count" if !k.is_bracketed() => AttributeSetResult::WrongType,
+        name => match name.parse::<usize>().ok().and_then(move |i| slice.get_mut(i)) {
+            Some(store) => store.set_attribute(k.next(), value),
+            None => AttributeSetResult::NoSuchProperty(value),
         }
     }
 }
@@ -168,37 +435,19 @@ impl<S, T> Store for HashMap<String, T, S>
     }
 
     fn get_attribute_mut<'a>(&'a mut self, k: PropertyAccessor) -> AttributeMutResult<'a> {
-        // TODO(Nemikolh): Strangely this code doesn't compile.
-        // for (prefix, key) in PrefixKeyIter::new(k) {
-        //     if let Some(store) = self.get_mut(prefix) {
-        //         match store.get_attribute_mut(key) {
-        //             AttributeMutResult::NoSuchProperty => continue,
-        //             default_case => return default_case,
-        //         }
-        //     }
-        // }
-        //
-        // TODO(Nemikolh): Slow hack to get the job done.
-        let mut kfound = None;
-        let mut pfound = None;
+        // The borrow checker considers that a result returned from within
+        // the loop keeps `self` borrowed for the following iterations.
+        // It doesn't: a borrow is only returned when the loop stops.
+        let this: *mut HashMap<String, T, S> = self;
         for (prefix, key) in PrefixKeyIter::new(k) {
-            kfound = Some(key.clone());
-            if let Some(store) = self.get(prefix) {
-                match store.get_attribute(key) {
-                    AttributeGetResult::NoSuchProperty => (),
-                    _ => {
-                        pfound = Some(prefix);
-                        break;
-                    }
+            if let Some(store) = unsafe { (*this).get_mut(prefix) } {
+                match store.get_attribute_mut(key) {
+                    AttributeMutResult::NoSuchProperty => (),
+                    default_case => return default_case,
                 }
             }
         }
-        if pfound.is_some() && kfound.is_some() {
-            let store = self.get_mut(pfound.unwrap()).unwrap();
-            store.get_attribute_mut(kfound.unwrap())
-        } else {
-            AttributeMutResult::NoSuchProperty
-        }
+        AttributeMutResult::NoSuchProperty
     }
 
     fn set_attribute<'a>(&mut self, k: PropertyAccessor, mut value: StoreValue<'a>) -> AttributeSetResult<'a> {
@@ -209,11 +458,51 @@ impl<S, T> Store for HashMap<String, T, S>
                     AttributeSetResult::NoSuchProperty(v) => value = v,
                     AttributeSetResult::WrongType => return AttributeSetResult::WrongType,
                     AttributeSetResult::Stored => return AttributeSetResult::Stored,
+                    AttributeSetResult::DanglingReference => return AttributeSetResult::DanglingReference,
+                    AttributeSetResult::Shared => return AttributeSetResult::Shared,
                 }
             }
         }
         AttributeSetResult::NoSuchProperty(value)
     }
+
+    fn with_attribute(&self, k: PropertyAccessor, f: &mut FnMut(AttributeGetResult)) {
+        for (prefix, key) in PrefixKeyIter::new(k) {
+            if let Some(store) = self.get(prefix) {
+                let mut found = false;
+                store.with_attribute(key, &mut |result| match result {
+                    AttributeGetResult::NoSuchProperty => (),
+                    default_case => {
+                        found = true;
+                        f(default_case)
+                    }
+                });
+                if found {
+                    return;
+                }
+            }
+        }
+        f(AttributeGetResult::NoSuchProperty)
+    }
+
+    fn with_attribute_mut(&mut self, k: PropertyAccessor, f: &mut FnMut(AttributeMutResult)) {
+        for (prefix, key) in PrefixKeyIter::new(k) {
+            if let Some(store) = self.get_mut(prefix) {
+                let mut found = false;
+                store.with_attribute_mut(key, &mut |result| match result {
+                    AttributeMutResult::NoSuchProperty => (),
+                    default_case => {
+                        found = true;
+                        f(default_case)
+                    }
+                });
+                if found {
+                    return;
+                }
+            }
+        }
+        f(AttributeMutResult::NoSuchProperty)
+    }
 }
 
 // ======================================== //
@@ -223,12 +512,16 @@ impl<S, T> Store for HashMap<String, T, S>
 #[cfg(test)]
 mod test {
 
-    use store::StoreValue;
+    use store::{StoreValue, StoreValueStatic};
     use Store;
     use PropertyAccessor;
+    use AttributeSetResult;
     use bench::Bencher;
     use num::traits::ToPrimitive;
     use std::collections::HashMap;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use std::sync::{Arc, Mutex};
 
     #[derive(Clone)]
     struct A {
@@ -267,6 +560,64 @@ mod test {
         }
     }
 
+    struct D {
+        ratio: f32,
+        target: Option<B>,
+        timer: Option<f64>,
+    }
+
+    declare_data_binding! {
+        D {
+            ratio,
+            target,
+            timer
+        }
+    }
+
+    #[test]
+    fn float_access_and_integer_to_float_conversion() {
+        let mut d = D { ratio: 0.5, target: None, timer: None };
+        assert_eq!(d.get_attribute(PropertyAccessor::new("ratio")).unwrap(), StoreValue::Float(0.5));
+        assert!(d.set_attribute(PropertyAccessor::new("ratio"), StoreValue::Integer(2)).is_stored());
+        assert_eq!(d.ratio, 2.0);
+    }
+
+    #[test]
+    fn f32_should_be_rendered_with_its_shortest_representation() {
+        let d = D { ratio: 0.1, target: None, timer: None };
+        assert_eq!(d.get_attribute(PropertyAccessor::new("ratio")).unwrap().to_string(), "0.1");
+        assert_eq!(StoreValueStatic::from(0.1f32).to_string(), "0.1");
+    }
+
+    #[test]
+    fn float_to_integer_conversion_should_reject_fractional_part() {
+        let mut b = B { b: 0, c: C { a: Vec::new(), b: 1 }};
+        assert!(b.set_attribute(PropertyAccessor::new("b"), StoreValue::Float(3.0)).is_stored());
+        assert_eq!(b.b, 3);
+        assert!(!b.set_attribute(PropertyAccessor::new("b"), StoreValue::Float(3.5)).is_stored());
+        assert_eq!(b.b, 3);
+    }
+
+    #[test]
+    fn option_none_should_resolve_to_null() {
+        let mut d = D { ratio: 0.5, target: None, timer: None };
+        assert_eq!(d.get_attribute(PropertyAccessor::new("timer")).unwrap(), StoreValue::Null);
+        assert_eq!(d.get_attribute(PropertyAccessor::new("target.b")).unwrap(), StoreValue::Null);
+        d.target = Some(B { b: 4, c: C { a: Vec::new(), b: 1 }});
+        assert_eq!(d.get_attribute(PropertyAccessor::new("target.b")).unwrap(), StoreValue::Integer(4));
+        assert!(d.set_attribute(PropertyAccessor::new("target"), StoreValue::Null).is_stored());
+        assert!(d.target.is_none());
+    }
+
+    #[test]
+    fn option_none_should_be_assigned_from_a_value() {
+        let mut d = D { ratio: 0.5, target: None, timer: None };
+        assert!(d.set_attribute(PropertyAccessor::new("timer"), StoreValue::Integer(3)).is_stored());
+        assert_eq!(d.timer, Some(3.0));
+        assert!(!d.set_attribute(PropertyAccessor::new("target"), StoreValue::Integer(3)).is_stored());
+        assert!(d.target.is_none());
+    }
+
     #[test]
     fn vec_access_should_return_box_of_elements() {
         let v = A {
@@ -280,6 +631,113 @@ mod test {
         assert_eq!(iter.next().unwrap().get_attribute(PropertyAccessor::new("b")).unwrap(), StoreValue::Integer(1));
     }
 
+    #[test]
+    fn vec_access_by_index_and_count() {
+        let mut v = A {
+            a: vec![B { b: 0, c: C { a: Vec::new(), b: 1 }},
+                    B { b: 1, c: C { a: Vec::new(), b: 2 }}
+            ]
+        };
+
+        assert_eq!(v.get_attribute(PropertyAccessor::new("a.count")).unwrap(), StoreValue::Integer(2));
+        assert_eq!(v.get_attribute(PropertyAccessor::new("a[1].c.b")).unwrap(), StoreValue::Integer(2));
+        assert_eq!(v.get_attribute(PropertyAccessor::new("a.0.b")).unwrap(), StoreValue::Integer(0));
+        assert!(!v.get_attribute(PropertyAccessor::new("a[2].b")).is_found());
+        assert!(v.set_attribute(PropertyAccessor::new("a[1].b"), StoreValue::Integer(7)).is_stored());
+        assert_eq!(v.a[1].b, 7);
+        assert!(!v.set_attribute(PropertyAccessor::new("a.count"), StoreValue::Integer(7)).is_stored());
+    }
+
+    #[test]
+    fn vec_mutable_access_to_elements() {
+        let mut v = A {
+            a: vec![B { b: 0, c: C { a: Vec::new(), b: 1 }},
+                    B { b: 1, c: C { a: Vec::new(), b: 2 }}
+            ]
+        };
+
+        assert!(v.get_attribute_mut(PropertyAccessor::new("a.1.c.b")).is_found());
+        assert!(!v.get_attribute_mut(PropertyAccessor::new("a.2.c.b")).is_found());
+        assert!(!v.get_attribute_mut(PropertyAccessor::new("a.count")).is_found());
+        assert!(v.set_attribute(PropertyAccessor::new("a.0.c.b"), StoreValue::Integer(5)).is_stored());
+        assert_eq!(v.a[0].c.b, 5);
+    }
+
+    #[test]
+    fn hashmap_mutable_access_should_try_each_prefix() {
+        let mut h = HashMap::new();
+        h.insert("foo".to_string(), vec![1, 2]);
+        h.insert("foo.bar".to_string(), vec![3]);
+
+        assert!(h.get_attribute_mut(PropertyAccessor::new("foo.bar.0")).is_found());
+        assert!(h.get_attribute_mut(PropertyAccessor::new("foo.1")).is_found());
+        assert!(!h.get_attribute_mut(PropertyAccessor::new("foo.bar.1")).is_found());
+        assert!(!h.get_attribute_mut(PropertyAccessor::new("bar")).is_found());
+    }
+
+    #[test]
+    fn shared_stores_should_be_readable_and_writable() {
+        let shared = Rc::new(RefCell::new(B { b: 3, c: C { a: Vec::new(), b: 1 }}));
+        let mut store = shared.clone();
+        assert_eq!(store.get_attribute(PropertyAccessor::new("c.b")).value(), Some(1.into()));
+        assert!(store.set_attribute(PropertyAccessor::new("b"), StoreValue::Integer(4)).is_stored());
+        assert_eq!(shared.borrow().b, 4);
+        // Shared, no mutable access.
+        assert!(!store.get_attribute_mut(PropertyAccessor::new("b")).is_found());
+
+        let shared = Arc::new(Mutex::new(vec![1, 2, 3]));
+        let mut store = shared.clone();
+        assert_eq!(store.get_attribute(PropertyAccessor::new("count")).value(), Some(3.into()));
+        assert!(store.set_attribute(PropertyAccessor::new("[2]"), StoreValue::Integer(7)).is_stored());
+        assert_eq!(shared.lock().unwrap()[2], 7);
+
+        let store = Rc::new(String::from("Bob"));
+        assert_eq!(store.get_attribute(PropertyAccessor::new("")).unwrap(), StoreValue::String("Bob"));
+    }
+
+    #[test]
+    fn shared_stores_should_not_panic_when_borrowed() {
+        let shared = Rc::new(RefCell::new(vec![1, 2]));
+        let mut store = shared.clone();
+        {
+            let _borrow = shared.borrow_mut();
+            assert!(!store.get_attribute(PropertyAccessor::new("0")).is_found());
+            match store.set_attribute(PropertyAccessor::new("0"), StoreValue::Integer(5)) {
+                AttributeSetResult::Shared => (),
+                _ => panic!(),
+            }
+        }
+        assert!(store.get_attribute(PropertyAccessor::new("0")).is_found());
+
+        let mut store = Rc::new(vec![1, 2]);
+        let _other = store.clone();
+        match store.set_attribute(PropertyAccessor::new("0"), StoreValue::Integer(5)) {
+            AttributeSetResult::Shared => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn hashmap_access_with_quoted_keys() {
+        let mut stats = HashMap::new();
+        stats.insert("str".to_string(), 12);
+        stats.insert("a.b".to_string(), 3);
+        let mut h = HashMap::new();
+        h.insert("stats".to_string(), stats);
+
+        assert_eq!(h.get_attribute(PropertyAccessor::new("stats[\"str\"]")).unwrap(), StoreValue::Integer(12));
+        assert_eq!(h.get_attribute(PropertyAccessor::new("stats['a.b']")).unwrap(), StoreValue::Integer(3));
+        assert!(h.set_attribute(PropertyAccessor::new("stats[\"str\"]"), StoreValue::Integer(14)).is_stored());
+        assert_eq!(h["stats"]["str"], 14);
+    }
+
+    #[test]
+    fn boxed_slice_access_by_index() {
+        let s: Box<[i32]> = vec![4, 5, 6].into_boxed_slice();
+        assert_eq!(s.get_attribute(PropertyAccessor::new("[2]")).unwrap(), StoreValue::Integer(6));
+        assert_eq!(s.get_attribute(PropertyAccessor::new("count")).unwrap(), StoreValue::Integer(3));
+    }
+
     #[bench]
     fn vec_direct_access(b: &mut Bencher) {
         let total = 1000;
@@ -1,11 +1,15 @@
 
 pub use self::cast::Cast;
 pub use self::cast::AssignFromCast;
+pub use self::cast::AsStoreValue;
 pub use self::value::StoreValue;
 pub use self::value::StoreValueStatic;
+pub use self::dynamic::{DynamicValue, ParseError};
 
 // This is where Store default implementation lives
 mod impls;
+// Store over a JSON / RON document
+mod dynamic;
 
 // Those two modules must be implemented
 // in sync.
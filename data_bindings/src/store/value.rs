@@ -1,10 +1,16 @@
+use std::fmt;
+
 /// `StoreValue` is the type that encapsulate
 /// a value extracted from a Store
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum StoreValue<'a> {
     String(&'a str),
     Integer(i64),
+    Float(f64),
     Boolean(bool),
+    /// Value of an absent `Option`. It is rendered as an
+    /// empty string and is considered false by conditionals.
+    Null,
 }
 
 /// Equivalent to of `StoreValue<'static>` to allow the implementation
@@ -14,11 +20,75 @@ pub enum StoreValue<'a> {
 ///     ...
 /// }
 /// ```
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum StoreValueStatic {
     String(String),
     Integer(i64),
+    Float(f64),
     Boolean(bool),
+    Null,
+}
+
+impl<'a> StoreValue<'a> {
+
+    /// Returns `true` if the value is `StoreValue::Null`.
+    pub fn is_null(&self) -> bool {
+        match *self {
+            StoreValue::Null => true,
+            _ => false,
+        }
+    }
+
+    /// Truth value used by conditionals.
+    /// `Null`, `false`, `0`, `0.0` and the empty string are false.
+    pub fn is_truthy(&self) -> bool {
+        match *self {
+            StoreValue::String(s) => !s.is_empty(),
+            StoreValue::Integer(i) => i != 0,
+            StoreValue::Float(f) => f != 0f64,
+            StoreValue::Boolean(b) => b,
+            StoreValue::Null => false,
+        }
+    }
+}
+
+impl StoreValueStatic {
+
+    /// See `StoreValue::is_truthy`.
+    pub fn is_truthy(&self) -> bool {
+        match *self {
+            StoreValueStatic::String(ref s) => !s.is_empty(),
+            StoreValueStatic::Integer(i) => i != 0,
+            StoreValueStatic::Float(f) => f != 0f64,
+            StoreValueStatic::Boolean(b) => b,
+            StoreValueStatic::Null => false,
+        }
+    }
+}
+
+/// This is how a value is rendered by a binding.
+impl<'a> fmt::Display for StoreValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StoreValue::String(s) => write!(f, "{}", s),
+            StoreValue::Integer(i) => write!(f, "{}", i),
+            StoreValue::Float(v) => write!(f, "{}", v),
+            StoreValue::Boolean(b) => write!(f, "{}", b),
+            StoreValue::Null => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for StoreValueStatic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StoreValueStatic::String(ref s) => write!(f, "{}", s),
+            StoreValueStatic::Integer(i) => write!(f, "{}", i),
+            StoreValueStatic::Float(v) => write!(f, "{}", v),
+            StoreValueStatic::Boolean(b) => write!(f, "{}", b),
+            StoreValueStatic::Null => Ok(()),
+        }
+    }
 }
 
 macro_rules! impl_for_integer {
@@ -40,6 +110,38 @@ impl_for_integer!(u32);
 impl_for_integer!(u16);
 impl_for_integer!(u8);
 
+/// Conversion of a float to the `f64` of a store value.
+pub trait ToStoreFloat {
+    fn to_store_float(self) -> f64;
+}
+
+impl ToStoreFloat for f64 {
+    fn to_store_float(self) -> f64 {
+        self
+    }
+}
+
+/// `0.1f32 as f64` is `0.10000000149011612`: the shortest
+/// representation of the `f32` is parsed instead.
+impl ToStoreFloat for f32 {
+    fn to_store_float(self) -> f64 {
+        format!("{}", self).parse().unwrap_or(self as f64)
+    }
+}
+
+macro_rules! impl_for_float {
+    ($float_type:ident) => (
+        impl From<$float_type> for StoreValueStatic {
+            fn from(f: $float_type) -> StoreValueStatic {
+                StoreValueStatic::Float(f.to_store_float())
+            }
+        }
+    )
+}
+
+impl_for_float!(f64);
+impl_for_float!(f32);
+
 impl From<String> for StoreValueStatic {
     fn from(s: String) -> StoreValueStatic {
         StoreValueStatic::String(s)
@@ -51,3 +153,14 @@ impl From<bool> for StoreValueStatic {
         StoreValueStatic::Boolean(b)
     }
 }
+
+impl<T> From<Option<T>> for StoreValueStatic
+    where T: Into<StoreValueStatic>
+{
+    fn from(o: Option<T>) -> StoreValueStatic {
+        match o {
+            Some(v) => v.into(),
+            None => StoreValueStatic::Null,
+        }
+    }
+}
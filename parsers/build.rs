@@ -14,8 +14,21 @@ fn main() {
         .entry("auto", "KwValue::Auto")
         .entry("expand", "KwValue::Expand")
         .entry("absolute", "KwValue::Absolute")
+        .entry("rtl", "KwValue::Rtl")
+        .entry("ltr", "KwValue::Ltr")
         .entry("fit", "KwValue::Fit")
         .entry("repeat", "KwValue::Repeat")
+        .entry("row", "KwValue::Row")
+        .entry("column", "KwValue::Column")
+        .entry("start", "KwValue::Start")
+        .entry("center", "KwValue::Center")
+        .entry("end", "KwValue::End")
+        .entry("space-between", "KwValue::SpaceBetween")
+        .entry("stretch", "KwValue::Stretch")
+        .entry("grid", "KwValue::Grid")
+        .entry("visible", "KwValue::Visible")
+        .entry("hidden", "KwValue::Hidden")
+        .entry("scroll", "KwValue::Scroll")
         .build(&mut file)
         .unwrap();
 
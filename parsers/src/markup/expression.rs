@@ -0,0 +1,454 @@
+// Dependencies
+use std::iter::Peekable;
+use std::str::Chars;
+use std::str::FromStr;
+use parsing::Error;
+
+use oil_shared::expression::{
+    Expr,
+    Literal,
+    UnaryOp,
+    BinaryOp
+};
+
+/// Parse the content of a data binding (what is inside `{{ }}`).
+///
+/// Grammar, from the lowest to the highest precedence:
+///
+/// ```txt
+///     pipe    := or ( '|' ident [ '(' pipe (',' pipe)* ')' ] )*
+///     or      := and ( '||' and )*
+///     and     := cmp ( '&&' cmp )*
+///     cmp     := add [ ('==' | '!=' | '<' | '<=' | '>' | '>=') add ]
+///     add     := mul ( ('+' | '-') mul )*
+///     mul     := unary ( ('*' | '/' | '%') unary )*
+///     unary   := ('-' | '!') unary | primary
+///     primary := number | string | 'true' | 'false' | 'null'
+///              | path | '(' pipe ')'
+/// ```
+pub fn parse_expression(text: &str) -> Result<Expr, Error> {
+    let tokens = try!(tokenize(text));
+    let mut parser = ExprParser {
+        tokens: tokens,
+        position: 0,
+    };
+    let expr = try!(parser.parse_pipe());
+    match parser.peek() {
+        None => Ok(expr),
+        Some(t) => Err(parser.error(format!("Unexpected `{}`", t.describe()))),
+    }
+}
+
+// ======================================== //
+//                  TOKENS                  //
+// ======================================== //
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(String),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match *self {
+            Token::Number(ref n) => n.clone(),
+            Token::Str(ref s) => format!("\"{}\"", s),
+            Token::Ident(ref i) => i.clone(),
+            Token::Op(op) => op.to_string(),
+            Token::LParen => "(".to_string(),
+            Token::RParen => ")".to_string(),
+            Token::Comma => ",".to_string(),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut col = 0;
+
+    loop {
+        let c = match chars.peek() {
+            Some(&c) => c,
+            None => break,
+        };
+        let start = col;
+
+        if c.is_whitespace() {
+            next(&mut chars, &mut col);
+            continue;
+        }
+
+        let token = match c {
+            '0'...'9' => Token::Number(consume_while(&mut chars, &mut col, is_number_char)),
+            '"' | '\'' => {
+                next(&mut chars, &mut col);
+                let s = consume_while(&mut chars, &mut col, |x| x != c);
+                if next(&mut chars, &mut col).is_none() {
+                    return Err(Error::new(0, start, "Unterminated string".to_string()));
+                }
+                Token::Str(s)
+            }
+            '(' => { next(&mut chars, &mut col); Token::LParen }
+            ')' => { next(&mut chars, &mut col); Token::RParen }
+            ',' => { next(&mut chars, &mut col); Token::Comma }
+            '+' => { next(&mut chars, &mut col); Token::Op("+") }
+            '-' => { next(&mut chars, &mut col); Token::Op("-") }
+            '*' => { next(&mut chars, &mut col); Token::Op("*") }
+            '/' => { next(&mut chars, &mut col); Token::Op("/") }
+            '%' => { next(&mut chars, &mut col); Token::Op("%") }
+            '|' => { next(&mut chars, &mut col); pick(&mut chars, &mut col, '|', "||", "|") }
+            '&' => { next(&mut chars, &mut col); pick(&mut chars, &mut col, '&', "&&", "&") }
+            '=' => { next(&mut chars, &mut col); pick(&mut chars, &mut col, '=', "==", "=") }
+            '!' => { next(&mut chars, &mut col); pick(&mut chars, &mut col, '=', "!=", "!") }
+            '<' => { next(&mut chars, &mut col); pick(&mut chars, &mut col, '=', "<=", "<") }
+            '>' => { next(&mut chars, &mut col); pick(&mut chars, &mut col, '=', ">=", ">") }
+            c if is_path_char(c) => Token::Ident(try!(consume_path(&mut chars, &mut col))),
+            c => return Err(Error::new(0, start, format!("Unexpected character `{}`", c))),
+        };
+
+        tokens.push((start, token));
+    }
+
+    Ok(tokens)
+}
+
+fn next(chars: &mut Peekable<Chars>, col: &mut usize) -> Option<char> {
+    let c = chars.next();
+    if c.is_some() {
+        *col += 1;
+    }
+    c
+}
+
+fn pick(chars: &mut Peekable<Chars>,
+        col: &mut usize,
+        expect: char,
+        if_found: &'static str,
+        otherwise: &'static str) -> Token
+{
+    if chars.peek() == Some(&expect) {
+        next(chars, col);
+        Token::Op(if_found)
+    } else {
+        Token::Op(otherwise)
+    }
+}
+
+fn consume_while<F>(chars: &mut Peekable<Chars>, col: &mut usize, test: F) -> String
+    where F: Fn(char) -> bool
+{
+    let mut result = String::new();
+    loop {
+        match chars.peek() {
+            Some(&c) if test(c) => result.push(c),
+            _ => return result,
+        }
+        next(chars, col);
+    }
+}
+
+/// Consume a property path, including bracketed segments
+/// such as `members[0]` or `stats["str"]`.
+fn consume_path(chars: &mut Peekable<Chars>, col: &mut usize) -> Result<String, Error> {
+    let mut path = String::new();
+    loop {
+        path.push_str(&consume_while(chars, col, is_path_char));
+        if chars.peek() != Some(&'[') {
+            return Ok(path);
+        }
+        let start = *col;
+        let mut quote = None;
+        loop {
+            let c = match next(chars, col) {
+                Some(c) => c,
+                None => return Err(Error::new(0, start, "Unterminated `[`".to_string())),
+            };
+            path.push(c);
+            match (quote, c) {
+                (None, '"') | (None, '\'') => quote = Some(c),
+                (Some(q), c) if q == c => quote = None,
+                (None, ']') => break,
+                _ => (),
+            }
+        }
+    }
+}
+
+fn is_number_char(c: char) -> bool {
+    match c {
+        '0'...'9' | '.' => true,
+        _ => false,
+    }
+}
+
+fn is_path_char(c: char) -> bool {
+    match c {
+        'a'...'z' | 'A'...'Z' | '0'...'9' | '_' | '.' | '$' => true,
+        _ => false,
+    }
+}
+
+// ======================================== //
+//                  PARSER                  //
+// ======================================== //
+
+struct ExprParser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+}
+
+impl ExprParser {
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|&(_, ref t)| t)
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.position).map(|&(_, ref t)| t.clone());
+        self.position += 1;
+        t
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        let found = match self.peek() {
+            Some(&Token::Op(o)) => o == op,
+            _ => false,
+        };
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn error(&self, msg: String) -> Error {
+        let col = self.tokens.get(self.position)
+            .map(|&(c, _)| c)
+            .or(self.tokens.last().map(|&(c, _)| c))
+            .unwrap_or(0);
+        Error::new(0, col, msg)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), Error> {
+        match self.bump() {
+            Some(ref t) if *t == expected => Ok(()),
+            Some(t) => {
+                self.position -= 1;
+                Err(self.error(format!(
+                    "Expected `{}` found: `{}`", expected.describe(), t.describe())))
+            }
+            None => Err(self.error(format!(
+                "Unexpected end of binding, expected `{}`", expected.describe()))),
+        }
+    }
+
+    fn parse_pipe(&mut self) -> Result<Expr, Error> {
+        let mut expr = try!(self.parse_or());
+        while self.eat_op("|") {
+            let name = match self.bump() {
+                Some(Token::Ident(name)) => name,
+                _ => {
+                    self.position -= 1;
+                    return Err(self.error("Expected a filter name after `|`".to_string()));
+                }
+            };
+            let mut args = Vec::new();
+            if self.peek() == Some(&Token::LParen) {
+                self.bump();
+                if self.peek() != Some(&Token::RParen) {
+                    args.push(try!(self.parse_pipe()));
+                    while self.peek() == Some(&Token::Comma) {
+                        self.bump();
+                        args.push(try!(self.parse_pipe()));
+                    }
+                }
+                try!(self.expect(Token::RParen));
+            }
+            expr = Expr::Filter(Box::new(expr), name, args);
+        }
+        Ok(expr)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, Error> {
+        let mut expr = try!(self.parse_and());
+        while self.eat_op("||") {
+            let rhs = try!(self.parse_and());
+            expr = Expr::Binary(BinaryOp::Or, Box::new(expr), Box::new(rhs));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, Error> {
+        let mut expr = try!(self.parse_cmp());
+        while self.eat_op("&&") {
+            let rhs = try!(self.parse_cmp());
+            expr = Expr::Binary(BinaryOp::And, Box::new(expr), Box::new(rhs));
+        }
+        Ok(expr)
+    }
+
+    fn parse_cmp(&mut self) -> Result<Expr, Error> {
+        let expr = try!(self.parse_add());
+        let op = match self.peek() {
+            Some(&Token::Op("==")) => BinaryOp::Eq,
+            Some(&Token::Op("!=")) => BinaryOp::Ne,
+            Some(&Token::Op("<"))  => BinaryOp::Lt,
+            Some(&Token::Op("<=")) => BinaryOp::Le,
+            Some(&Token::Op(">"))  => BinaryOp::Gt,
+            Some(&Token::Op(">=")) => BinaryOp::Ge,
+            _ => return Ok(expr),
+        };
+        self.bump();
+        let rhs = try!(self.parse_add());
+        Ok(Expr::Binary(op, Box::new(expr), Box::new(rhs)))
+    }
+
+    fn parse_add(&mut self) -> Result<Expr, Error> {
+        let mut expr = try!(self.parse_mul());
+        loop {
+            let op = match self.peek() {
+                Some(&Token::Op("+")) => BinaryOp::Add,
+                Some(&Token::Op("-")) => BinaryOp::Sub,
+                _ => return Ok(expr),
+            };
+            self.bump();
+            let rhs = try!(self.parse_mul());
+            expr = Expr::Binary(op, Box::new(expr), Box::new(rhs));
+        }
+    }
+
+    fn parse_mul(&mut self) -> Result<Expr, Error> {
+        let mut expr = try!(self.parse_unary());
+        loop {
+            let op = match self.peek() {
+                Some(&Token::Op("*")) => BinaryOp::Mul,
+                Some(&Token::Op("/")) => BinaryOp::Div,
+                Some(&Token::Op("%")) => BinaryOp::Rem,
+                _ => return Ok(expr),
+            };
+            self.bump();
+            let rhs = try!(self.parse_unary());
+            expr = Expr::Binary(op, Box::new(expr), Box::new(rhs));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, Error> {
+        if self.eat_op("-") {
+            let e = try!(self.parse_unary());
+            Ok(Expr::Unary(UnaryOp::Neg, Box::new(e)))
+        } else if self.eat_op("!") {
+            let e = try!(self.parse_unary());
+            Ok(Expr::Unary(UnaryOp::Not, Box::new(e)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, Error> {
+        match self.bump() {
+            Some(Token::Number(n)) => {
+                if n.contains('.') {
+                    f64::from_str(&n)
+                        .map(|f| Expr::Literal(Literal::Float(f)))
+                        .map_err(|err| self.error(format!("Incorrect float value: {}", err)))
+                } else {
+                    i64::from_str(&n)
+                        .map(|i| Expr::Literal(Literal::Integer(i)))
+                        .map_err(|err| self.error(format!("Incorrect integer value: {}", err)))
+                }
+            }
+            Some(Token::Str(s)) => Ok(Expr::Literal(Literal::String(s))),
+            Some(Token::Ident(i)) => match &i[..] {
+                "true" => Ok(Expr::Literal(Literal::Boolean(true))),
+                "false" => Ok(Expr::Literal(Literal::Boolean(false))),
+                "null" => Ok(Expr::Literal(Literal::Null)),
+                _ => Ok(Expr::Key(i)),
+            },
+            Some(Token::LParen) => {
+                let e = try!(self.parse_pipe());
+                try!(self.expect(Token::RParen));
+                Ok(e)
+            }
+            Some(t) => {
+                self.position -= 1;
+                Err(self.error(format!("Unexpected `{}`", t.describe())))
+            }
+            None => Err(self.error("Unexpected end of binding".to_string())),
+        }
+    }
+}
+
+// ======================================== //
+//                   TESTS                  //
+// ======================================== //
+
+#[cfg(test)]
+mod test {
+
+    use super::parse_expression;
+    use oil_shared::expression::{Expr, Literal, BinaryOp};
+
+    fn key(k: &str) -> Box<Expr> {
+        Box::new(Expr::Key(k.to_string()))
+    }
+
+    fn int(i: i64) -> Box<Expr> {
+        Box::new(Expr::Literal(Literal::Integer(i)))
+    }
+
+    #[test]
+    fn simple_key_should_be_a_key() {
+        assert_eq!(parse_expression(" player.name ").unwrap(), *key("player.name"));
+    }
+
+    #[test]
+    fn arithmetic_should_follow_precedence() {
+        assert_eq!(
+            parse_expression("player.hp * 100 / player.max_hp + 1").unwrap(),
+            Expr::Binary(BinaryOp::Add,
+                Box::new(Expr::Binary(BinaryOp::Div,
+                    Box::new(Expr::Binary(BinaryOp::Mul, key("player.hp"), int(100))),
+                    key("player.max_hp"))),
+                int(1))
+        );
+    }
+
+    #[test]
+    fn pipes_should_apply_to_the_whole_expression() {
+        assert_eq!(
+            parse_expression("a == b | percent(1)").unwrap(),
+            Expr::Filter(
+                Box::new(Expr::Binary(BinaryOp::Eq, key("a"), key("b"))),
+                "percent".to_string(),
+                vec![*int(1)])
+        );
+        assert_eq!(
+            parse_expression("gold | thousands").unwrap(),
+            Expr::Filter(key("gold"), "thousands".to_string(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn paths_should_accept_brackets() {
+        assert_eq!(parse_expression("party.members[0].name").unwrap(),
+                   Expr::Key("party.members[0].name".to_string()));
+        assert_eq!(parse_expression("stats[\"a]b\"] + 1").unwrap(),
+                   Expr::Binary(BinaryOp::Add, key("stats[\"a]b\"]"), int(1)));
+        assert!(parse_expression("stats[\"str\"").is_err());
+    }
+
+    #[test]
+    fn invalid_expressions_should_be_rejected() {
+        assert!(parse_expression("a +").is_err());
+        assert!(parse_expression("(a").is_err());
+        assert!(parse_expression("a | 3").is_err());
+        assert!(parse_expression("\"abc").is_err());
+        assert!(parse_expression("a b").is_err());
+    }
+}
@@ -2,7 +2,7 @@
 use std::collections::HashMap;
 
 use oil_shared::markup::{
-    Node, NodeType, TemplateData, View, Template
+    Node, NodeType, TemplateData, RepeatData, View, Template
 };
 use ErrorReporter;
 
@@ -65,6 +65,44 @@ impl<E> Library<E>
         }
     }
 
+    /// # Check the repeats
+    ///
+    /// Report the `repeat` tags of the views whose template doesn't
+    /// exist or is repeated inside itself. Those repeats are ignored.
+    pub fn check_repeats(&self) {
+        for (_, view) in self.views.iter() {
+            self.check_repeats_for_node(view, &mut Vec::new());
+        }
+    }
+
+    fn check_repeats_for_node(&self, node: &Node, visited: &mut Vec<String>) {
+        if let NodeType::Repeat(RepeatData { ref template_name, .. }) = node.node_type {
+            let location = node.position().map_or(String::new(), |p| format!(" {}", p));
+            if visited.iter().any(|t| t == template_name) {
+                self.err.log(format!(
+                        "Warning{} : Template `{}` is repeated inside itself",
+                        location, template_name));
+            } else {
+                match self.templates.get(template_name) {
+                    None => {
+                        self.err.log(format!(
+                                "Warning{} : `{}` template name not found",
+                                location, template_name));
+                    }
+                    Some(template) => {
+                        visited.push(template_name.clone());
+                        self.check_repeats_for_node(template, visited);
+                        visited.pop();
+                    }
+                }
+            }
+        }
+
+        for child in node.children.iter() {
+            self.check_repeats_for_node(child, visited);
+        }
+    }
+
     fn resolve_templates_for_node(err: &E,
                                   templates: &HashMap<String, Template>,
                                   node: &mut Node)
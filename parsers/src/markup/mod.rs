@@ -7,6 +7,7 @@ use ErrorReporter;
 use oil_shared::markup::Node;
 
 pub use self::lib::Library;
+pub use self::expression::parse_expression;
 
 // Name for the "main" view.
 pub const MAIN_VIEW_NAME: &'static str = "main";
@@ -23,6 +24,7 @@ const REPEAT_TAG: &'static str = "repeat";
 mod parser;
 mod tags;
 mod lib;
+mod expression;
 
 /// Parse the given BufRead.
 ///
@@ -2,6 +2,7 @@
 use xml::reader::EventReader;
 use xml::reader::XmlEvent;
 use xml::attribute::OwnedAttribute;
+use xml::common::Position as XmlPosition;
 use std::io::Read;
 
 use std::collections::HashMap;
@@ -16,6 +17,7 @@ use super::ErrorStatus;
 use super::tags;
 use super::lookup_name;
 use super::ParseError;
+use super::expression::parse_expression;
 
 use super::TEMPLATE_TAG;
 use super::VIEW_TAG;
@@ -30,7 +32,8 @@ use oil_shared::markup::{
     Node,
     View,
     Template,
-    NodeType
+    NodeType,
+    Position
 };
 
 /// Parser
@@ -117,14 +120,12 @@ impl<E, B> Parser<E, B>
 
                 match attr_name {
                     None => {
-                        // FIXME(Waiting the pull request on netvl/xml-rs to be merged)
-                        // let (row, col) = self.parser.get_cursor();
-                        let (row, col) = (0, 0);
+                        let position = self.position();
                         self.err.log(
                             format!(
                                 "Warning {}:{} : `template` has no name add an \
                                  attribute 'name=\"<a-name>\"'",
-                            row, col)
+                            position.line, position.column)
                         );
 
                         self.consume_children(name)
@@ -144,14 +145,12 @@ impl<E, B> Parser<E, B>
                 Ok(())
             }
             _ => {
-                // FIXME(Waiting the pull request on netvl/xml-rs to be merged)
-                // let (row, col) = self.parser.get_cursor();
-                let (row, col) = (0, 0);
+                let position = self.position();
                 self.err.log(
                     format!(
                         "Warning {}:{} : `{}` can't be at root level, \
                         you can only have `template` or `view`"
-                    , row+1, col+1, name));
+                    , position.line, position.column, name));
 
                 self.consume_children(name)
             }
@@ -164,6 +163,7 @@ impl<E, B> Parser<E, B>
                  -> Result<Option<Node>, ParseError>
     {
         let ignore_child = name == TEMPLATE_TAG;
+        let position = self.position();
 
         let node_type = match name {
             TEMPLATE_TAG     => tags::parse_template(attributes),
@@ -173,11 +173,8 @@ impl<E, B> Parser<E, B>
             PROGRESS_BAR_TAG => tags::parse_pbar(attributes),
             REPEAT_TAG       => tags::parse_repeat(attributes),
             _ => {
-                // FIXME(Waiting the pull request on netvl/xml-rs to be merged)
-                // let (row, col) = self.parser.get_cursor();
-                let (row, col) = (0, 0);
                 self.err.log(
-                    format!("Warning {}:{} : Unknown tag `{}`", row+1, col+1, name)
+                    format!("Warning {}:{} : Unknown tag `{}`", position.line, position.column, name)
                 );
                 Err((ErrorType::Warning, ErrorStatus::Reported))
             }
@@ -200,6 +197,7 @@ impl<E, B> Parser<E, B>
             Ok(nt) => {
                 let classes = lookup_name("class", attributes);
                 let mut node = Node::new(classes, nt);
+                node.set_position(Some(position));
 
                 if ignore_child {
 
@@ -217,7 +215,7 @@ impl<E, B> Parser<E, B>
         }
     }
 
-    fn parse_data_binding(&mut self, text: &str, parent: &mut Node) {
+    fn parse_data_binding(&mut self, text: &str, start: Position, parent: &mut Node) {
         let mut buf_consumer = BufferConsumer::new(text.as_bytes());
         loop {
             let text = buf_consumer.consume_while(|c| c != '{').unwrap();
@@ -226,6 +224,10 @@ impl<E, B> Parser<E, B>
                             None,
                             NodeType::Text(text))));
             }
+            let position = match buf_consumer.position() {
+                (0, col) => Position { line: start.line, column: start.column + col },
+                (row, col) => Position { line: start.line + row, column: col + 1 },
+            };
             match buf_consumer.consume_any_char() {
                 Some('{') => {
                     match buf_consumer.consume_any_char() {
@@ -236,13 +238,27 @@ impl<E, B> Parser<E, B>
                                     match buf_consumer.consume_any_char() {
                                         Some('}') => {
                                             // Yahoo! We have a correct data binding
-                                            parent.add(Some(Node::new(
+                                            match parse_expression(&data_binding) {
+                                                Ok(expr) => {
+                                                    let mut node = Node::new(
                                                         None,
-                                                        NodeType::Binding(data_binding))));
+                                                        NodeType::Binding(expr));
+                                                    node.set_position(Some(position));
+                                                    parent.add(Some(node));
+                                                }
+                                                Err(err) => {
+                                                    self.err.log(format!("Warning {}:{} : Invalid data binding `{}`: {}", position.line, position.column, data_binding, err));
+                                                    data_binding.insert(0, '{');
+                                                    data_binding.insert(0, '{');
+                                                    data_binding.push_str("}}");
+                                                    parent.add(Some(Node::new(
+                                                                None,
+                                                                NodeType::Text(data_binding))));
+                                                }
+                                            }
                                         }
                                         _ => {
-                                            let (row, col) = (0, 0);
-                                            self.err.log(format!("Warning {}:{} : Missing '}}' for data binding", row, col));
+                                            self.err.log(format!("Warning {}:{} : Missing '}}' for data binding", position.line, position.column));
                                             data_binding.insert(0, '{');
                                             data_binding.insert(0, '{');
                                             data_binding.push('}');
@@ -254,8 +270,7 @@ impl<E, B> Parser<E, B>
                                     }
                                 }
                                 _ => {
-                                    let (row, col) = (0, 0);
-                                    self.err.log(format!("Warning {}:{} : Missing '}}' for data binding", row, col));
+                                    self.err.log(format!("Warning {}:{} : Missing '}}' for data binding", position.line, position.column));
                                     data_binding.insert(0, '{');
                                     data_binding.insert(0, '{');
                                     parent.add(Some(Node::new(
@@ -266,8 +281,7 @@ impl<E, B> Parser<E, B>
                             }
                         }
                         _ => {
-                            let (row, col) = (0, 0);
-                            self.err.log(format!("Warning {}:{} : Missing \"{{ .. }}}}\" for data binding", row, col));
+                            self.err.log(format!("Warning {}:{} : Missing \"{{ .. }}}}\" for data binding", position.line, position.column));
                             let text = "{".to_string();
                             parent.add(Some(Node::new(
                                         None,
@@ -281,22 +295,29 @@ impl<E, B> Parser<E, B>
         }
     }
 
+    /// Position of the last event read.
+    fn position(&self) -> Position {
+        let pos = self.parser.position();
+        Position {
+            line: pos.row as usize + 1,
+            column: pos.column as usize + 1,
+        }
+    }
+
     fn report_error_if_needed(&mut self,
                               parse_error: ParseError) -> ParseError
     {
-        // FIXME(Waiting the pull request on netvl/xml-rs to be merged)
-        // let (row, col) = self.parser.get_cursor();
-        let (row, col) = (0, 0);
+        let position = self.position();
         match parse_error {
             (ErrorType::Fatal, ErrorStatus::NotReported(msg)) => {
                 self.err.log(
-                    format!("Error {}:{} : {}", row+1, col+1, msg)
+                    format!("Error {}:{} : {}", position.line, position.column, msg)
                 );
                 (ErrorType::Fatal, ErrorStatus::Reported)
             }
             (ErrorType::Warning, ErrorStatus::NotReported(msg)) => {
                 self.err.log(
-                    format!("Warning {}:{} : {}", row+1, col+1, msg)
+                    format!("Warning {}:{} : {}", position.line, position.column, msg)
                 );
                 (ErrorType::Warning, ErrorStatus::Reported)
             }
@@ -322,12 +343,10 @@ impl<E, B> Parser<E, B>
 
                     depth += 1;
 
-                    // FIXME(Waiting the pull request on netvl/xml-rs to be merged)
-                    // let (row, col) = self.parser.get_cursor();
-                    let (row, col) = (0, 0);
+                    let position = self.position();
                     self.err.log(
                         format!("Warning {}:{}, `{}` has been ignored",
-                                row+1, col+1, name)
+                                position.line, position.column, name)
                     );
                 }
                 XmlEvent::EndElement { name } => {
@@ -379,7 +398,8 @@ impl<E, B> Parser<E, B>
                     return Ok(());
                 }
                 XmlEvent::Characters( text ) => {
-                    self.parse_data_binding(&text, parent);
+                    let start = self.position();
+                    self.parse_data_binding(&text, start, parent);
                 }
                 XmlEvent::EndDocument => unreachable!(),
                 _ => ()
@@ -393,7 +413,19 @@ impl<E, B> Parser<E, B>
 mod test {
 
     use std::io::BufReader;
-    use EmptyErrorReporter;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use {EmptyErrorReporter, ErrorReporter};
+
+    /// Keeps the messages logged by the parser.
+    #[derive(Clone, Default)]
+    struct LogReporter(Rc<RefCell<Vec<String>>>);
+
+    impl ErrorReporter for LogReporter {
+        fn log(&self, msg: String) {
+            self.0.borrow_mut().push(msg);
+        }
+    }
 
     #[test]
     fn reject_invalid_root_tags() {
@@ -469,11 +501,72 @@ mod test {
         assert_eq!(res.templates.len(), 0);
     }
 
+    #[test]
+    fn parse_binding_expressions() {
+        use oil_shared::markup::NodeType;
+        use oil_shared::expression::Expr;
+
+        let reader = BufReader::new(
+            "<view>\
+                Gold: {{player.gold | thousands}}{{player.hp +}}\
+             </view>
+            ".as_bytes());
+        let mut parser = super::Parser::new(EmptyErrorReporter, reader);
+
+        let res = parser.parse();
+        let view = res.views.values().next().unwrap();
+
+        assert_eq!(view.children.len(), 3);
+        match view.children[1].node_type {
+            NodeType::Binding(Expr::Filter(_, ref name, _)) => assert_eq!(name, "thousands"),
+            _ => panic!("Expected a filtered binding"),
+        }
+        assert_eq!(view.children[2].node_type, NodeType::Text("{{player.hp +}}".to_string()));
+    }
+
+    #[test]
+    fn invalid_binding_should_be_reported_at_its_position() {
+        let reader = BufReader::new(
+            "<view>\n  \
+               Gold: {{player.gold +}}\n\
+             </view>
+            ".as_bytes());
+        let log = LogReporter::default();
+        let mut parser = super::Parser::new(log.clone(), reader);
+        parser.parse();
+
+        let messages = log.0.borrow();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].starts_with("Warning 2:9 : Invalid data binding `player.gold +`"),
+                "{}", messages[0]);
+    }
+
+    #[test]
+    fn nodes_should_know_their_position() {
+        use oil_shared::markup::Position;
+
+        let reader = BufReader::new(
+            "<view>\n  \
+               <group>\n    \
+                 Gold: {{player.gold}}\n  \
+               </group>\n\
+             </view>
+            ".as_bytes());
+        let mut parser = super::Parser::new(EmptyErrorReporter, reader);
+
+        let res = parser.parse();
+        let view = res.views.values().next().unwrap();
+        let group = &view.children[0];
+
+        assert_eq!(group.position(), Some(Position { line: 2, column: 3 }));
+        assert_eq!(group.children[1].position(), Some(Position { line: 3, column: 11 }));
+    }
+
     #[test]
     fn accept_well_formed_repeat() {
         let reader = BufReader::new(
             "<view>\
-                <repeat iter=\"{arf}\" template-name=\"test\"/>\
+                <repeat iter=\"{{arf}}\" template-name=\"test\"/>\
              </view>
             ".as_bytes());
         let mut parser = super::Parser::new(EmptyErrorReporter, reader);
@@ -484,4 +577,82 @@ mod test {
         assert_eq!(res.views.values().next().unwrap().children.len(), 1);
         assert_eq!(res.templates.len(), 0);
     }
+
+    #[test]
+    fn accept_sorted_and_paged_repeat() {
+        use oil_shared::markup::NodeType;
+
+        let reader = BufReader::new(
+            "<view>\
+                <repeat iter=\"{{shop.items}}\" template-name=\"item\" sort-by=\"-item.price\" \
+                        filter=\"{{item.category == shop.tab}}\" page-size=\"8\" page=\"{{shop.page}}\" key=\"id\"/>\
+                <repeat iter=\"{{shop.items}}\" template-name=\"item\" page-size=\"none\"/>\
+             </view>
+            ".as_bytes());
+        let mut parser = super::Parser::new(EmptyErrorReporter, reader);
+
+        let res = parser.parse();
+        let view = res.views.values().next().unwrap();
+
+        assert_eq!(view.children.len(), 1);
+        match view.children[0].node_type {
+            NodeType::Repeat(ref data) => {
+                assert_eq!(data.sort_by, Some("-item.price".to_string()));
+                assert_eq!(data.filter, Some("{{item.category == shop.tab}}".to_string()));
+                assert_eq!(data.page_size, Some(8));
+                assert_eq!(data.page, Some("{{shop.page}}".to_string()));
+                assert_eq!(data.key, Some("id".to_string()));
+            }
+            _ => panic!("Expected a repeat"),
+        }
+    }
+
+    #[test]
+    fn invalid_repeat_attributes_should_be_reported() {
+        let reader = BufReader::new(
+            "<view>\n\
+                <repeat iter=\"items\" template-name=\"item\"/>\n\
+                <repeat iter=\"{{items}}\" template-name=\"item\" filter=\"{{item.price >}}\"/>\n\
+                <repeat iter=\"{{items}}\" template-name=\"item\" page=\"first\"/>\n\
+                <repeat iter=\"{{items}}\" template-name=\"item\" sort-by=\"price\"/>\n\
+             </view>
+            ".as_bytes());
+        let log = LogReporter::default();
+        let mut parser = super::Parser::new(log.clone(), reader);
+
+        let res = parser.parse();
+
+        assert_eq!(res.views.values().next().unwrap().children.len(), 0);
+        let messages = log.0.borrow();
+        assert_eq!(messages.len(), 4);
+        assert!(messages[0].starts_with("Warning 2:"), "{}", messages[0]);
+        assert!(messages[0].contains("`iter`"), "{}", messages[0]);
+        assert!(messages[1].contains("`filter`"), "{}", messages[1]);
+        assert!(messages[2].contains("`page`"), "{}", messages[2]);
+        assert!(messages[3].contains("`sort-by`"), "{}", messages[3]);
+    }
+
+    #[test]
+    fn unknown_and_recursive_repeated_templates_should_be_reported() {
+        let reader = BufReader::new(
+            "<template name=\"node\">\n\
+                <repeat iter=\"{{children}}\" template-name=\"node\"/>\n\
+             </template>\n\
+             <view>\n\
+                <repeat iter=\"{{tree}}\" template-name=\"node\"/>\n\
+                <repeat iter=\"{{items}}\" template-name=\"item\"/>\n\
+             </view>
+            ".as_bytes());
+        let log = LogReporter::default();
+        let mut parser = super::Parser::new(log.clone(), reader);
+
+        let res = parser.parse();
+        res.check_repeats();
+
+        let messages = log.0.borrow();
+        assert_eq!(*messages, vec![
+            "Warning 2:1 : Template `node` is repeated inside itself".to_string(),
+            "Warning 6:1 : `item` template name not found".to_string(),
+        ]);
+    }
 }
@@ -5,6 +5,7 @@ use super::ErrorType;
 use super::ErrorStatus;
 use super::lookup_name;
 use super::HasNodeChildren;
+use super::expression::parse_expression;
 
 use oil_shared::markup::{
     Node,
@@ -83,9 +84,64 @@ pub fn parse_repeat(attributes: &Vec<OwnedAttribute>) -> ResOrError {
            lookup_name("iter", attributes))
     {
         (Some(name), Some(iter)) => {
+            if binding_content(&iter).map_or(true, |key| key.is_empty()) {
+                return Err((
+                    ErrorType::Warning,
+                    ErrorStatus::NotReported(
+                        "`iter` attribute in `repeat` must be a data binding such as `{{items}}`")
+                ));
+            }
+            let filter = lookup_name("filter", attributes);
+            if let Some(ref filter) = filter {
+                if binding_content(filter).map_or(true, |expr| parse_expression(expr).is_err()) {
+                    return Err((
+                        ErrorType::Warning,
+                        ErrorStatus::NotReported(
+                            "`filter` attribute in `repeat` must be a valid data binding")
+                    ));
+                }
+            }
+            let sort_by = lookup_name("sort-by", attributes);
+            if let Some(ref key) = sort_by {
+                let key = key.trim();
+                let key = if key.starts_with('-') { &key[1..] } else { key };
+                if !key.starts_with("item.") || key.len() == "item.".len() {
+                    return Err((
+                        ErrorType::Warning,
+                        ErrorStatus::NotReported(
+                            "`sort-by` attribute in `repeat` must be a key of `item` such as `item.price`")
+                    ));
+                }
+            }
+            let page = lookup_name("page", attributes);
+            if let Some(ref page) = page {
+                if binding_content(page).is_none() && page.trim().parse::<usize>().is_err() {
+                    return Err((
+                        ErrorType::Warning,
+                        ErrorStatus::NotReported(
+                            "`page` attribute in `repeat` must be an integer or a data binding")
+                    ));
+                }
+            }
+            let page_size = match lookup_name("page-size", attributes) {
+                Some(size) => match size.trim().parse() {
+                    Ok(size) if size > 0 => Some(size),
+                    _ => return Err((
+                        ErrorType::Warning,
+                        ErrorStatus::NotReported(
+                            "`page-size` attribute in `repeat` must be a positive integer")
+                    )),
+                },
+                None => None,
+            };
             Ok(NodeType::Repeat(RepeatData {
                 template_name: name,
-                iter: iter
+                iter: iter,
+                sort_by: sort_by,
+                filter: filter,
+                page_size: page_size,
+                page: page,
+                key: lookup_name("key", attributes),
             }))
         }
         (None, _) => {
@@ -104,3 +160,13 @@ pub fn parse_repeat(attributes: &Vec<OwnedAttribute>) -> ResOrError {
         }
     }
 }
+
+/// Returns the content of an attribute value of the form `{{...}}`.
+fn binding_content(value: &str) -> Option<&str> {
+    let value = value.trim();
+    if value.starts_with("{{") && value.ends_with("}}") && value.len() >= 4 {
+        Some(value[2..value.len() - 2].trim())
+    } else {
+        None
+    }
+}
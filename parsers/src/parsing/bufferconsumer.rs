@@ -96,35 +96,35 @@ impl<B> BufferConsumer<B>
     }
 
     pub fn consume_any_char(&mut self) -> Option<char> {
-        if self.tmp_char.is_none() {
-            match self.buffer.next().and_then(|a| a.ok()) {
-                Some(c) => {
-                    if c == '\n' {
-                        self.row += 1;
-                        self.col = 0;
-                    } else {
-                        self.col += 1;
-                    }
-                    Some(c)
-                }
-                None => None
+        let c = match self.tmp_char.take() {
+            Some(c) => Some(c),
+            None => self.buffer.next().and_then(|a| a.ok()),
+        };
+        match c {
+            Some('\n') => {
+                self.row += 1;
+                self.col = 0;
             }
-        } else {
-            let c = self.tmp_char.unwrap();
-            self.tmp_char = None;
-            Some(c)
+            Some(_) => self.col += 1,
+            None => (),
         }
+        c
     }
 
     pub fn look_next_char(&mut self) -> Option<char> {
 
         if self.tmp_char.is_none() {
-            self.tmp_char = self.consume_any_char();
+            self.tmp_char = self.buffer.next().and_then(|a| a.ok());
         }
 
         self.tmp_char
     }
 
+    /// Row and column of the next character, starting at 0.
+    pub fn position(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
     pub fn error(&self, msg: &str) -> Error {
         Error::new(self.row, self.col, msg.to_string())
     }
@@ -160,7 +160,11 @@ impl<'a, 'b, R, E, B> Parser<'a, 'b, R, E, B>
             _ => return Err(self.bc.error("Invalid identifier expected `:`"))
         }
 
-        let value = try!(self.parse_value());
+        let value = if name == "z-index" {
+            try!(self.parse_integer())
+        } else {
+            try!(self.parse_value())
+        };
 
         try!(self.bc.consume_whitespace());
         match self.bc.consume_any_char() {
@@ -217,6 +221,25 @@ impl<'a, 'b, R, E, B> Parser<'a, 'b, R, E, B>
         }
     }
 
+    /// Same as `parse_value` for a value that must be
+    /// an integer, such as a `z-index`. It can be negative.
+    fn parse_integer(&mut self) -> Result<Value, Error> {
+
+        try!(self.bc.consume_whitespace());
+        let sign = match self.bc.look_next_char() {
+            Some('-') => {
+                self.bc.consume_any_char();
+                -1f32
+            }
+            _ => 1f32,
+        };
+
+        match try!(self.parse_value()) {
+            Value::Length(val, unit) if val.fract() == 0f32 => Ok(Value::Length(sign * val, unit)),
+            _ => Err(self.bc.error("Expected an integer")),
+        }
+    }
+
     fn consume_unit(&mut self) -> Result<Unit, Error> {
         try!(self.bc.consume_identifier());
         Ok(Unit::Px)
@@ -249,3 +272,47 @@ fn convert_to_style_value<R>(ctor: &Constructor, resource_manager: &mut R)
         Constructor::None => None,
     }
 }
+
+#[cfg(test)]
+mod test {
+
+    use std::io::BufReader;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use ErrorReporter;
+    use oil_shared::deps::StyleDefinitions;
+    use oil_shared::resource::create_null_manager;
+    use oil_shared::style::{Value, Unit};
+
+    /// Keeps the messages logged by the parser.
+    #[derive(Clone, Default)]
+    struct LogReporter(Rc<RefCell<Vec<String>>>);
+
+    impl ErrorReporter for LogReporter {
+        fn log(&self, msg: String) {
+            self.0.borrow_mut().push(msg);
+        }
+    }
+
+    #[test]
+    fn z_index_should_be_an_integer() {
+        let reader = BufReader::new(
+            ".back { z-index: -2; }\n\
+             .popup { z-index: 1.5; }".as_bytes());
+        let log = LogReporter::default();
+        let defs = StyleDefinitions::new();
+        let mut manager = create_null_manager();
+        let mut parser = super::Parser::new(log.clone(), reader, &defs, &mut manager);
+
+        let res = parser.parse();
+
+        assert_eq!(res.rules.len(), 1);
+        match res.rules[0].declarations[0].value {
+            Value::Length(z, Unit::Px) => assert_eq!(z, -2f32),
+            ref value => panic!("{:?}", value),
+        }
+        let messages = log.0.borrow();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains("Expected an integer"), "{}", messages[0]);
+    }
+}
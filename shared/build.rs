@@ -18,6 +18,12 @@ fn main() {
         .entry("bottom", "PropertyName::BOTTOM")
         .entry("height", "PropertyName::HEIGHT")
         .entry("width", "PropertyName::WIDTH")
+        // Size constraints
+        .entry("min-width", "PropertyName::MIN_WIDTH")
+        .entry("max-width", "PropertyName::MAX_WIDTH")
+        .entry("min-height", "PropertyName::MIN_HEIGHT")
+        .entry("max-height", "PropertyName::MAX_HEIGHT")
+        .entry("aspect-ratio", "PropertyName::ASPECT_RATIO")
         // Margin properties
         .entry("margin", "PropertyName::MARGIN")
         .entry("margin-left", "PropertyName::MARGIN_LEFT")
@@ -38,6 +44,24 @@ fn main() {
         .entry("border-bottom", "PropertyName::BORDER_BOTTOM")
         // Layout mode (absolute / rtl / ltr)
         .entry("layout", "PropertyName::LAYOUT_MODE")
+        // Stacking of the children (row / column)
+        .entry("direction", "PropertyName::DIRECTION")
+        .entry("justify-content", "PropertyName::JUSTIFY_CONTENT")
+        .entry("align-items", "PropertyName::ALIGN_ITEMS")
+        .entry("gap", "PropertyName::GAP")
+        // Grid (display: grid)
+        .entry("display", "PropertyName::DISPLAY")
+        .entry("grid-columns", "PropertyName::GRID_COLUMNS")
+        .entry("grid-rows", "PropertyName::GRID_ROWS")
+        .entry("cell-width", "PropertyName::CELL_WIDTH")
+        .entry("cell-height", "PropertyName::CELL_HEIGHT")
+        // Clipping of the children (hidden / scroll)
+        .entry("overflow", "PropertyName::OVERFLOW")
+        // Text
+        .entry("font", "PropertyName::FONT")
+        .entry("font-size", "PropertyName::FONT_SIZE")
+        // Stacking
+        .entry("z-index", "PropertyName::Z_INDEX")
         // Background image
         .entry("background-image", "PropertyName::BACKGROUND_IMAGE")
         .entry("background-image-rule", "PropertyName::BACKGROUND_IMAGE_RULE")
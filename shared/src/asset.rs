@@ -4,7 +4,7 @@ use std::path::Path;
 use deps::Constructor;
 use resource::{BasicResourceManager, ResourceId};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FontData;
 
 // TODO handle shared images somehow
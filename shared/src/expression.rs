@@ -0,0 +1,81 @@
+/// Expression found inside a data binding `{{ ... }}`.
+///
+/// It is produced once by the markup parser and evaluated
+/// by oil each time one of the keys it depends on changes.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Expr {
+    /// A property path such as `player.name`.
+    Key(String),
+    /// A constant value such as `100` or `"gold"`.
+    Literal(Literal),
+    /// `-a` or `!a`
+    Unary(UnaryOp, Box<Expr>),
+    /// `a + b`, `a == b`, ...
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// `value | name(arg0, arg1, ...)`
+    Filter(Box<Expr>, String, Vec<Expr>),
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Literal {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Null,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum BinaryOp {
+    // Arithmetic (`+` is also the string concatenation)
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    // Comparisons
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    // Logic
+    And,
+    Or,
+}
+
+impl Expr {
+
+    /// Returns the property keys this expression depends on,
+    /// in the order they appear.
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys = Vec::new();
+        self.collect_keys(&mut keys);
+        keys
+    }
+
+    fn collect_keys<'a>(&'a self, keys: &mut Vec<&'a str>) {
+        match *self {
+            Expr::Key(ref k) => keys.push(k),
+            Expr::Literal(_) => (),
+            Expr::Unary(_, ref e) => e.collect_keys(keys),
+            Expr::Binary(_, ref l, ref r) => {
+                l.collect_keys(keys);
+                r.collect_keys(keys);
+            }
+            Expr::Filter(ref e, _, ref args) => {
+                e.collect_keys(keys);
+                for a in args.iter() {
+                    a.collect_keys(keys);
+                }
+            }
+        }
+    }
+}
@@ -5,4 +5,5 @@ pub mod resource;
 pub mod style;
 pub mod deps;
 pub mod markup;
+pub mod expression;
 pub mod properties;
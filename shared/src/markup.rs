@@ -1,9 +1,11 @@
 use std::collections::HashSet;
+use std::fmt;
+use expression::Expr;
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum NodeType {
     Text(String),
-    Binding(String),
+    Binding(Expr),
     Group,
     Button(ButtonData),
     LineInput(LineInputData),
@@ -15,13 +17,46 @@ pub enum NodeType {
     RootTemplate
 }
 
+impl NodeType {
+
+    /// Name of the markup tag of this node.
+    pub fn tag_name(&self) -> &'static str {
+        match *self {
+            NodeType::Text(_) => "text",
+            NodeType::Binding(_) => "binding",
+            NodeType::Group => "group",
+            NodeType::Button(_) => "button",
+            NodeType::LineInput(_) => "line-input",
+            NodeType::ProgressBar(_) => "progress-bar",
+            NodeType::Template(_) => "template",
+            NodeType::Repeat(_) => "repeat",
+            NodeType::RootView => "view",
+            NodeType::RootTemplate => "template",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Node {
     pub children: Vec<Node>,
     classes: Option<String>,
+    position: Option<Position>,
     pub node_type: NodeType,
 }
 
+/// Where a node starts in the markup file, starting at 1.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 impl Node {
     pub fn tree_size(&self) -> usize {
         let mut count = 1;
@@ -41,7 +76,8 @@ pub fn new_template(classes: Option<String>) -> Template {
     Node {
         children: Vec::new(),
         node_type: NodeType::RootTemplate,
-        classes: classes
+        classes: classes,
+        position: None,
     }
 }
 
@@ -50,6 +86,7 @@ pub fn new_view(classes: Option<String>) -> View {
         children: Vec::new(),
         node_type: NodeType::RootView,
         classes: classes,
+        position: None,
     }
 }
 
@@ -60,6 +97,7 @@ impl Node {
             children: Vec::new(),
             node_type: nt,
             classes: classes,
+            position: None,
         }
     }
 
@@ -67,7 +105,18 @@ impl Node {
         Node {
             children: other.children.clone(),
             node_type: nt,
-            classes: None
+            classes: None,
+            position: None,
+        }
+    }
+
+    /// Returns a copy of this node without its children.
+    pub fn without_children(&self) -> Node {
+        Node {
+            children: Vec::new(),
+            node_type: self.node_type.clone(),
+            classes: self.classes.clone(),
+            position: self.position,
         }
     }
 
@@ -77,6 +126,14 @@ impl Node {
             None => HashSet::new()
         }
     }
+
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Option<Position>) {
+        self.position = position;
+    }
 }
 
 // ------------------------------------------------- Button tag
@@ -111,4 +168,9 @@ pub struct TemplateData {
 pub struct RepeatData {
     pub template_name: String,
     pub iter: String,
+    pub sort_by: Option<String>,
+    pub filter: Option<String>,
+    pub page_size: Option<usize>,
+    pub page: Option<String>,
+    pub key: Option<String>,
 }
@@ -14,6 +14,13 @@ pub enum PropertyName {
     BOTTOM,
     HEIGHT,
     WIDTH,
+    // Size constraints
+    MIN_WIDTH,
+    MAX_WIDTH,
+    MIN_HEIGHT,
+    MAX_HEIGHT,
+    // Width divided by the height
+    ASPECT_RATIO,
     // Margin properties
     MARGIN,
     MARGIN_LEFT,
@@ -33,7 +40,28 @@ pub enum PropertyName {
     BORDER_TOP,
     BORDER_BOTTOM,
     // Layout mode (absolute / rtl / ltr)
+    // With `rtl`, the lines of the children start on the right.
+    // The direction is inherited by the children.
     LAYOUT_MODE,
+    // Stacking of the children (row / column)
+    DIRECTION,
+    JUSTIFY_CONTENT,
+    ALIGN_ITEMS,
+    GAP,
+    // Grid (display: grid)
+    DISPLAY,
+    GRID_COLUMNS,
+    GRID_ROWS,
+    CELL_WIDTH,
+    CELL_HEIGHT,
+    // Clipping of the children (hidden / scroll)
+    OVERFLOW,
+    // Text (font-size is a length, font a dep)
+    // Inherited by the text of the children.
+    FONT,
+    FONT_SIZE,
+    // Painting order, see `LayoutBuffer::paint_order`
+    Z_INDEX,
 
     /// Background
     /// Possibles rules:
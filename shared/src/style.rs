@@ -2,12 +2,12 @@
 use asset;
 //use color::alpha::Rgba;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Stylesheet {
     pub rules: Vec<Rule>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Rule {
     pub selector: Selector,
     pub declarations: Vec<Declaration>,
@@ -21,26 +21,18 @@ pub enum SelectorState {
     Creation
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Selector {
     pub name: String,
     pub state: SelectorState
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Declaration {
     pub name: String,
     pub value: Value,
 }
 
-// TODO: FIXME
-// Remember to have a property layout to either
-// render right to left (rtl) or left to right (ltr)
-// ```
-//      layout: rtl; // ltr is default
-// ```
-// That does not affect the layout algorithm
-// instead, the x property is inversed as parent.width - x
 // Note: KeywordAuto, KeywordAbsolute, etc ... should be merged
 // at this point into Keyword(String). Leaving this for later.
 //
@@ -58,8 +50,21 @@ pub enum KwValue {
     Auto,
     Expand,
     Absolute,
+    Rtl,
+    Ltr,
     Fit,
-    Repeat
+    Repeat,
+    Row,
+    Column,
+    Start,
+    Center,
+    End,
+    SpaceBetween,
+    Stretch,
+    Grid,
+    Visible,
+    Hidden,
+    Scroll
 }
 
 #[derive(Debug, Clone)]
@@ -1,20 +1,120 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::iter;
 
 // use data_bindings::{StoreValue, DBCLookup};
-use markup::{View, Template, NodeType};
+use oil_databindings::context::{ScopedContext, DirtyKeys};
+use oil_databindings::store::StoreValueStatic;
+use oil_databindings::PropertyAccessor;
+use oil_shared::expression::Expr;
+use markup::{Node, View, Template, NodeType, RepeatData, parse_expression};
 use util::BufferFromTree;
 use layout::LayoutBuffer;
 use DataBindingsContext;
+use super::expression;
+use super::validation::{self, BoundKey, UnresolvedKey};
+use super::{AttributeGetResult, Store};
 
 pub struct DataBindingBuffer {
+    // The view without the instances of its repeats.
+    view: View,
     bindings: BufferFromTree<DataBindingNode>,
+    repeats: Vec<RepeatNode>,
+    // Origin of each node of the expanded view.
+    origins: Vec<Origin>,
+    // Every key used by the view, kept for `validate`.
+    keys: Vec<BoundKey>,
     // iterators: BufferFromTree<IteratorNode>,
     // iterator_bindings: BufferFromTree<IteratorBindingNode>,
 }
 
 struct DataBindingNode {
-    key: String,
-    // current: StoreValue,
+    expr: Expr,
+    // Keys the expression depends on.
+    keys: Vec<String>,
+    // `None` until the binding has been evaluated once.
+    current: Option<StoreValueStatic>,
+}
+
+/// A `repeat` tag. The bindings of its template are evaluated
+/// in the scope of each item.
+struct RepeatNode {
+    // Index of the repeat node in its view or template.
+    index: usize,
+    // Instantiated for each item.
+    template: Template,
+    // Key of the list.
+    iter: String,
+    // Key of the items and true for a descending order.
+    sort_by: Option<(String, bool)>,
+    filter: Option<Expr>,
+    page_size: Option<usize>,
+    page: Option<Page>,
+    // Key of the items identifying them.
+    key: Option<String>,
+    bindings: Vec<Expr>,
+    repeats: Vec<RepeatNode>,
+    // Keys the repeat depends on, outside of the items.
+    keys: Vec<String>,
+    // Keys of the template, looked up in the items first.
+    item_keys: Vec<String>,
+    // The item keys that some item doesn't have, which are then
+    // resolved outside of the items. Updated with `current`.
+    outer_keys: Vec<String>,
+    current: Vec<Instance>,
+    // False until the items have been evaluated once.
+    evaluated: bool,
+    // The instances have been added, removed or reordered
+    // since the view was last expanded.
+    reshaped: bool,
+}
+
+/// An item of a `RepeatNode`, once filtered, sorted and paged.
+#[derive(PartialEq, Debug)]
+struct Instance {
+    id: InstanceId,
+    values: Vec<StoreValueStatic>,
+    // Instances of the repeats of the template.
+    repeats: Vec<Vec<Instance>>,
+}
+
+/// Identity of an instance: the value of the `key` attribute of the
+/// item, which is kept when the items are reordered. Items without
+/// that key are identified by their index in the bound list.
+#[derive(Clone, PartialEq, Debug)]
+enum InstanceId {
+    Key(StoreValueStatic),
+    Index(usize),
+}
+
+/// Where a node of the expanded view comes from. It doesn't
+/// change when the instances of a repeat are reordered.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Origin {
+    // The instances holding the node, outermost first, with the
+    // index of their repeat node in its view or template.
+    instances: Vec<(usize, InstanceId)>,
+    // Index of the node in its view or template.
+    index: usize,
+}
+
+/// The nodes of a view expanded with the instances of its repeats.
+struct Expansion {
+    origins: Vec<Origin>,
+    // Index and text of the nodes of the repeated bindings.
+    texts: Vec<(usize, String)>,
+}
+
+/// The repeats of a view or of an instance, with their instances.
+struct ExpansionScope<'a> {
+    instances: Vec<(usize, InstanceId)>,
+    repeats: &'a [RepeatNode],
+    lists: Vec<&'a [Instance]>,
+}
+
+enum Page {
+    Fixed(usize),
+    Key(String),
 }
 
 // struct IteratorNode {
@@ -29,14 +129,279 @@ struct DataBindingNode {
 // }
 
 impl DataBindingNode {
-//     fn update<G, V>(&mut self, context: &ContextManager<G, V>, layout: &mut LayoutBuffer, lookup: usize) -> bool {
-//         // TODO
-//     }
 
-    fn new(key: String) -> DataBindingNode {
+    /// Returns true if the binding needs to query the context again.
+    /// Without change tracking, this is always the case.
+    fn is_dirty(&self, dirty_keys: Option<&DirtyKeys>) -> bool {
+        match (self.current.as_ref(), dirty_keys) {
+            (Some(_), Some(dirty)) => self.keys.iter().any(|k| dirty.is_dirty(k)),
+            _ => true,
+        }
+    }
+
+    /// Evaluate the expression and returns true if the value has changed.
+    /// The new value is given to the layout as the text of the node.
+    fn update<C>(&mut self, context: &C, scope: &ScopedContext, layout: &mut LayoutBuffer, lookup: usize) -> bool
+        where C: DataBindingsContext
+    {
+        let value = expression::evaluate(&self.expr, context, scope);
+
+        if self.current.as_ref() != Some(&value) {
+            layout.set_text(lookup, value.to_string());
+            self.current = Some(value);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn new(expr: Expr) -> DataBindingNode {
         DataBindingNode {
-            key: key,
-//             current: StoreValue::Integer(0),
+            keys: expr.keys().iter().map(|k| k.to_string()).collect(),
+            expr: expr,
+            current: None,
+        }
+    }
+}
+
+impl RepeatNode {
+
+    /// See `DataBindingNode::is_dirty`.
+    fn is_dirty(&self, dirty_keys: Option<&DirtyKeys>) -> bool {
+        match dirty_keys {
+            Some(dirty) if self.evaluated => self.keys.iter()
+                .chain(self.outer_keys.iter())
+                .any(|k| dirty.is_dirty(k)),
+            _ => true,
+        }
+    }
+
+    /// Evaluate the template for each item and returns true if a value has changed.
+    fn update<C>(&mut self, context: &C, scope: &ScopedContext) -> bool
+        where C: DataBindingsContext
+    {
+        let (instances, outer_keys) = self.evaluate(context, scope);
+        self.outer_keys = outer_keys;
+        self.evaluated = true;
+        if self.current != instances {
+            self.reshaped |= !same_instances(&self.current, &instances);
+            self.current = instances;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the instances and the item keys that some item doesn't have.
+    fn evaluate<C>(&self, context: &C, scope: &ScopedContext) -> (Vec<Instance>, Vec<String>)
+        where C: DataBindingsContext
+    {
+        let mut instances = Vec::new();
+        let mut outer_keys = Vec::new();
+        // The list can be shared with the application:
+        // its items are only borrowed for this call.
+        scope.with_lookup(&self.iter, &mut |result| {
+            let items: Vec<_> = match result {
+                AttributeGetResult::IterableType(items) => items.collect(),
+                // An absent list
+                _ => Vec::new(),
+            };
+            let ids = self.instances(context, scope, &items);
+            instances = ids.iter().enumerate().map(|(index, &id)| {
+                let item_scope = scope.child(items[id], index, ids.len());
+                let key = self.key.as_ref().and_then(|key| {
+                    items[id].get_attribute(PropertyAccessor::new(key)).value()
+                });
+                Instance {
+                    id: key.map_or(InstanceId::Index(id), InstanceId::Key),
+                    values: self.bindings.iter()
+                        .map(|expr| expression::evaluate(expr, context, &item_scope))
+                        .collect(),
+                    repeats: self.repeats.iter()
+                        .map(|repeat| repeat.evaluate(context, &item_scope).0)
+                        .collect(),
+                }
+            }).collect();
+            outer_keys = self.item_keys.iter().filter(|key| {
+                items.iter().any(|item| !item.get_attribute(PropertyAccessor::new(key)).is_found())
+            }).cloned().collect();
+        });
+        (instances, outer_keys)
+    }
+
+    /// Returns the indices of the items to repeat: the items are
+    /// filtered, then sorted and finally the current page is kept.
+    fn instances<C>(&self, context: &C, scope: &ScopedContext, items: &[&Store]) -> Vec<usize>
+        where C: DataBindingsContext
+    {
+        let mut ids: Vec<usize> = match self.filter {
+            Some(ref filter) => (0..items.len()).filter(|&i| {
+                let item_scope = scope.named_child(items[i], i, items.len(), "item");
+                expression::evaluate(filter, context, &item_scope).is_truthy()
+            }).collect(),
+            None => (0..items.len()).collect(),
+        };
+        if let Some((ref key, descending)) = self.sort_by {
+            let keys: Vec<_> = items.iter()
+                .map(|item| item.get_attribute(PropertyAccessor::new(key)).value())
+                .collect();
+            // Stable, items without the key are last.
+            ids.sort_by(|&a, &b| {
+                let order = match (&keys[a], &keys[b]) {
+                    (&Some(ref a), &Some(ref b)) =>
+                        expression::compare(a, b).unwrap_or(Ordering::Equal),
+                    (&Some(_), &None) => return Ordering::Less,
+                    (&None, &Some(_)) => return Ordering::Greater,
+                    (&None, &None) => Ordering::Equal,
+                };
+                if descending { order.reverse() } else { order }
+            });
+        }
+        if let Some(size) = self.page_size {
+            let page = match self.page {
+                Some(Page::Fixed(page)) => page,
+                Some(Page::Key(ref key)) => match scope.get_value(key) {
+                    Some(StoreValueStatic::Integer(page)) if page > 0 => page as usize,
+                    _ => 0,
+                },
+                None => 0,
+            };
+            ids = ids.into_iter().skip(page * size).take(size).collect();
+        }
+        ids
+    }
+
+    /// Returns `None` if an attribute is invalid, or if the template
+    /// doesn't exist or is repeated inside itself. The markup parser
+    /// reports those repeats, see `Library::check_repeats`.
+    fn new(data: &RepeatData,
+           index: usize,
+           templates: &HashMap<String, Template>,
+           visited: &mut Vec<String>) -> Option<RepeatNode>
+    {
+        let template_name = &data.template_name;
+        let iter = match validation::attribute_key(&data.iter) {
+            Some(iter) => iter,
+            None => return None,
+        };
+        if visited.iter().any(|t| t == template_name) {
+            return None;
+        }
+        let template = match templates.get(template_name) {
+            Some(template) => template,
+            None => return None,
+        };
+        let filter = match data.filter {
+            Some(ref filter) => match validation::attribute_key(filter).map(|f| parse_expression(&f)) {
+                Some(Ok(expr)) => Some(expr),
+                _ => return None,
+            },
+            None => None,
+        };
+        let page = match data.page {
+            Some(ref page) => match (validation::attribute_key(page), page.trim().parse()) {
+                (Some(key), _) => Some(Page::Key(key)),
+                (None, Ok(page)) => Some(Page::Fixed(page)),
+                (None, Err(_)) => return None,
+            },
+            None => None,
+        };
+        // Like the filter, the sort key is a key of `item`.
+        let sort_by = match data.sort_by {
+            Some(ref key) => {
+                let key = key.trim();
+                let (key, descending) = match strip_prefix(key, "-") {
+                    Some(key) => (key, true),
+                    None => (key, false),
+                };
+                match strip_prefix(key, "item.") {
+                    Some(key) if !key.is_empty() => Some((key.to_string(), descending)),
+                    _ => return None,
+                }
+            }
+            None => None,
+        };
+        let mut keys = vec![iter.clone()];
+        if let Some(Page::Key(ref key)) = page {
+            keys.push(key.clone());
+        }
+        if let Some(ref filter) = filter {
+            // The keys of `item` are the item's, the others are looked up outside.
+            for key in filter.keys() {
+                match strip_prefix(key, "$parent.") {
+                    Some(outer) => keys.push(outer.to_string()),
+                    None if key.starts_with('$') || key.starts_with("item.") => (),
+                    None => keys.push(key.to_string()),
+                }
+            }
+        }
+        let mut repeat = RepeatNode {
+            index: index,
+            template: template.clone(),
+            keys: keys,
+            iter: iter,
+            sort_by: sort_by,
+            filter: filter,
+            page_size: data.page_size,
+            page: page,
+            key: data.key.as_ref().map(|key| key.trim().to_string()),
+            bindings: Vec::new(),
+            repeats: Vec::new(),
+            item_keys: Vec::new(),
+            outer_keys: Vec::new(),
+            current: Vec::new(),
+            evaluated: false,
+            reshaped: false,
+        };
+        visited.push(template_name.to_string());
+        repeat.collect(template, &mut 0, templates, visited);
+        visited.pop();
+        Some(repeat)
+    }
+
+    /// A key of the template is only resolved outside of the items if
+    /// an item doesn't have it, or if it starts with `$parent.`.
+    fn add_template_key(&mut self, key: &str) {
+        match strip_prefix(key, "$parent.") {
+            Some(outer) => self.keys.push(outer.to_string()),
+            None if key.starts_with('$') => (),
+            None => self.item_keys.push(key.to_string()),
+        }
+    }
+
+    /// Collect the bindings and the repeats of the template in the
+    /// order of the nodes. `index` is the index of `node` in the template.
+    fn collect(&mut self,
+               node: &Node,
+               index: &mut usize,
+               templates: &HashMap<String, Template>,
+               visited: &mut Vec<String>)
+    {
+        let node_index = *index;
+        *index += 1;
+        match node.node_type {
+            NodeType::Binding(ref expr) => {
+                for key in expr.keys() {
+                    self.add_template_key(key);
+                }
+                self.bindings.push(expr.clone());
+            }
+            NodeType::Repeat(ref data) => {
+                if let Some(repeat) = RepeatNode::new(data, node_index, templates, visited) {
+                    // The keys of a nested repeat are looked up in our items first.
+                    for key in repeat.keys.iter().chain(repeat.item_keys.iter()) {
+                        self.add_template_key(key);
+                    }
+                    self.repeats.push(repeat);
+                }
+                // The children of a repeat are replaced by its instances.
+                *index += node.tree_size() - 1;
+                return;
+            }
+            _ => (),
+        }
+        for kid in node.children.iter() {
+            self.collect(kid, index, templates, visited);
         }
     }
 }
@@ -88,15 +453,40 @@ impl DataBindingNode {
 // }
 //
 impl DataBindingBuffer {
-    pub fn update<C>(&mut self, context: &mut C, layout: &mut LayoutBuffer) -> bool
+    /// Refresh the bindings of the view named `view_name` and
+    /// returns true if at least one of them has changed.
+    ///
+    /// If the context tracks changes, only the bindings
+    /// subscribed to a dirty key are queried.
+    ///
+    /// If the instances of a repeat have been added, removed or
+    /// reordered, the view must be expanded again, see `is_reshaped`.
+    pub fn update<C>(&mut self, view_name: &String, context: &mut C, layout: &mut LayoutBuffer) -> bool
         where C: DataBindingsContext
     {
         let mut has_changed = false;
-//         for (&lookup, node) in self.bindings.enumerate_lookup_indices_mut().unwrap() {
-//             if node.update(context, layout, lookup) {
-//                 has_changed = true;
-//             }
-//         }
+        let context = &*context;
+        let dirty_keys = context.dirty_keys();
+        let view_context = context.get_view_context(view_name);
+        let scope = ScopedContext::new(&view_context);
+        for (&lookup, node) in self.bindings.enumerate_lookup_indices_mut().unwrap() {
+            if node.is_dirty(dirty_keys) && node.update(context, &scope, layout, lookup) {
+                has_changed = true;
+            }
+        }
+        let mut repeats_changed = false;
+        for node in self.repeats.iter_mut() {
+            if node.is_dirty(dirty_keys) && node.update(context, &scope) {
+                repeats_changed = true;
+            }
+        }
+        if repeats_changed && !self.is_reshaped() {
+            // Same instances, only their values have changed.
+            for (index, text) in self.expansion().1.texts {
+                layout.set_text(index, text);
+            }
+        }
+        has_changed |= repeats_changed;
 //         for (&lookup, node) in self.iterators.enumerate_lookup_indices_mut().unwrap() {
 //             if node.update(context, layout, lookup) {
 //                 has_changed = true;
@@ -109,16 +499,78 @@ impl DataBindingBuffer {
 //         }
         has_changed
     }
+
+    /// Forget the values of the bindings, so that they are all
+    /// queried at the next update. The keys modified while the
+    /// view wasn't updated aren't dirty anymore.
+    pub fn invalidate(&mut self) {
+        for (_, node) in self.bindings.enumerate_mut() {
+            node.current = None;
+        }
+        for node in self.repeats.iter_mut() {
+            node.evaluated = false;
+        }
+    }
+
+    /// Returns true if the instances of a repeat have been added,
+    /// removed or reordered since the view was last expanded.
+    pub fn is_reshaped(&self) -> bool {
+        self.repeats.iter().any(|repeat| repeat.reshaped)
+    }
+
+    /// Returns the view with the current instances of its repeats,
+    /// from which the other buffers of the view are built. Inside the
+    /// instances, the bindings are replaced by their values.
+    ///
+    /// The bindings outside of the repeats are queried again
+    /// at the next update.
+    pub fn expand(&mut self) -> View {
+        let (view, expansion) = self.expansion();
+        self.bindings = binding_nodes(&view);
+        self.origins = expansion.origins;
+        for repeat in self.repeats.iter_mut() {
+            repeat.reshaped = false;
+        }
+        view
+    }
+
+    /// Returns the origin of the node at `index` in the expanded view.
+    pub fn origin(&self, index: usize) -> Option<&Origin> {
+        self.origins.get(index)
+    }
+
+    /// Returns the index of the node coming from `origin`
+    /// in the expanded view, if it's still there.
+    pub fn index_of(&self, origin: &Origin) -> Option<usize> {
+        self.origins.iter().position(|o| o == origin)
+    }
+
+    fn expansion(&self) -> (View, Expansion) {
+        let scope = ExpansionScope {
+            instances: Vec::new(),
+            repeats: &self.repeats,
+            lists: self.repeats.iter().map(|repeat| &repeat.current[..]).collect(),
+        };
+        let mut expansion = Expansion {
+            origins: Vec::new(),
+            texts: Vec::new(),
+        };
+        // Outside of the instances, the bindings have no values.
+        let view = expansion.expand_node(&self.view, &mut 0, &scope, &mut iter::empty());
+        (view, expansion)
+    }
+
+    /// Returns the keys used by the view that
+    /// can't be resolved by the context.
+    pub fn validate<C>(&self, view_name: &str, context: &C) -> Vec<UnresolvedKey>
+        where C: DataBindingsContext
+    {
+        validation::check_keys(view_name, &self.keys, context)
+    }
 //
+    /// The buffers of the view must be built from `expand`.
     pub fn new(view: &View, templates: &HashMap<String, Template>) -> DataBindingBuffer {
-        let bindings = BufferFromTree::new_with_lookup_table(view, 0, |node| {
-            match node.node_type {
-                NodeType::Binding(ref binding) => {
-                    Some(DataBindingNode::new(binding.clone()))
-                }
-                _ => None
-            }
-        });
+        let bindings = binding_nodes(view);
 //         let iterators = BufferFromTree::new_with_lookup_table(view, 0, |node| {
 //             match node.node_type {
 //                 // NodeType::Repeat(RepeatData { ref template_name, ref iter }) => {
@@ -135,10 +587,388 @@ impl DataBindingBuffer {
 //                 _ => None
 //             }
 //         });
+        let mut repeats = Vec::new();
+        collect_repeats(view, &mut 0, templates, &mut repeats);
         DataBindingBuffer {
+            view: view.clone(),
             bindings: bindings,
+            repeats: repeats,
+            origins: Vec::new(),
+            keys: validation::collect_bound_keys(view),
 //             iterators: iterators,
 //             iterator_bindings: iterator_bindings,
         }
     }
 }
+
+// ======================================== //
+//                  HELPERS                 //
+// ======================================== //
+
+impl Expansion {
+
+    /// Returns a copy of `node` with the instances of its repeats.
+    /// `index` is the index of `node` in its view or template, and
+    /// `values` are the values of the bindings of the instance.
+    fn expand_node<'a>(&mut self,
+                       node: &Node,
+                       index: &mut usize,
+                       scope: &ExpansionScope<'a>,
+                       values: &mut Iterator<Item=&'a StoreValueStatic>) -> Node
+    {
+        let source_index = *index;
+        *index += 1;
+        let position = self.origins.len();
+        self.origins.push(Origin {
+            instances: scope.instances.clone(),
+            index: source_index,
+        });
+        let mut expanded = node.without_children();
+        match node.node_type {
+            NodeType::Binding(_) => {
+                if let Some(value) = values.next() {
+                    let text = value.to_string();
+                    self.texts.push((position, text.clone()));
+                    expanded.node_type = NodeType::Text(text);
+                }
+            }
+            NodeType::Repeat(_) => {
+                let repeats = scope.repeats;
+                if let Some(i) = repeats.iter().position(|r| r.index == source_index) {
+                    for instance in scope.lists[i] {
+                        let kid = self.expand_instance(&repeats[i], instance, &scope.instances);
+                        expanded.children.push(kid);
+                    }
+                }
+                // The children of a repeat are replaced by its instances.
+                *index += node.tree_size() - 1;
+                return expanded;
+            }
+            _ => (),
+        }
+        for kid in node.children.iter() {
+            let kid = self.expand_node(kid, index, scope, values);
+            expanded.children.push(kid);
+        }
+        expanded
+    }
+
+    /// Returns the template of `repeat` for `instance`, as a group.
+    fn expand_instance<'a>(&mut self,
+                           repeat: &'a RepeatNode,
+                           instance: &'a Instance,
+                           outer: &[(usize, InstanceId)]) -> Node
+    {
+        let mut instances = outer.to_vec();
+        instances.push((repeat.index, instance.id.clone()));
+        let scope = ExpansionScope {
+            instances: instances,
+            repeats: &repeat.repeats,
+            lists: instance.repeats.iter().map(|list| &list[..]).collect(),
+        };
+        let mut group = self.expand_node(&repeat.template, &mut 0, &scope, &mut instance.values.iter());
+        group.node_type = NodeType::Group;
+        group
+    }
+}
+
+fn binding_nodes(view: &View) -> BufferFromTree<DataBindingNode> {
+    BufferFromTree::new_with_lookup_table(view, 0, |node| {
+        match node.node_type {
+            NodeType::Binding(ref binding) => {
+                Some(DataBindingNode::new(binding.clone()))
+            }
+            _ => None
+        }
+    })
+}
+
+/// See `RepeatNode::collect`.
+fn collect_repeats(node: &Node,
+                   index: &mut usize,
+                   templates: &HashMap<String, Template>,
+                   repeats: &mut Vec<RepeatNode>)
+{
+    let node_index = *index;
+    *index += 1;
+    if let NodeType::Repeat(ref data) = node.node_type {
+        if let Some(repeat) = RepeatNode::new(data, node_index, templates, &mut Vec::new()) {
+            repeats.push(repeat);
+        }
+        *index += node.tree_size() - 1;
+        return;
+    }
+    for kid in node.children.iter() {
+        collect_repeats(kid, index, templates, repeats);
+    }
+}
+
+/// Returns true if both lists have the same instances in the same order,
+/// whatever their values.
+fn same_instances(a: &[Instance], b: &[Instance]) -> bool {
+    a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| {
+        a.id == b.id && a.repeats.iter().zip(b.repeats.iter()).all(|(a, b)| same_instances(a, b))
+    })
+}
+
+/// Returns the rest of `key` if it starts with `prefix`.
+fn strip_prefix<'a>(key: &'a str, prefix: &str) -> Option<&'a str> {
+    if key.starts_with(prefix) {
+        Some(&key[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+// ======================================== //
+//                   TESTS                  //
+// ======================================== //
+
+#[cfg(test)]
+mod test {
+
+    use std::collections::HashMap;
+    use std::io::BufReader;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use super::{DataBindingBuffer, RepeatNode, InstanceId};
+    use oil_databindings::context::{ScopedContext, DirtyKeys};
+    use oil_databindings::store::{DynamicValue, StoreValueStatic};
+    use oil_shared::expression::Expr;
+    use oil_shared::markup::{self, Node, NodeType, RepeatData};
+    use oil_parsers::EmptyErrorReporter;
+    use data_bindings::DefaultContextManager;
+    use layout::LayoutBuffer;
+    use DataBindingsContext;
+
+    fn context(page: i64) -> DefaultContextManager {
+        let mut context = DefaultContextManager::default();
+        let shop = DynamicValue::from_json(&format!(r#"{{
+            "tab": "food",
+            "page": {},
+            "items": [
+                {{ "name": "Apple", "price": 3, "category": "food" }},
+                {{ "name": "Sword", "price": 50, "category": "weapon" }},
+                {{ "name": "Bread", "price": 2, "category": "food" }},
+                {{ "name": "Cheese", "price": 8, "category": "food" }}
+            ]
+        }}"#, page)).unwrap();
+        context.register_global_store("shop".to_string(), shop);
+        context
+    }
+
+    fn repeat(sort_by: &str) -> RepeatNode {
+        let mut template = markup::new_template(None);
+        template.children.push(Node::new(None, NodeType::Binding(Expr::Key("name".to_string()))));
+        template.children.push(Node::new(None, NodeType::Binding(Expr::Key("currency".to_string()))));
+        let mut templates = HashMap::new();
+        templates.insert("item".to_string(), template);
+        let data = RepeatData {
+            template_name: "item".to_string(),
+            iter: "{{shop.items}}".to_string(),
+            sort_by: Some(sort_by.to_string()),
+            filter: Some("{{item.category == shop.tab}}".to_string()),
+            page_size: Some(2),
+            page: Some("{{shop.page}}".to_string()),
+            key: None,
+        };
+        RepeatNode::new(&data, 0, &templates, &mut Vec::new()).unwrap()
+    }
+
+    fn names(repeat: &mut RepeatNode, context: &DefaultContextManager) -> Vec<(InstanceId, String)> {
+        let view = context.get_view_context("main");
+        repeat.update(context, &ScopedContext::new(&view));
+        repeat.current.iter()
+            .map(|instance| (instance.id.clone(), instance.values[0].to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn items_should_be_filtered_sorted_and_paged() {
+        let mut repeat = repeat("-item.price");
+        assert_eq!(names(&mut repeat, &context(0)),
+                   vec![(InstanceId::Index(3), "Cheese".to_string()),
+                        (InstanceId::Index(0), "Apple".to_string())]);
+        // Without a key, instances keep the index of the item in the list
+        assert_eq!(names(&mut repeat, &context(1)),
+                   vec![(InstanceId::Index(2), "Bread".to_string())]);
+        assert_eq!(names(&mut repeat, &context(2)), vec![]);
+    }
+
+    #[test]
+    fn only_keys_missing_from_the_items_should_be_looked_up_outside() {
+        let mut context = context(0);
+        context.register_global_value("name".to_string(), "Shop".to_string());
+        context.register_global_value("currency".to_string(), "gold".to_string());
+        let mut repeat = repeat("item.price");
+        names(&mut repeat, &context);
+        let dirty = |key: &str| {
+            let mut dirty = DirtyKeys::new();
+            dirty.mark(key);
+            dirty
+        };
+        // Every item has a name
+        assert!(!repeat.is_dirty(Some(&dirty("name"))));
+        assert!(!repeat.is_dirty(Some(&dirty("item.category"))));
+        assert!(repeat.is_dirty(Some(&dirty("currency"))));
+        assert!(repeat.is_dirty(Some(&dirty("shop.tab"))));
+    }
+
+    #[test]
+    fn items_of_a_shared_list_should_be_repeated() {
+        let mut context = DefaultContextManager::default();
+        let shop = DynamicValue::from_json(r#"{
+            "tab": "food",
+            "items": [{ "name": "Apple", "price": 3, "category": "food" }]
+        }"#).unwrap();
+        context.register_global_store("shop".to_string(), Rc::new(RefCell::new(shop)));
+        let mut repeat = repeat("item.price");
+        assert_eq!(names(&mut repeat, &context), vec![(InstanceId::Index(0), "Apple".to_string())]);
+    }
+
+    #[test]
+    fn items_without_the_sort_key_should_be_last() {
+        let mut repeat = repeat("item.weight");
+        assert_eq!(names(&mut repeat, &context(0)),
+                   vec![(InstanceId::Index(0), "Apple".to_string()),
+                        (InstanceId::Index(2), "Bread".to_string())]);
+    }
+
+    #[test]
+    fn instances_should_keep_their_key_when_the_list_is_reordered() {
+        let mut repeat = repeat("item.price");
+        repeat.key = Some("name".to_string());
+        let bread = || InstanceId::Key(StoreValueStatic::String("Bread".to_string()));
+        let apple = || InstanceId::Key(StoreValueStatic::String("Apple".to_string()));
+        assert_eq!(names(&mut repeat, &context(0)),
+                   vec![(bread(), "Bread".to_string()), (apple(), "Apple".to_string())]);
+
+        let mut context = DefaultContextManager::default();
+        let shop = DynamicValue::from_json(r#"{
+            "tab": "food",
+            "items": [
+                { "name": "Bread", "price": 2, "category": "food" },
+                { "name": "Apple", "price": 3, "category": "food" }
+            ]
+        }"#).unwrap();
+        context.register_global_store("shop".to_string(), shop);
+        assert_eq!(names(&mut repeat, &context),
+                   vec![(bread(), "Bread".to_string()), (apple(), "Apple".to_string())]);
+    }
+
+    fn shop(items: &str) -> DefaultContextManager {
+        let mut context = DefaultContextManager::default();
+        let shop = DynamicValue::from_json(&format!(r#"{{ "items": [{}] }}"#, items)).unwrap();
+        context.register_global_store("shop".to_string(), shop);
+        context
+    }
+
+    #[test]
+    fn repeats_should_be_expanded_with_their_instances() {
+        let lib = ::markup::parse(EmptyErrorReporter, BufReader::new(
+            "<template name=\"item\"><button>{{name}}</button>{{price}}</template>\
+             <view>{{shop.title}}<repeat iter=\"{{shop.items}}\" template-name=\"item\" key=\"name\"/></view>"
+            .as_bytes()));
+        let (_, view) = lib.views.into_iter().next().unwrap();
+        let mut buffer = DataBindingBuffer::new(&view, &lib.templates);
+        let mut layout = LayoutBuffer::new(&buffer.expand());
+        let main = "main".to_string();
+        let texts = |view: &Node| {
+            let mut texts = Vec::new();
+            collect_texts(view, &mut texts);
+            texts
+        };
+
+        let mut context = shop(r#"{ "name": "Apple", "price": 3 }, { "name": "Bread", "price": 2 }"#);
+        assert!(buffer.update(&main, &mut context, &mut layout));
+        assert!(buffer.is_reshaped());
+        let expanded = buffer.expand();
+        assert!(!buffer.is_reshaped());
+        // view, binding, repeat, then a group per instance
+        assert_eq!(expanded.tree_size(), 11);
+        assert_eq!(texts(&expanded), vec!["Apple", "3", "Bread", "2"]);
+        let apple = buffer.origin(4).cloned().unwrap();
+        layout = LayoutBuffer::new(&expanded);
+
+        // Only a value has changed: the view is kept.
+        let mut context = shop(r#"{ "name": "Apple", "price": 4 }, { "name": "Bread", "price": 2 }"#);
+        assert!(buffer.update(&main, &mut context, &mut layout));
+        assert!(!buffer.is_reshaped());
+
+        // Reordered: the button of the apple is found again.
+        let mut context = shop(r#"{ "name": "Bread", "price": 2 }, { "name": "Apple", "price": 4 }"#);
+        assert!(buffer.update(&main, &mut context, &mut layout));
+        assert!(buffer.is_reshaped());
+        let expanded = buffer.expand();
+        assert_eq!(texts(&expanded), vec!["Bread", "2", "Apple", "4"]);
+        assert_eq!(buffer.index_of(&apple), Some(8));
+    }
+
+    #[test]
+    fn only_bindings_with_a_dirty_key_should_be_queried() {
+        let lib = ::markup::parse(EmptyErrorReporter, BufReader::new(
+            "<view>{{player.hp}}{{player.xp}}</view>".as_bytes()));
+        let (_, view) = lib.views.into_iter().next().unwrap();
+        let mut buffer = DataBindingBuffer::new(&view, &lib.templates);
+        let mut layout = LayoutBuffer::new(&view);
+        let mut context = DefaultContextManager::default();
+        context.track_changes();
+        context.register_global_value("player.hp".to_string(), 1);
+        context.register_global_value("player.xp".to_string(), 1);
+        let main = "main".to_string();
+        assert!(buffer.update(&main, &mut context, &mut layout));
+        context.clear_dirty_keys();
+
+        // Both values change but only `player.hp` is reported.
+        context.register_global_value("player.hp".to_string(), 2);
+        context.register_global_value("player.xp".to_string(), 2);
+        context.clear_dirty_keys();
+        context.notify("player.hp");
+        assert!(buffer.update(&main, &mut context, &mut layout));
+        let values: Vec<_> = buffer.bindings.enumerate()
+            .map(|(_, node)| node.current.clone())
+            .collect();
+        assert_eq!(values, vec![Some(StoreValueStatic::Integer(2)),
+                                Some(StoreValueStatic::Integer(1))]);
+
+        // Nothing is dirty: nothing is queried.
+        context.clear_dirty_keys();
+        assert!(!buffer.update(&main, &mut context, &mut layout));
+    }
+
+    #[test]
+    fn keys_changed_while_the_view_is_hidden_should_be_queried_once_shown() {
+        let lib = ::markup::parse(EmptyErrorReporter, BufReader::new(
+            "<view>{{player.hp}}</view>".as_bytes()));
+        let (_, view) = lib.views.into_iter().next().unwrap();
+        let mut buffer = DataBindingBuffer::new(&view, &lib.templates);
+        let mut layout = LayoutBuffer::new(&view);
+        let mut context = DefaultContextManager::default();
+        context.track_changes();
+        context.register_global_value("player.hp".to_string(), 1);
+        let main = "main".to_string();
+        assert!(buffer.update(&main, &mut context, &mut layout));
+        context.clear_dirty_keys();
+
+        // The view is hidden: the frame ends without updating it.
+        context.register_global_value("player.hp".to_string(), 2);
+        context.clear_dirty_keys();
+
+        // Shown again
+        buffer.invalidate();
+        assert!(buffer.update(&main, &mut context, &mut layout));
+        let values: Vec<_> = buffer.bindings.enumerate()
+            .map(|(_, node)| node.current.clone())
+            .collect();
+        assert_eq!(values, vec![Some(StoreValueStatic::Integer(2))]);
+    }
+
+    fn collect_texts(node: &Node, texts: &mut Vec<String>) {
+        if let NodeType::Text(ref text) = node.node_type {
+            texts.push(text.clone());
+        }
+        for kid in node.children.iter() {
+            collect_texts(kid, texts);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::cmp::Ordering;
+
+use oil_shared::expression::{Expr, Literal, UnaryOp, BinaryOp};
+use oil_databindings::context::ScopedContext;
+use oil_databindings::store::StoreValueStatic;
+use oil_databindings::filters::as_f64;
+use DataBindingsContext;
+
+/// Evaluate a binding expression against the view context, or the
+/// scope of an item if the binding is inside a repeated template.
+///
+/// The rules are the following:
+///
+///  * A missing property evaluates to `Null`. So does a property of a
+///    dropped store, which is reported by the validation instead.
+///  * Arithmetic on two integers gives an integer, otherwise a float.
+///    `Null` or a division by zero gives `Null`.
+///  * `+` with a string on either side is a concatenation.
+///  * `&&`, `||` and `!` use the truth value of `StoreValueStatic::is_truthy`.
+///  * An unknown filter gives `Null`.
+pub fn evaluate<C>(expr: &Expr, context: &C, scope: &ScopedContext) -> StoreValueStatic
+    where C: DataBindingsContext
+{
+    match *expr {
+        Expr::Key(ref key) => scope.lookup(key).value().unwrap_or(StoreValueStatic::Null),
+        Expr::Literal(ref l) => match *l {
+            Literal::String(ref s) => StoreValueStatic::String(s.clone()),
+            Literal::Integer(i) => StoreValueStatic::Integer(i),
+            Literal::Float(f) => StoreValueStatic::Float(f),
+            Literal::Boolean(b) => StoreValueStatic::Boolean(b),
+            Literal::Null => StoreValueStatic::Null,
+        },
+        Expr::Unary(op, ref e) => {
+            let value = evaluate(e, context, scope);
+            match op {
+                UnaryOp::Not => StoreValueStatic::Boolean(!value.is_truthy()),
+                UnaryOp::Neg => match value {
+                    StoreValueStatic::Integer(i) => StoreValueStatic::Integer(-i),
+                    StoreValueStatic::Float(f) => StoreValueStatic::Float(-f),
+                    _ => StoreValueStatic::Null,
+                },
+            }
+        }
+        Expr::Binary(op, ref l, ref r) => {
+            let lhs = evaluate(l, context, scope);
+            // Short-circuit logical operators
+            match op {
+                BinaryOp::And if !lhs.is_truthy() => return StoreValueStatic::Boolean(false),
+                BinaryOp::Or if lhs.is_truthy() => return StoreValueStatic::Boolean(true),
+                _ => (),
+            }
+            let rhs = evaluate(r, context, scope);
+            binary(op, lhs, rhs)
+        }
+        Expr::Filter(ref e, ref name, ref args) => {
+            let value = evaluate(e, context, scope);
+            let args: Vec<_> = args.iter().map(|a| evaluate(a, context, scope)).collect();
+            context.apply_filter(name, &value, &args).unwrap_or(StoreValueStatic::Null)
+        }
+    }
+}
+
+/// Order two values the way the comparison operators do. Values
+/// of different types, other than numbers, can't be compared.
+pub fn compare(lhs: &StoreValueStatic, rhs: &StoreValueStatic) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (&StoreValueStatic::Integer(a), &StoreValueStatic::Integer(b)) => Some(a.cmp(&b)),
+        (&StoreValueStatic::String(ref a), &StoreValueStatic::String(ref b)) => Some(a.cmp(b)),
+        (&StoreValueStatic::Boolean(a), &StoreValueStatic::Boolean(b)) => Some(a.cmp(&b)),
+        (&StoreValueStatic::Null, &StoreValueStatic::Null) => Some(Ordering::Equal),
+        _ => match (as_f64(lhs), as_f64(rhs)) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => None,
+        }
+    }
+}
+
+// ======================================== //
+//                  HELPERS                 //
+// ======================================== //
+
+fn binary(op: BinaryOp, lhs: StoreValueStatic, rhs: StoreValueStatic) -> StoreValueStatic {
+    match op {
+        BinaryOp::And | BinaryOp::Or => StoreValueStatic::Boolean(rhs.is_truthy()),
+        BinaryOp::Eq => StoreValueStatic::Boolean(compare(&lhs, &rhs) == Some(Ordering::Equal)),
+        BinaryOp::Ne => StoreValueStatic::Boolean(compare(&lhs, &rhs) != Some(Ordering::Equal)),
+        BinaryOp::Lt => StoreValueStatic::Boolean(compare(&lhs, &rhs) == Some(Ordering::Less)),
+        BinaryOp::Gt => StoreValueStatic::Boolean(compare(&lhs, &rhs) == Some(Ordering::Greater)),
+        BinaryOp::Le => StoreValueStatic::Boolean(match compare(&lhs, &rhs) {
+            Some(Ordering::Less) | Some(Ordering::Equal) => true,
+            _ => false,
+        }),
+        BinaryOp::Ge => StoreValueStatic::Boolean(match compare(&lhs, &rhs) {
+            Some(Ordering::Greater) | Some(Ordering::Equal) => true,
+            _ => false,
+        }),
+        BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => {
+            arithmetic(op, lhs, rhs)
+        }
+    }
+}
+
+fn arithmetic(op: BinaryOp, lhs: StoreValueStatic, rhs: StoreValueStatic) -> StoreValueStatic {
+    // String concatenation
+    if op == BinaryOp::Add {
+        match (&lhs, &rhs) {
+            (&StoreValueStatic::String(_), _) | (_, &StoreValueStatic::String(_)) => {
+                return StoreValueStatic::String(format!("{}{}", lhs, rhs));
+            }
+            _ => (),
+        }
+    }
+    match (lhs, rhs) {
+        (StoreValueStatic::Integer(a), StoreValueStatic::Integer(b)) => {
+            let res = match op {
+                BinaryOp::Add => a.checked_add(b),
+                BinaryOp::Sub => a.checked_sub(b),
+                BinaryOp::Mul => a.checked_mul(b),
+                BinaryOp::Div => a.checked_div(b),
+                BinaryOp::Rem => a.checked_rem(b),
+                _ => unreachable!(),
+            };
+            res.map(StoreValueStatic::Integer).unwrap_or(StoreValueStatic::Null)
+        }
+        (a, b) => match (as_f64(&a), as_f64(&b)) {
+            (Some(_), Some(y)) if y == 0f64 && (op == BinaryOp::Div || op == BinaryOp::Rem) => {
+                StoreValueStatic::Null
+            }
+            (Some(x), Some(y)) => StoreValueStatic::Float(match op {
+                BinaryOp::Add => x + y,
+                BinaryOp::Sub => x - y,
+                BinaryOp::Mul => x * y,
+                BinaryOp::Div => x / y,
+                BinaryOp::Rem => x % y,
+                _ => unreachable!(),
+            }),
+            _ => StoreValueStatic::Null,
+        }
+    }
+}
+
+// ======================================== //
+//                   TESTS                  //
+// ======================================== //
+
+#[cfg(test)]
+mod test {
+
+    use super::evaluate;
+    use oil_shared::expression::{Expr, Literal, BinaryOp};
+    use oil_databindings::store::{StoreValueStatic, DynamicValue};
+    use oil_databindings::context::ScopedContext;
+    use data_bindings::DefaultContextManager;
+    use DataBindingsContext;
+
+    fn key(k: &str) -> Box<Expr> {
+        Box::new(Expr::Key(k.to_string()))
+    }
+
+    fn lit(l: Literal) -> Box<Expr> {
+        Box::new(Expr::Literal(l))
+    }
+
+    fn context() -> DefaultContextManager {
+        let mut context = DefaultContextManager::default();
+        context.register_global_value("player.hp".to_string(), 30);
+        context.register_global_value("player.max_hp".to_string(), 120);
+        context.register_global_value("player.name".to_string(), "Bob".to_string());
+        context
+    }
+
+    #[test]
+    fn arithmetic_should_use_integers_then_floats() {
+        let c = context();
+        let view = c.get_view_context("main");
+        let scope = ScopedContext::new(&view);
+        let e = Expr::Binary(BinaryOp::Div,
+            Box::new(Expr::Binary(BinaryOp::Mul, key("player.hp"), lit(Literal::Integer(100)))),
+            key("player.max_hp"));
+        assert_eq!(evaluate(&e, &c, &scope), StoreValueStatic::Integer(25));
+        let e = Expr::Binary(BinaryOp::Div, key("player.hp"), lit(Literal::Float(2.0)));
+        assert_eq!(evaluate(&e, &c, &scope), StoreValueStatic::Float(15.0));
+        let e = Expr::Binary(BinaryOp::Div, key("player.hp"), lit(Literal::Integer(0)));
+        assert_eq!(evaluate(&e, &c, &scope), StoreValueStatic::Null);
+    }
+
+    #[test]
+    fn add_with_a_string_should_concatenate() {
+        let c = context();
+        let view = c.get_view_context("main");
+        let scope = ScopedContext::new(&view);
+        let e = Expr::Binary(BinaryOp::Add, key("player.name"), key("player.hp"));
+        assert_eq!(evaluate(&e, &c, &scope), StoreValueStatic::String("Bob30".to_string()));
+    }
+
+    #[test]
+    fn comparisons_and_filters() {
+        let mut c = context();
+        c.register_filter("double".to_string(), |v, _| match *v {
+            StoreValueStatic::Integer(i) => StoreValueStatic::Integer(i * 2),
+            _ => StoreValueStatic::Null,
+        });
+        let view = c.get_view_context("main");
+        let scope = ScopedContext::new(&view);
+        let e = Expr::Binary(BinaryOp::Lt, key("player.hp"), key("player.max_hp"));
+        assert_eq!(evaluate(&e, &c, &scope), StoreValueStatic::Boolean(true));
+        let e = Expr::Filter(key("player.hp"), "double".to_string(), Vec::new());
+        assert_eq!(evaluate(&e, &c, &scope), StoreValueStatic::Integer(60));
+        let e = Expr::Filter(key("player.hp"), "percent".to_string(), Vec::new());
+        assert_eq!(evaluate(&e, &c, &scope), StoreValueStatic::String("3000%".to_string()));
+    }
+
+    #[test]
+    fn keys_should_be_resolved_in_the_item_scope() {
+        let mut c = context();
+        let friends = DynamicValue::from_json(r#"[{ "name": "Alice" }, { "name": "Eve" }]"#).unwrap();
+        c.register_global_store("friends".to_string(), friends);
+        let view = c.get_view_context("main");
+        let scope = ScopedContext::new(&view);
+        let friends: Vec<_> = scope.lookup("friends").unwrap_iter().collect();
+        let item = scope.child(friends[1], 1, friends.len());
+        let e = Expr::Binary(BinaryOp::Add, key("name"), key("$index"));
+        assert_eq!(evaluate(&e, &c, &item), StoreValueStatic::String("Eve1".to_string()));
+        assert_eq!(evaluate(&Expr::Key("player.name".to_string()), &c, &item),
+                   StoreValueStatic::String("Bob".to_string()));
+    }
+}
@@ -8,14 +8,28 @@ pub use oil_databindings::{
     AttributeGetResult,
     AttributeSetResult,
     DefaultContextManager,
-    DataBindingsContext
+    DataBindingsContext,
+    BindingError,
+    BindingResult,
+    WrongTypePolicy
 };
 pub mod context {
     pub use oil_databindings::context::ContextManager;
+    pub use oil_databindings::context::DirtyKeys;
+    pub use oil_databindings::context::{Update, UpdateSender};
+    pub use oil_databindings::Filters;
 }
 
 pub use self::buffer::DataBindingBuffer;
+pub use self::validation::{
+    BoundKey,
+    UnresolvedKey,
+    ValidationReport,
+    validate_library
+};
 mod buffer;
+mod expression;
+mod validation;
 
 // trait IsRepeatable {
 //     fn iter(&self, closure: &mut IteratingClosure) -> bool;
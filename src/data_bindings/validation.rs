@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+use std::collections::btree_map;
+use std::fmt;
+
+use oil_databindings::store::StoreValue;
+use oil_shared::expression::Expr;
+use markup::{Node, NodeType, Library, View, parse_expression};
+use DataBindingsContext;
+use super::{AttributeGetResult, BindingError};
+
+/// A property path used by a view, and where it is used.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundKey {
+    pub key: String,
+    /// Line and column of the node in the markup, such as `12:5`.
+    /// Nodes that weren't parsed use their path in the markup
+    /// tree instead, such as `view/group[1]/progress-bar[0]`.
+    pub location: String,
+    iterable: bool,
+}
+
+/// A key that couldn't be resolved by the context.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnresolvedKey {
+    pub location: String,
+    pub error: BindingError,
+}
+
+/// Unresolved keys per view, produced by `Router::validate`
+/// or `validate_library`.
+#[derive(Default, Debug)]
+pub struct ValidationReport {
+    views: BTreeMap<String, Vec<UnresolvedKey>>,
+}
+
+impl ValidationReport {
+
+    pub fn new() -> ValidationReport {
+        ValidationReport::default()
+    }
+
+    /// Add the unresolved keys of a view.
+    pub fn add(&mut self, view_name: String, unresolved: Vec<UnresolvedKey>) {
+        if !unresolved.is_empty() {
+            self.views.entry(view_name).or_insert_with(Vec::new).extend(unresolved);
+        }
+    }
+
+    /// Returns true if every key has been resolved.
+    pub fn is_ok(&self) -> bool {
+        self.views.is_empty()
+    }
+
+    /// Returns the unresolved keys of the given view.
+    pub fn unresolved(&self, view_name: &str) -> &[UnresolvedKey] {
+        self.views.get(view_name).map(|v| &v[..]).unwrap_or(&[])
+    }
+
+    /// Iterate over the views having unresolved keys, sorted by name.
+    pub fn iter(&self) -> btree_map::Iter<String, Vec<UnresolvedKey>> {
+        self.views.iter()
+    }
+}
+
+/// One line per unresolved key, so that it can be printed by a CI job.
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (view, unresolved) in self.views.iter() {
+            for u in unresolved.iter() {
+                try!(writeln!(f, "{}: {}: {}", view, u.location, u.error));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Collect every key used by the view: bindings, `iter`, `page` and
+/// `filter` of the `repeat` tags and bound attributes (`value="{{...}}"`, ...).
+///
+/// Bindings inside a repeated template are relative to each item,
+/// so they can't be checked here.
+pub fn collect_bound_keys(view: &View) -> Vec<BoundKey> {
+    let mut keys = Vec::new();
+    collect_for_node(view, view.node_type.tag_name().to_string(), &mut keys);
+    keys
+}
+
+/// Resolve the keys against the context, as if they were used by
+/// the view `view_name`.
+pub fn check_keys<C>(view_name: &str, keys: &[BoundKey], context: &C) -> Vec<UnresolvedKey>
+    where C: DataBindingsContext
+{
+    let view_context = context.get_view_context(view_name);
+    keys.iter().filter_map(|k| {
+        let mut error = None;
+        // Lists of shared stores are only iterable within `with_lookup`.
+        view_context.with_lookup(&k.key, &mut |result| error = match (result, k.iterable) {
+            (AttributeGetResult::NoSuchProperty, _) =>
+                Some(BindingError::NoSuchProperty(k.key.clone())),
+            (AttributeGetResult::DanglingReference, _) =>
+                Some(BindingError::DanglingReference(k.key.clone())),
+            (AttributeGetResult::SharedIterableType, _) =>
+                Some(BindingError::SharedStore(k.key.clone())),
+            // An absent list
+            (AttributeGetResult::PrimitiveType(StoreValue::Null), true) => None,
+            (AttributeGetResult::IterableType(_), true) => None,
+            (AttributeGetResult::IterableType(_), false) |
+            (_, true) => Some(BindingError::WrongType(k.key.clone())),
+            (_, false) => None,
+        });
+        error.map(|error| UnresolvedKey {
+            location: k.location.clone(),
+            error: error,
+        })
+    }).collect()
+}
+
+/// Validate all the views of a library without creating them. This
+/// doesn't need a display, which makes it usable from a test against
+/// a fixture context.
+pub fn validate_library<E, C>(lib: &Library<E>, context: &C) -> ValidationReport
+    where C: DataBindingsContext
+{
+    let mut report = ValidationReport::new();
+    for (name, view) in lib.views.iter() {
+        report.add(name.clone(), check_keys(name, &collect_bound_keys(view), context));
+    }
+    report
+}
+
+// ======================================== //
+//                  HELPERS                 //
+// ======================================== //
+
+fn collect_for_node(node: &Node, path: String, keys: &mut Vec<BoundKey>) {
+    {
+        let location = match node.position() {
+            Some(position) => position.to_string(),
+            None => path.clone(),
+        };
+        let mut push = |expr: &Expr, iterable: bool| {
+            for k in expr.keys() {
+                keys.push(BoundKey {
+                    key: k.to_string(),
+                    location: location.clone(),
+                    iterable: iterable,
+                });
+            }
+        };
+        match node.node_type {
+            NodeType::Binding(ref expr) => push(expr, false),
+            NodeType::Repeat(ref data) => {
+                if let Some(expr) = attribute_expr(&data.iter) {
+                    // Only a plain key is iterated
+                    let iterable = if let Expr::Key(_) = expr { true } else { false };
+                    push(&expr, iterable);
+                }
+                if let Some(expr) = data.page.as_ref().and_then(|v| attribute_expr(v)) {
+                    push(&expr, false);
+                }
+                if let Some(expr) = data.filter.as_ref().and_then(|v| attribute_expr(v)) {
+                    // `item.xxx` is relative to each item
+                    for k in expr.keys().into_iter().filter(|k| !k.starts_with("item.")) {
+                        push(&Expr::Key(k.to_string()), false);
+                    }
+                }
+            }
+            NodeType::Button(ref data) => {
+                if let Some(expr) = data.key.as_ref().and_then(|v| attribute_expr(v)) {
+                    push(&expr, false);
+                }
+            }
+            NodeType::LineInput(ref data) => {
+                for v in data.value.iter().chain(data.key.iter()) {
+                    if let Some(expr) = attribute_expr(v) {
+                        push(&expr, false);
+                    }
+                }
+            }
+            NodeType::ProgressBar(ref data) => {
+                if let Some(expr) = data.value.as_ref().and_then(|v| attribute_expr(v)) {
+                    push(&expr, false);
+                }
+            }
+            _ => (),
+        }
+    }
+    for (i, kid) in node.children.iter().enumerate() {
+        let kid_path = format!("{}/{}[{}]", path, kid.node_type.tag_name(), i);
+        collect_for_node(kid, kid_path, keys);
+    }
+}
+
+/// Parse an attribute value of the form `{{expression}}`. An invalid
+/// expression is kept as a key, so that it gets reported.
+fn attribute_expr(value: &str) -> Option<Expr> {
+    attribute_key(value).map(|text| match parse_expression(&text) {
+        Ok(expr) => expr,
+        Err(_) => Expr::Key(text),
+    })
+}
+
+/// Returns the key of an attribute value of the form `{{key}}`.
+pub fn attribute_key(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.starts_with("{{") && value.ends_with("}}") && value.len() >= 4 {
+        Some(value[2..value.len() - 2].trim().to_string())
+    } else {
+        None
+    }
+}
+
+// ======================================== //
+//                   TESTS                  //
+// ======================================== //
+
+#[cfg(test)]
+mod test {
+
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use super::{collect_bound_keys, check_keys, ValidationReport};
+    use oil_shared::expression::Expr;
+    use oil_shared::markup::{self, Node, NodeType, Position, ProgressBarData, RepeatData};
+    use data_bindings::{DefaultContextManager, BindingError};
+
+    fn repeat(iter: &str) -> Node {
+        Node::new(None, NodeType::Repeat(RepeatData {
+            template_name: "friend".to_string(),
+            iter: iter.to_string(),
+            sort_by: None,
+            filter: None,
+            page_size: None,
+            page: None,
+            key: None,
+        }))
+    }
+
+    fn view() -> markup::View {
+        let mut view = markup::new_view(None);
+        let mut group = Node::new(None, NodeType::Group);
+        let mut binding = Node::new(None, NodeType::Binding(Expr::Key("player.nmae".to_string())));
+        binding.set_position(Some(Position { line: 3, column: 9 }));
+        let mut bar = Node::new(None, NodeType::ProgressBar(ProgressBarData {
+            value: Some("{{ player.xp / player.max_xp | percent }}".to_string()),
+        }));
+        bar.set_position(Some(Position { line: 4, column: 5 }));
+        group.children.push(binding);
+        group.children.push(bar);
+        view.children.push(Node::new(None, NodeType::Text("Hello".to_string())));
+        view.children.push(group);
+        view.children.push(repeat("{{player.xp}}"));
+        view
+    }
+
+    #[test]
+    fn keys_should_be_collected_with_their_location() {
+        let keys = collect_bound_keys(&view());
+        let keys: Vec<_> = keys.iter().map(|k| (&k.key[..], &k.location[..])).collect();
+        assert_eq!(keys, vec![
+            ("player.nmae", "3:9"),
+            ("player.xp", "4:5"),
+            ("player.max_xp", "4:5"),
+            ("player.xp", "view/repeat[2]"),
+        ]);
+    }
+
+    #[test]
+    fn unresolved_keys_should_be_reported() {
+        let mut context = DefaultContextManager::default();
+        context.register_global_value("player.name".to_string(), "Bob".to_string());
+        context.register_global_value("player.xp".to_string(), 12);
+        context.register_global_value("player.max_xp".to_string(), 100);
+        let mut report = ValidationReport::new();
+        report.add("main".to_string(), check_keys("main", &collect_bound_keys(&view()), &context));
+        assert!(!report.is_ok());
+        let errors: Vec<_> = report.unresolved("main").iter().map(|u| u.error.clone()).collect();
+        assert_eq!(errors, vec![
+            BindingError::NoSuchProperty("player.nmae".to_string()),
+            BindingError::WrongType("player.xp".to_string()),
+        ]);
+        assert_eq!(report.to_string().lines().next(),
+                   Some("main: 3:9: No such property `player.nmae`"));
+    }
+
+    #[test]
+    fn lists_of_shared_stores_should_be_iterable() {
+        let mut context = DefaultContextManager::default();
+        context.register_global_store("friends".to_string(), Rc::new(RefCell::new(vec![1i64, 2])));
+        let mut view = markup::new_view(None);
+        view.children.push(repeat("{{friends}}"));
+        assert!(check_keys("main", &collect_bound_keys(&view), &context).is_empty());
+    }
+}
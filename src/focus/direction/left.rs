@@ -2,6 +2,7 @@ use focus::FocusNode;
 use super::Cursor;
 use util::ref_eq;
 use super::find_parent_or_neighbour;
+use super::right::next_sibling;
 
 /// This function returns the next node on the left.
 ///
@@ -23,6 +24,17 @@ pub fn focus_left<'a>(from: &'a FocusNode, cursor: &Cursor) -> &'a FocusNode {
 fn find_left_neighbour<'a>(parent: &'a FocusNode, from: &'a FocusNode)
     -> Option<&'a FocusNode>
 {
+    // In a right-to-left layout, the next sibling is on the left.
+    if parent.is_rtl {
+        next_sibling(parent, from)
+    } else {
+        previous_sibling(parent, from)
+    }
+}
+
+pub fn previous_sibling<'a>(parent: &'a FocusNode, from: &'a FocusNode)
+    -> Option<&'a FocusNode>
+{
 
     let mut prev_child = None;
 
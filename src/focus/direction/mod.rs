@@ -180,6 +180,20 @@ fn find_neighbor<'a>(
                 })
                 .max_by(|&(w, _)| F32Ord(w));
 
+            // No overlap (e.g. the last row of a grid is shorter):
+            // pick the node with the closest column instead.
+            let res = match res {
+                Some((w, _)) if w <= 0f32 => {
+                    let line = (current_node.line_number as isize + offset) as usize;
+                    let column = current_node.column_number as isize;
+                    parent.children()
+                        .filter(|n| n.line_number == line)
+                        .map(|n| (-(n.column_number as isize - column).abs() as f32, n))
+                        .max_by(|&(w, _)| F32Ord(w))
+                }
+                res => res,
+            };
+
             if let Some((_, node)) = res {
                 if node.is_acceptor {
                     node
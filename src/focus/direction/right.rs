@@ -2,6 +2,7 @@ use focus::FocusNode;
 use super::Cursor;
 use util::ref_eq;
 use super::find_parent_or_neighbour;
+use super::left::previous_sibling;
 
 /// This function returns the next node on the right.
 ///
@@ -23,6 +24,17 @@ pub fn focus_right<'a>(from: &'a FocusNode, cursor: &Cursor) -> &'a FocusNode {
 
 fn find_right_neighbour<'a>(parent: &'a FocusNode, from: &'a FocusNode)
     -> Option<&'a FocusNode>
+{
+    // In a right-to-left layout, the previous sibling is on the right.
+    if parent.is_rtl {
+        previous_sibling(parent, from)
+    } else {
+        next_sibling(parent, from)
+    }
+}
+
+pub fn next_sibling<'a>(parent: &'a FocusNode, from: &'a FocusNode)
+    -> Option<&'a FocusNode>
 {
     let mut next_child = false;
 
@@ -22,8 +22,16 @@ pub struct FocusAcceptor {
     // The parent of this node.
     parent: *const FocusNode,
     is_acceptor: bool,
+    // True if the children are laid out from right to left
+    is_rtl: bool,
     line_number: usize,
+    // Position of this node in its line
+    column_number: usize,
+    // Position of this node in the painting order
+    layer: usize,
     bounds: Rect,
+    // Area outside of which this node isn't visible
+    clip: Option<Rect>,
 }
 
 impl FocusAcceptor {
@@ -32,8 +40,12 @@ impl FocusAcceptor {
         FocusAcceptor {
             parent: ptr::null_mut(),
             line_number: 0,
+            column_number: 0,
+            layer: 0,
             is_acceptor: node.is_acceptor,
+            is_rtl: false,
             bounds: Default::default(),
+            clip: None,
         }
     }
 
@@ -111,6 +123,17 @@ impl FocusBuffer {
         }
     }
 
+    /// Returns the acceptor at `global_index` in the view, if any.
+    pub fn acceptor(&self, global_index: usize) -> Option<FocusedElement> {
+        self.buffer.iter()
+            .find(|&node| node.is_acceptor
+                  && self.node_as_global_index(node) == global_index as isize)
+            .map(|node| FocusedElement {
+                focus_node: self.node_as_index(node),
+                cursor: Cursor::new(node),
+            })
+    }
+
     pub fn global_index(&self, el: &FocusedElement) -> Option<usize> {
         if el.focus_node >= 0 {
             Some(self.node_as_global_index(self.get(el.focus_node as usize).unwrap()) as usize)
@@ -135,6 +158,27 @@ impl FocusBuffer {
         self.focus_any(previous, direction::focus_left, Axis::X)
     }
 
+    /// Returns the acceptor at the point (`x`, `y`). If several
+    /// acceptors are there, the one painted last is picked.
+    /// Acceptors clipped out at that point are ignored.
+    pub fn acceptor_at(&self, x: f32, y: f32) -> Option<FocusedElement> {
+        let mut top: Option<&FocusNode> = None;
+
+        for node in self.buffer.iter() {
+            if node.is_acceptor && node.bounds.contains(x, y)
+                && node.clip.map_or(true, |clip| clip.contains(x, y))
+                && top.map_or(true, |top| node.layer > top.layer)
+            {
+                top = Some(node);
+            }
+        }
+
+        top.map(|node| FocusedElement {
+            focus_node: self.node_as_index(node),
+            cursor: Cursor::new(node),
+        })
+    }
+
     fn focus_any<'a, F>(&'a self, previous: &FocusedElement, pick_next: F, axis: Axis)
         -> Option<FocusedElement>
         where F: Fn(&'a FocusNode, &Cursor) -> &'a FocusNode
@@ -158,13 +202,24 @@ impl FocusBuffer {
 
     pub fn update_nodes(&mut self, layout_data: &LayoutBuffer) {
 
+        // The painting order can change without the nodes being dirty
+        let mut layers = vec![0; layout_data.len()];
+        for (layer, &i) in layout_data.paint_order().iter().enumerate() {
+            layers[i] = layer;
+        }
+
         for (&i, focus) in self.buffer.enumerate_lookup_indices_mut().unwrap() {
+            focus.layer = layers[i];
+
             // This part is always safe because the initialization step
             // ensure that:
             //       self.layout_data.len() >= self.render_data
             //
             // See RenderBuffer#update_nodes
             let boxi = unsafe { layout_data.get_unchecked(i) };
+            if !boxi.is_dirty() {
+                continue;
+            }
             let ref rec = boxi.dim().content;
             focus.bounds = Rect {
                 x: rec.x,
@@ -173,7 +228,9 @@ impl FocusBuffer {
                     + boxi.dim().margin.right + boxi.dim().border.right,
                 height: rec.height + boxi.dim().margin.top + boxi.dim().border.top
                     + boxi.dim().margin.bottom + boxi.dim().border.bottom,
-            }
+            };
+            focus.clip = boxi.clip();
+            focus.is_rtl = boxi.is_rtl();
         }
 
         // Resolve line numbers
@@ -203,6 +260,7 @@ unsafe fn resolve_parent(focus_node: &mut FocusNode, parent: *const FocusNode) {
 fn resolve_line_numbers(focus_node: &mut FocusNode) {
 
     let mut current_line_number = 0;
+    let mut current_column_number = 0;
     let mut current_y = focus_node.children()
         .next()
         .map(|c| c.bounds.y)
@@ -216,9 +274,12 @@ fn resolve_line_numbers(focus_node: &mut FocusNode) {
         if child.bounds.y > current_y {
             current_y = child.bounds.y;
             current_line_number += 1;
+            current_column_number = 0;
         }
 
         child.line_number = current_line_number;
+        child.column_number = current_column_number;
+        current_column_number += 1;
 
         resolve_line_numbers(child);
     }
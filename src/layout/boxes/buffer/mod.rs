@@ -1,15 +1,24 @@
 use util::flat_tree::FlatTree;
 use util::flat_tree::TreeNode;
 use std::ops::Deref;
+use std::rc::Rc;
 use super::LayoutBox;
-use markup::Node;
+use layout::Rect;
+use layout::dim::{self, Align, Direction, Grid, Overflow};
+use layout::text::{TextMeasurer, TextStyle, NoTextMeasurer};
+use markup::{Node, NodeType};
 use state::StateBuffer;
 
 
 mod repeat_node;
 mod simple_node;
 
-pub struct LayoutBuffer(FlatTree<LayoutBox>);
+pub struct LayoutBuffer {
+    tree: FlatTree<LayoutBox>,
+    measurer: Rc<TextMeasurer>,
+    // Indices of the nodes, back to front
+    paint_order: Vec<usize>,
+}
 pub type LayoutNode = TreeNode<LayoutBox>;
 
 
@@ -17,7 +26,7 @@ impl Deref for LayoutBuffer {
     type Target = [LayoutNode];
 
     fn deref<'a>(&'a self) -> &'a [LayoutNode] {
-        self.0.deref()
+        self.tree.deref()
     }
 }
 
@@ -27,37 +36,384 @@ impl LayoutBuffer {
 
         let size = style_tree.tree_size();
 
-        LayoutBuffer(FlatTree::new(style_tree, size, converter))
+        LayoutBuffer {
+            tree: FlatTree::new(style_tree, size, converter),
+            measurer: Rc::new(NoTextMeasurer),
+            paint_order: (0..size).collect(),
+        }
+    }
+
+    /// Measure the text with `measurer` from now on.
+    /// The layout must then be computed again.
+    pub fn set_text_measurer(&mut self, measurer: Rc<TextMeasurer>) {
+        self.measurer = measurer;
+    }
+
+    /// The measurer given to `set_text_measurer`.
+    pub fn text_measurer(&self) -> Rc<TextMeasurer> {
+        self.measurer.clone()
+    }
+
+    /// Change the text of the binding node at `index`.
+    /// The layout must then be computed again.
+    pub fn set_text(&mut self, index: usize, text: String) {
+        if let Some(ref mut current) = self.tree[index].text {
+            *current = text;
+        }
     }
 
     pub fn update_from_state(&mut self, state_buffer: &StateBuffer) {
 
-        for (lb, state) in self.0.iter_mut().zip(state_buffer.iter()) {
+        for (lb, state) in self.tree.iter_mut().zip(state_buffer.iter()) {
             lb.update_from_state(state);
         }
     }
 
+    /// Update the nodes at `indices` from their state, then layout
+    /// again the subtrees of the nodes whose layout has changed.
+    /// Their parents are laid out again only if the size of the
+    /// subtree has changed.
+    ///
+    /// The nodes updated are marked as dirty.
+    pub fn update_nodes_from_state(
+        &mut self,
+        indices: &[usize],
+        state_buffer: &StateBuffer,
+        max_width: f32,
+        max_height: f32)
+    {
+        let mut changed = Vec::with_capacity(indices.len());
+
+        for &i in indices {
+            let state = state_buffer.get(i).unwrap();
+            let ref mut node = self.tree[i];
+
+            node.mark_dirty();
+            if node.update_from_state(state) {
+                changed.push(i);
+            }
+        }
+
+        if !changed.is_empty() {
+            for i in changed {
+                self.relayout(i, max_width, max_height);
+            }
+            // A z-index might have changed
+            self.compute_paint_order();
+        }
+    }
+
+    /// Layout again the subtree at `index`, then its ancestors
+    /// until one of them keeps its size.
+    fn relayout(&mut self, index: usize, max_width: f32, max_height: f32) {
+
+        for i in self.tree.path_to(index).into_iter().rev() {
+            let ref mut node = self.tree[i];
+
+            if !compute_layout_subtree(node, &*self.measurer) {
+                node.mark_subtree_dirty();
+                return;
+            }
+        }
+
+        // The size of a root has changed
+        self.compute_layout(max_width, max_height);
+    }
+
+    /// Must be called once the buffers depending on
+    /// the layout have been updated.
+    pub fn clear_dirty(&mut self) {
+        self.tree.clear_dirty();
+    }
+
     pub fn compute_layout(&mut self, max_width: f32, max_height: f32) {
 
+        // Start from the style
+        for node in self.tree.iter_mut() {
+            node.reset();
+        }
+
+        // Grids: their size and the size of their children
+        // only depends on their cells
+        for root in self.tree.tree_iter_mut() {
+            compute_layout_grid_sizes(root);
+        }
+
+        // Text: its width on a single line is needed by the first pass
+        for root in self.tree.tree_iter_mut() {
+            compute_layout_text_widths(root, &*self.measurer, &TextStyle::default());
+        }
+
         // First pass: compute default width
-        for root in self.0.tree_iter_mut() {
+        for root in self.tree.tree_iter_mut() {
             compute_layout_defaut_width(root, max_width);
         }
 
         // Second pass: compute auto margins and width auto
-        for root in self.0.tree_iter_mut() {
+        for root in self.tree.tree_iter_mut() {
             compute_layout_auto_width(root, max_width);
         }
 
+        // Text: now that its width is known, wrap it to get its height
+        for root in self.tree.tree_iter_mut() {
+            compute_layout_text_heights(root, &*self.measurer);
+        }
+
         // Third pass: layout children and compute their height
-        for root in self.0.tree_iter_mut() {
-            compute_layout_height_and_position(root, max_height);
+        for root in self.tree.tree_iter_mut() {
+            compute_layout_height_and_position(root, max_height, false);
+        }
+
+        // Fourth pass: place the children of the grids in their cells
+        for root in self.tree.tree_iter_mut() {
+            compute_layout_grid_positions(root);
+        }
+
+        // Fifth pass: scroll the containers and clip their children
+        for root in self.tree.tree_iter_mut() {
+            compute_layout_scroll_and_clip(root, None);
+        }
+
+        self.compute_paint_order();
+        self.tree.mark_all_dirty();
+    }
+
+    /// Returns the indices of the nodes in the order they must be
+    /// painted: a node is painted after the nodes it is above.
+    ///
+    /// A node with a `z-index` is a stacking context: it's painted
+    /// with its children after the other nodes of its parent context,
+    /// the greatest `z-index` last, or before them with a negative
+    /// `z-index`. An absolutely positioned node
+    /// without `z-index` is painted as if it had `z-index: 0`, but
+    /// the children with a `z-index` stay in the parent context.
+    /// Otherwise the nodes are painted in the markup order.
+    pub fn paint_order(&self) -> &[usize] {
+        &self.paint_order
+    }
+
+    fn compute_paint_order(&mut self) {
+        let mut order = Vec::with_capacity(self.tree.len());
+
+        for root in self.tree.tree_iter() {
+            paint_stacking_context(&self.tree, root, &mut order);
         }
+
+        self.paint_order = order;
     }
+
+    /// Scroll the innermost container with `overflow: scroll`
+    /// holding the node at `index` (the node itself included).
+    /// The offset is clamped to the size of the content, and the
+    /// children of the container are marked as dirty.
+    ///
+    /// Returns false if there's no such container.
+    pub fn scroll_by(&mut self, index: usize, dx: f32, dy: f32) -> bool {
+        match self.scroll_ancestors(index).first() {
+            Some(&i) => {
+                let ref mut container = self.tree[i];
+                container.scroll_x += dx;
+                container.scroll_y += dy;
+                // Scrolling never changes the size of the container.
+                compute_layout_subtree(container, &*self.measurer);
+                container.mark_subtree_dirty();
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Set the scroll offset of the node at `index`, for instance
+    /// when the buffer is built again. The offset is clamped
+    /// to the size of the content by the next layout.
+    pub fn set_scroll(&mut self, index: usize, (x, y): (f32, f32)) {
+        let ref mut node = self.tree[index];
+        node.scroll_x = x;
+        node.scroll_y = y;
+    }
+
+    /// Scroll the containers holding the node at `index` so that
+    /// it is visible, starting with the innermost one.
+    ///
+    /// Returns true if a scroll offset has changed. The nodes
+    /// moved are then marked as dirty.
+    pub fn scroll_into_view(&mut self, index: usize) -> bool {
+        let mut rect = self.tree[index].border_box();
+        let mut outermost = None;
+
+        for i in self.scroll_ancestors(index) {
+
+            if i == index {
+                continue;
+            }
+
+            let ref mut container = self.tree[i];
+            let view = container.padding_box();
+            let dx = scroll_delta(rect.x, rect.width, view.x, view.width);
+            let dy = scroll_delta(rect.y, rect.height, view.y, view.height);
+
+            if dx != 0f32 || dy != 0f32 {
+                container.scroll_x += dx;
+                container.scroll_y += dy;
+                rect.x -= dx;
+                rect.y -= dy;
+                outermost = Some(i);
+            }
+        }
+
+        match outermost {
+            Some(i) => {
+                let ref mut container = self.tree[i];
+                compute_layout_subtree(container, &*self.measurer);
+                container.mark_subtree_dirty();
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Returns the containers with `overflow: scroll` holding
+    /// the node at `index`, the innermost first.
+    fn scroll_ancestors(&self, index: usize) -> Vec<usize> {
+        self.tree.path_to(index)
+            .into_iter()
+            .rev()
+            .filter(|&i| self.tree[i].overflow == Overflow::Scroll)
+            .collect()
+    }
+}
+
+/// Push `this` and the nodes of its stacking context, see `paint_order`.
+fn paint_stacking_context(tree: &FlatTree<LayoutBox>, this: &LayoutNode, order: &mut Vec<usize>)
+{
+    order.push(tree.node_as_index(this) as usize);
+
+    let mut layers = Vec::new();
+    collect_layers(this, &mut layers);
+    // Stable: the markup order is kept for the same z-index
+    layers.sort_by(|a, b| a.0.unwrap_or(0).cmp(&b.0.unwrap_or(0)));
+
+    // The negative layers go below the flow
+    let flow_start = layers.iter()
+        .position(|&(z_index, _)| z_index.unwrap_or(0) >= 0)
+        .unwrap_or(layers.len());
+
+    for (i, &(z_index, layer)) in layers.iter().enumerate() {
+        if i == flow_start {
+            paint_flow(tree, this, order);
+        }
+        match z_index {
+            Some(_) => paint_stacking_context(tree, layer, order),
+            None => {
+                order.push(tree.node_as_index(layer) as usize);
+                paint_flow(tree, layer, order);
+            }
+        }
+    }
+
+    if flow_start == layers.len() {
+        paint_flow(tree, this, order);
+    }
+}
+
+/// Push the children of `this` which are not layered, in markup order.
+fn paint_flow(tree: &FlatTree<LayoutBox>, this: &LayoutNode, order: &mut Vec<usize>)
+{
+    for child in this.children() {
+        if !is_layered(child) {
+            order.push(tree.node_as_index(child) as usize);
+            paint_flow(tree, child, order);
+        }
+    }
+}
+
+/// Collect the layered nodes of the stacking context of `this`, with
+/// the z-index they are sorted by. An absolutely positioned node
+/// without `z-index` has no stacking context of its own.
+fn collect_layers<'a>(this: &'a LayoutNode, layers: &mut Vec<(Option<i32>, &'a LayoutNode)>)
+{
+    for child in this.children() {
+        if is_layered(child) {
+            layers.push((child.z_index, child));
+        }
+        if child.z_index.is_none() {
+            collect_layers(child, layers);
+        }
+    }
+}
+
+fn is_layered(this: &LayoutNode) -> bool
+{
+    this.z_index.is_some() || this.flags.has_absolute_positioning()
 }
 
-fn converter(_: &Node) -> Option<LayoutBox> {
-    Some(LayoutBox::default())
+fn converter(node: &Node) -> Option<LayoutBox> {
+    match node.node_type {
+        NodeType::Text(ref text) => Some(LayoutBox::new_text(text.clone())),
+        // The text is given once the binding is resolved
+        NodeType::Binding(_) => Some(LayoutBox::new_text(String::new())),
+        _ => Some(LayoutBox::default()),
+    }
+}
+
+/// Layout a node and its children again, with the space its parent
+/// gave it during the last layout. The position of the node is kept,
+/// so is the size its parent imposed on it.
+///
+/// Returns true if the space taken by the node has changed: its
+/// parent must then be laid out again.
+fn compute_layout_subtree(this: &mut LayoutNode, measurer: &TextMeasurer) -> bool
+{
+    let (x, y) = (this.dim.content.x, this.dim.content.y);
+    let last = this.last;
+    let size = (this.dim.content.width + horizontal_extra(this),
+                this.dim.content.height + vertical_extra(this));
+
+    reset_subtree(this);
+    this.dim.content.x = x;
+    this.dim.content.y = y;
+
+    // The parents keep their font
+    let inherited_font = this.inherited_font.clone();
+
+    // Applied by the parent at the same step as in `compute_layout`
+    if let Some((width, height)) = last.cell {
+        set_cell_size(this, width, height);
+    }
+    compute_layout_grid_sizes(this);
+    compute_layout_text_widths(this, measurer, &inherited_font);
+    let eaten = compute_layout_defaut_width(this, last.width);
+    match last.stretched_width {
+        Some(width) if !this.flags.has_width_fixed() => stretch_width(this, width),
+        _ => (),
+    }
+    compute_layout_auto_width(this, last.auto_width);
+    compute_layout_text_heights(this, measurer);
+    if this.flags.has_absolute_positioning() {
+        stretch_between_top_and_bottom(this, last.height);
+    }
+    compute_layout_height_and_position(this, last.height, last.parent_is_rtl);
+    match last.stretched_height {
+        Some(height) if !this.flags.has_height_fixed()
+            && this.constraints.aspect_ratio.is_none() => stretch_height(this, height),
+        _ => (),
+    }
+    compute_layout_grid_positions(this);
+    let clip = this.clip;
+    compute_layout_scroll_and_clip(this, clip);
+
+    size != (this.dim.content.width + horizontal_extra(this),
+             this.dim.content.height + vertical_extra(this))
+        || eaten != last.eaten
+}
+
+fn reset_subtree(this: &mut LayoutNode)
+{
+    this.reset();
+
+    for child in this.children_mut() {
+        reset_subtree(child);
+    }
 }
 
 /// This function compute the width for this node
@@ -67,6 +423,8 @@ fn converter(_: &Node) -> Option<LayoutBox> {
 /// This can appear when this node has a child with a fixed width.
 fn compute_layout_defaut_width(this: &mut LayoutNode, space_available_for_self: f32) -> f32
 {
+    this.last.width = space_available_for_self;
+
     // Compute the extra part to remove
     let mut o = this.dim.padding.left
         + this.dim.padding.right
@@ -79,22 +437,42 @@ fn compute_layout_defaut_width(this: &mut LayoutNode, space_available_for_self:
     let space_available = if this.flags.has_width_fixed() {
         this.dim.content.width
     } else {
-        space_available_for_self - o
+        (space_available_for_self - o).min(this.constraints.max_width)
     };
 
-    // Iterating variables
-    let mut max = 0f32;
+    // Iterating variables, a text is wrapped
+    // if it's wider than the space available.
+    let mut max = match this.text {
+        Some(_) => this.text_width.min(space_available),
+        None => 0f32,
+    };
     let mut sum = 0f32;
     let mut line_space_available = space_available;
 
+    // A row is never wrapped.
+    if this.flow.direction == Direction::Row {
+        max = compute_layout_row_width(this, space_available);
+
     // Scope to reduce iter lifetime.
-    {
+    } else {
+        let column = this.flow.direction == Direction::Column;
         let mut iter = this.children_mut();
         let mut option_next = iter.next();
         let mut next_child = false;
 
         loop {
 
+            // Absolutely positioned children are out of the line flow,
+            // their width is computed once the width of this node is known.
+            let is_absolute = match option_next {
+                Some(ref child) => child.flags.has_absolute_positioning(),
+                None => false,
+            };
+            if is_absolute {
+                option_next = iter.next();
+                continue;
+            }
+
             // This line is confusing...
             // child has the type &mut &mut LayoutBox (one additional indirection)
             // but in release both child, option_next and iter are optimized out.
@@ -117,7 +495,7 @@ fn compute_layout_defaut_width(this: &mut LayoutNode, space_available_for_self:
                     }
 
                     // If the child is auto then we simply restart with a new line
-                    if child.flags.is_new_line_forced() {
+                    if child.flags.is_new_line_forced() || column {
                         sum = 0f32;
                         line_space_available = space_available;
                     }
@@ -166,13 +544,25 @@ fn compute_layout_defaut_width(this: &mut LayoutNode, space_available_for_self:
 
     // Assign width for this.
     if !this.flags.has_width_fixed() {
-        this.dim.content.width = if this.flags.has_width_expand() {
+        this.dim.content.width = this.constraints.clamp_width(if this.flags.has_width_expand() {
             space_available
         } else {
             max.min(space_available)
-        }
+        });
     };
 
+    // Now the absolutely positioned children:
+    let padding_box_width = this.dim.content.width
+        + this.dim.padding.left
+        + this.dim.padding.right;
+
+    for child in this.children_mut() {
+        if child.flags.has_absolute_positioning() {
+            let space_available = child.offsets.available_width(padding_box_width);
+            compute_layout_defaut_width(child, space_available);
+        }
+    }
+
     // Compute the free space for margin in expand mode:
     let s = space_available_for_self - o - this.dim.content.width;
 
@@ -194,11 +584,54 @@ fn compute_layout_defaut_width(this: &mut LayoutNode, space_available_for_self:
         _ => ()
     }
 
-    if this.flags.has_width_fixed() {
+    let eaten = if this.flags.has_width_fixed() {
         this.dim.content.width + o
     } else {
-        max + o
+        this.constraints.clamp_width(max) + o
+    };
+
+    this.last.eaten = eaten;
+    eaten
+}
+
+/// Same as `compute_layout_defaut_width` for the children of a row:
+/// they are all on the same line and the children with an
+/// expand width share the space left. Returns the width of the line.
+fn compute_layout_row_width(this: &mut LayoutNode, space_available: f32) -> f32
+{
+    let gap = this.flow.gap;
+    let mut sum = 0f32;
+    let mut count = 0;
+    let mut expanding = 0;
+
+    for child in this.children_mut() {
+        if child.flags.has_absolute_positioning() {
+            continue;
+        }
+        count += 1;
+        if child.flags.is_x_expand() {
+            expanding += 1;
+        } else {
+            sum += compute_layout_defaut_width(child, (space_available - sum).max(0f32));
+        }
+    }
+
+    if count > 1 {
+        sum += gap * (count - 1) as f32;
+    }
+
+    if expanding > 0 {
+        let share = ((space_available - sum) / expanding as f32).max(0f32);
+
+        for child in this.children_mut() {
+            if !child.flags.has_absolute_positioning() && child.flags.is_x_expand() {
+                compute_layout_defaut_width(child, share);
+                sum += child.dim.content.width + horizontal_extra(child);
+            }
+        }
     }
+
+    sum
 }
 
 /// This function performs a tree traversal to compute the auto values
@@ -206,6 +639,8 @@ fn compute_layout_defaut_width(this: &mut LayoutNode, space_available_for_self:
 /// It should be called after compute_layout_default_width
 fn compute_layout_auto_width(this: &mut LayoutNode, space_available: f32)
 {
+    this.last.auto_width = space_available;
+
     // Resolve auto width for this.
     if this.flags.has_width_auto() {
         let o = this.dim.padding.left
@@ -215,12 +650,53 @@ fn compute_layout_auto_width(this: &mut LayoutNode, space_available: f32)
             + this.dim.border.left
             + this.dim.border.right;
 
-        this.dim.content.width = space_available - o;
+        this.dim.content.width = this.constraints.clamp_width(space_available - o);
+    }
+
+    // The cells of a grid without `cell-width` share its width
+    if let Some(grid) = this.grid {
+        if grid.cell_width.is_none() {
+            let cell_width = Grid::cell(grid.columns, this.dim.content.width, this.flow.gap);
+
+            for child in this.children_mut() {
+                if !child.flags.has_absolute_positioning() {
+                    let cell_height = child.last.cell.map_or(0f32, |cell| cell.1);
+                    set_cell_size(child, cell_width, cell_height);
+                    compute_layout_defaut_width(child, cell_width);
+                }
+            }
+        }
     }
 
+    let padding_box_width = this.dim.content.width
+        + this.dim.padding.left
+        + this.dim.padding.right;
+
+    let flow = this.flow;
+
     for child in this.children_mut() {
 
-        compute_layout_auto_width(child, this.dim.content.width);
+        if child.flags.has_absolute_positioning() {
+            let space_available = child.offsets.available_width(padding_box_width);
+            compute_layout_auto_width(child, space_available);
+            continue;
+        }
+
+        match flow.direction {
+            // The width of the children of a row is known
+            // after the first pass.
+            Direction::Row => {
+                let space_available = child.dim.content.width + horizontal_extra(child);
+                compute_layout_auto_width(child, space_available);
+            }
+            Direction::Column if flow.align == Align::Stretch
+                && !child.flags.has_width_fixed() =>
+            {
+                stretch_width(child, this.dim.content.width);
+                compute_layout_auto_width(child, this.dim.content.width);
+            }
+            _ => compute_layout_auto_width(child, this.dim.content.width),
+        }
     }
 
     // Compute the free space for margin in auto mode:
@@ -242,37 +718,135 @@ fn compute_layout_auto_width(this: &mut LayoutNode, space_available: f32)
     }
 }
 
-//
-// TODO: FIXME Text nodes must have their size precomputed somehow
+/// Measure the text of the `Text` and binding nodes on a single line,
+/// with the font of their closest parent that has one. The first
+/// pass then gives them this width, or less if the text is wrapped.
+fn compute_layout_text_widths(this: &mut LayoutNode, measurer: &TextMeasurer, inherited: &TextStyle)
+{
+    this.inherited_font = inherited.clone();
+    let style = this.text_style();
+
+    let width = match this.text {
+        Some(ref text) => style.measure_line(measurer, text).width,
+        None => 0f32,
+    };
+    this.text_width = width;
+
+    for child in this.children_mut() {
+        compute_layout_text_widths(child, measurer, &style);
+    }
+}
+
+/// Wrap the text of the `Text` and binding nodes to their width:
+/// the third pass keeps the height of the lines.
+///
+/// PRECONDITONS: compute_layout_auto_width has been called
+///
+fn compute_layout_text_heights(this: &mut LayoutNode, measurer: &TextMeasurer)
+{
+    if !this.flags.has_height_fixed() {
+        let height = match this.text {
+            Some(ref text) => {
+                let style = this.text_style();
+                Some(style.measure(measurer, text, this.dim.content.width).height)
+            }
+            None => None,
+        };
+
+        if let Some(height) = height {
+            this.dim.content.height = this.constraints.clamp_height(height);
+        }
+    }
+
+    for child in this.children_mut() {
+        compute_layout_text_heights(child, measurer);
+    }
+}
+
 //
 // PRECONDITONS: compute_width has been called
 //
-fn compute_layout_height_and_position(this: &mut LayoutNode, max_height: f32)
+fn compute_layout_height_and_position(this: &mut LayoutNode, max_height: f32, parent_is_rtl: bool)
 {
-
-    // At this point we don't know this.dim.height / this.dim.width
-    // positions
-    let mut x = this.dim.content.x
-        + this.dim.padding.left
-        + this.dim.margin.left
-        + this.dim.border.left;
-    let mut y = this.dim.content.y
-        + this.dim.padding.top
-        + this.dim.margin.top
-        + this.dim.border.top;
+    this.last.height = max_height;
+    this.last.parent_is_rtl = parent_is_rtl;
+
+    let rtl = this.flags.is_rtl(parent_is_rtl);
+    this.rtl = rtl;
+
+    // The width is known: so is the height with an aspect ratio.
+    let height_known = match this.constraints.aspect_ratio {
+        Some(ratio) if !this.flags.has_height_fixed() => {
+            this.dim.content.height = this.constraints.clamp_height(
+                this.dim.content.width / ratio);
+            true
+        }
+        _ => this.flags.has_height_fixed(),
+    };
 
     // Equivalent rule for child max height:
-    let child_max_height = if this.flags.has_height_fixed() {
+    let child_max_height = if height_known {
 
         this.dim.content.height
     } else {
 
-        max_height
+        (max_height
         - this.dim.padding.bottom - this.dim.padding.top
         - this.dim.border.bottom  - this.dim.border.top
-        - this.dim.margin.bottom  - this.dim.margin.top
+        - this.dim.margin.bottom  - this.dim.margin.top)
+        .min(this.constraints.max_height)
+    };
+
+    // `height: auto` and `height: expand` take all the height given.
+    if !height_known
+        && (this.flags.has_height_auto() || this.flags.has_height_expand())
+    {
+        this.dim.content.height = child_max_height;
+    }
+
+    let content_height = match this.flow.direction {
+        Direction::Inline => compute_layout_lines(this, child_max_height, rtl),
+        Direction::Row => compute_layout_row(this, child_max_height, rtl),
+        Direction::Column => compute_layout_column(this, child_max_height, rtl),
     };
 
+    // Finally: the height !
+    if !height_known {
+        this.dim.content.height = this.constraints.clamp_height(
+            this.dim.content.height.max(child_max_height.min(content_height)));
+    }
+
+    // Absolutely positioned children are placed relative to
+    // the padding box of this node.
+    let padding_box = this.padding_box();
+
+    for child in this.children_mut() {
+        if child.flags.has_absolute_positioning() {
+            compute_layout_absolute_position(child, &padding_box, rtl);
+        }
+    }
+}
+
+/// Layout the children in lines, from left to right (or right to left),
+/// and returns the height of the lines.
+fn compute_layout_lines(this: &mut LayoutNode, child_max_height: f32, rtl: bool) -> f32
+{
+    // The lines are filled the same way in both directions,
+    // only the x position of the children is mirrored.
+    let line_start = this.dim.content.x
+        + this.dim.padding.left
+        + this.dim.margin.left
+        + this.dim.border.left;
+    let line_end = line_start + this.dim.content.width;
+
+    // At this point we don't know this.dim.height / this.dim.width
+    // positions
+    let mut x = line_start;
+    let mut y = this.dim.content.y
+        + this.dim.padding.top
+        + this.dim.margin.top
+        + this.dim.border.top;
+
     // Current line width allow to track the layout progress
     // in the x direction while height allow to track the y direction
     let mut current_line_width  = 0f32;
@@ -336,10 +910,15 @@ fn compute_layout_height_and_position(this: &mut LayoutNode, max_height: f32)
 
         for child in this.children_mut() {
 
+            // Positioned once the height of this node is known.
+            if child.flags.has_absolute_positioning() {
+                continue;
+            }
+
             let need_new_line = child.flags.is_new_line_forced();
 
             // Line return ?
-            if child.dim.content.width + current_line_width > this.dim.content.width {
+            if outer_width(child) + current_line_width > this.dim.content.width {
                 let ref d = this.dim;
                 line_return!(
                     stack,
@@ -350,30 +929,25 @@ fn compute_layout_height_and_position(this: &mut LayoutNode, max_height: f32)
                     d);
             }
 
-            child.dim.content.x = x;
-            child.dim.content.y = y;
-
             // Update the x position:
-            let child_total_width = child.dim.content.width
-                + child.dim.margin.left
-                + child.dim.margin.right
-                + child.dim.border.left
-                + child.dim.border.right;
+            let child_total_width = outer_width(child);
+
+            child.dim.content.x = if rtl {
+                line_start + line_end - x - child_total_width
+            } else {
+                x
+            };
+            child.dim.content.y = y;
 
             x += child_total_width;
             current_line_width += child_total_width;
 
-            compute_layout_height_and_position(child, current_height_left);
+            compute_layout_height_and_position(child, current_height_left, rtl);
 
 
             // Note: at this point child.margin (top, right) are either fixed
             // or zero (if they were auto). They will be computed in a later pass.
-            current_line_height = current_line_height.max(child.dim.content.height
-                + child.dim.margin.top
-                + child.dim.margin.bottom
-                + child.dim.border.top
-                + child.dim.border.bottom
-            );
+            current_line_height = current_line_height.max(outer_height(child));
 
             if child.flags.has_margin_top_or_bot_auto() {
                 stack.push(child);
@@ -398,7 +972,437 @@ fn compute_layout_height_and_position(this: &mut LayoutNode, max_height: f32)
     }
 
 
-    // Finally: the height !
-    this.dim.content.height =
-        this.dim.content.height.max(child_max_height.min(accumulated_line_height));
+    accumulated_line_height
+}
+
+/// Layout the children of a row, see `Flow`,
+/// and returns the height of the row.
+fn compute_layout_row(this: &mut LayoutNode, child_max_height: f32, rtl: bool) -> f32
+{
+    let flow = this.flow;
+    let (line_start, y) = content_origin(this);
+    let line_end = line_start + this.dim.content.width;
+
+    let mut used = 0f32;
+    let mut count = 0;
+
+    for child in this.children_mut() {
+        if !child.flags.has_absolute_positioning() {
+            used += outer_width(child);
+            count += 1;
+        }
+    }
+
+    if count > 1 {
+        used += flow.gap * (count - 1) as f32;
+    }
+
+    // Along the row
+    let (offset, spacing) = flow.justify.distribute(this.dim.content.width - used, count);
+    let mut x = line_start + offset;
+    let mut row_height = 0f32;
+
+    for child in this.children_mut() {
+
+        if child.flags.has_absolute_positioning() {
+            continue;
+        }
+
+        let child_total_width = outer_width(child);
+
+        child.dim.content.x = if rtl {
+            line_start + line_end - x - child_total_width
+        } else {
+            x
+        };
+        child.dim.content.y = y;
+
+        x += child_total_width + flow.gap + spacing;
+
+        compute_layout_height_and_position(child, child_max_height, rtl);
+
+        row_height = row_height.max(outer_height(child));
+    }
+
+    // Across the row, now that its height is known
+    let height = this.dim.content.height.max(child_max_height.min(row_height));
+
+    for child in this.children_mut() {
+
+        if child.flags.has_absolute_positioning() {
+            continue;
+        }
+
+        if flow.align == Align::Stretch && !child.flags.has_height_fixed()
+            && child.constraints.aspect_ratio.is_none()
+        {
+            stretch_height(child, height);
+        } else {
+            let dy = flow.align.offset(height - outer_height(child));
+            translate(child, 0f32, dy);
+        }
+    }
+
+    row_height
+}
+
+/// Layout the children of a column, see `Flow`,
+/// and returns the height of the column.
+fn compute_layout_column(this: &mut LayoutNode, child_max_height: f32, rtl: bool) -> f32
+{
+    let flow = this.flow;
+    let (line_start, y) = content_origin(this);
+    let width = this.dim.content.width;
+
+    let mut used = 0f32;
+    let mut count = 0;
+    let mut expanding = 0;
+
+    // The children are laid out at the top of the column, then
+    // moved down once the height left to the expanding ones is known.
+    let place = |child: &mut LayoutNode, max_height: f32| {
+        let child_total_width = outer_width(child);
+        let x = line_start + flow.align.offset(width - child_total_width);
+
+        child.dim.content.x = if rtl {
+            2f32 * line_start + width - x - child_total_width
+        } else {
+            x
+        };
+        child.dim.content.y = y;
+
+        compute_layout_height_and_position(child, max_height, rtl);
+        outer_height(child)
+    };
+
+    for child in this.children_mut() {
+        if child.flags.has_absolute_positioning() {
+            continue;
+        }
+        count += 1;
+        if child.flags.has_height_expand() && !child.flags.has_height_fixed() {
+            expanding += 1;
+        } else {
+            used += place(child, child_max_height);
+        }
+    }
+
+    if count > 1 {
+        used += flow.gap * (count - 1) as f32;
+    }
+
+    if expanding > 0 {
+        let share = ((child_max_height - used) / expanding as f32).max(0f32);
+
+        for child in this.children_mut() {
+            if !child.flags.has_absolute_positioning()
+                && child.flags.has_height_expand()
+                && !child.flags.has_height_fixed()
+            {
+                used += place(child, share);
+            }
+        }
+    }
+
+    // Along the column, now that its height is known
+    let height = this.dim.content.height.max(child_max_height.min(used));
+    let (offset, spacing) = flow.justify.distribute(height - used, count);
+    let mut next_y = y + offset;
+
+    for child in this.children_mut() {
+
+        if child.flags.has_absolute_positioning() {
+            continue;
+        }
+
+        let dy = next_y - child.dim.content.y;
+        translate(child, 0f32, dy);
+        next_y += outer_height(child) + flow.gap + spacing;
+    }
+
+    used
+}
+
+/// Give to the children of a grid the size of a cell, and to the grid
+/// the size of its cells. A fixed width or height is kept, the size
+/// of the cells is then computed from it.
+///
+/// Without `cell-width`, the width of a grid that isn't fixed is only
+/// known after the first pass: `compute_layout_auto_width` gives the
+/// cells their width then.
+fn compute_layout_grid_sizes(this: &mut LayoutNode)
+{
+    if let Some(grid) = this.grid {
+        let gap = this.flow.gap;
+        let count = this.children()
+            .filter(|c| !c.flags.has_absolute_positioning())
+            .count();
+        let rows = grid.rows.unwrap_or((count + grid.columns - 1) / grid.columns);
+
+        let cell_width = match grid.cell_width {
+            Some(w) => w,
+            None if this.flags.has_width_fixed() =>
+                Grid::cell(grid.columns, this.dim.content.width, gap),
+            None => 0f32,
+        };
+        let cell_height = match grid.cell_height {
+            Some(h) => h,
+            None if this.flags.has_height_fixed() =>
+                Grid::cell(rows, this.dim.content.height, gap),
+            None => 0f32,
+        };
+
+        if !this.flags.has_width_fixed() && grid.cell_width.is_some() {
+            this.dim.content.width = Grid::extent(grid.columns, cell_width, gap);
+            this.flags = (this.flags & !(dim::WIDTH_AUTO | dim::WIDTH_EXPAND)) | dim::WIDTH_FIXED;
+        }
+
+        if !this.flags.has_height_fixed() {
+            this.dim.content.height = Grid::extent(rows, cell_height, gap);
+            this.flags = (this.flags & !(dim::HEIGHT_AUTO | dim::HEIGHT_EXPAND)) | dim::HEIGHT_FIXED;
+        }
+
+        for child in this.children_mut() {
+            if !child.flags.has_absolute_positioning() {
+                set_cell_size(child, cell_width, cell_height);
+            }
+        }
+    }
+
+    for child in this.children_mut() {
+        compute_layout_grid_sizes(child);
+    }
+}
+
+/// Give to a child of a grid the size of its cell, margins included.
+fn set_cell_size(this: &mut LayoutNode, cell_width: f32, cell_height: f32)
+{
+    this.last.cell = Some((cell_width, cell_height));
+    this.dim.content.width = cell_width - horizontal_extra(this);
+    this.dim.content.height = cell_height - vertical_extra(this);
+    this.flags = (this.flags & !(dim::WIDTH_AUTO | dim::WIDTH_EXPAND
+                                 | dim::HEIGHT_AUTO | dim::HEIGHT_EXPAND
+                                 | dim::MARGIN_X_AUTO | dim::MARGIN_Y_AUTO
+                                 | dim::MARGIN_X_EXPAND))
+        | dim::WIDTH_FIXED | dim::HEIGHT_FIXED;
+}
+
+/// Give to a child of a column with `align-items: stretch`
+/// the `width` of the column, margins included.
+fn stretch_width(this: &mut LayoutNode, width: f32)
+{
+    this.last.stretched_width = Some(width);
+    this.dim.content.width = this.constraints.clamp_width(width - horizontal_extra(this));
+}
+
+/// Same as `stretch_width` for the children of a row.
+fn stretch_height(this: &mut LayoutNode, height: f32)
+{
+    this.last.stretched_height = Some(height);
+    this.dim.content.height = this.constraints.clamp_height(height - vertical_extra(this));
+}
+
+/// Move the children of the grids in their cells, row by row.
+///
+/// PRECONDITONS: compute_layout_height_and_position has been called
+///
+fn compute_layout_grid_positions(this: &mut LayoutNode)
+{
+    if let Some(grid) = this.grid {
+        let gap = this.flow.gap;
+        let (x0, y0) = content_origin(this);
+        let width = this.dim.content.width;
+        let rtl = this.rtl;
+        let mut index = 0;
+
+        for child in this.children_mut() {
+
+            if child.flags.has_absolute_positioning() {
+                continue;
+            }
+
+            let cell_width = child.dim.content.width + horizontal_extra(child);
+            let cell_height = child.dim.content.height + vertical_extra(child);
+            let column = (index % grid.columns) as f32;
+            let row = (index / grid.columns) as f32;
+            let x = x0 + column * (cell_width + gap);
+            let x = if rtl { 2f32 * x0 + width - x - cell_width } else { x };
+            let y = y0 + row * (cell_height + gap);
+
+            let (dx, dy) = (x - child.dim.content.x, y - child.dim.content.y);
+            translate(child, dx, dy);
+            index += 1;
+        }
+    }
+
+    for child in this.children_mut() {
+        compute_layout_grid_positions(child);
+    }
+}
+
+/// Move the children of the scroll containers by their scroll offset,
+/// and give to every node the area it is clipped to.
+///
+/// PRECONDITONS: compute_layout_grid_positions has been called
+///
+fn compute_layout_scroll_and_clip(this: &mut LayoutNode, clip: Option<Rect>)
+{
+    this.clip = clip;
+
+    let clip = match this.overflow {
+        Overflow::Visible => clip,
+        overflow => {
+            let padding_box = this.padding_box();
+
+            if overflow == Overflow::Scroll {
+                // Size of the content, to bound the scroll offset
+                let (x0, y0) = content_origin(this);
+                let mut content_width = 0f32;
+                let mut content_height = 0f32;
+
+                for child in this.children() {
+                    content_width = content_width.max(child.dim.content.x - x0
+                        + child.dim.content.width + horizontal_extra(child));
+                    content_height = content_height.max(child.dim.content.y - y0
+                        + child.dim.content.height + vertical_extra(child));
+                }
+
+                this.scroll_x = this.scroll_x
+                    .min(content_width - this.dim.content.width)
+                    .max(0f32);
+                this.scroll_y = this.scroll_y
+                    .min(content_height - this.dim.content.height)
+                    .max(0f32);
+
+                let (dx, dy) = (this.scroll_x, this.scroll_y);
+                if dx != 0f32 || dy != 0f32 {
+                    for child in this.children_mut() {
+                        translate(child, -dx, -dy);
+                    }
+                }
+            } else {
+                this.scroll_x = 0f32;
+                this.scroll_y = 0f32;
+            }
+
+            Some(match clip {
+                Some(clip) => clip.intersection(&padding_box),
+                None => padding_box,
+            })
+        }
+    };
+
+    for child in this.children_mut() {
+        compute_layout_scroll_and_clip(child, clip);
+    }
+}
+
+/// Position an absolutely positioned node inside the padding box
+/// of its parent. Without offsets, the node is at the top left corner.
+///
+/// PRECONDITONS: compute_width has been called
+///
+fn compute_layout_absolute_position(this: &mut LayoutNode, padding_box: &Rect, parent_is_rtl: bool)
+{
+    let o_x = this.dim.padding.left + this.dim.padding.right
+        + this.dim.border.left + this.dim.border.right
+        + this.dim.margin.left + this.dim.margin.right;
+    let o_y = this.dim.padding.top + this.dim.padding.bottom
+        + this.dim.border.top + this.dim.border.bottom
+        + this.dim.margin.top + this.dim.margin.bottom;
+
+    let max_height = this.offsets.available_height(padding_box.height);
+    stretch_between_top_and_bottom(this, max_height);
+
+    this.dim.content.x = match (this.offsets.left, this.offsets.right) {
+        (Some(left), _) => padding_box.x + left,
+        (None, Some(right)) => padding_box.x + padding_box.width
+            - right - this.dim.content.width - o_x,
+        // Mirrored like the children of the parent
+        (None, None) if parent_is_rtl => padding_box.x + padding_box.width
+            - this.dim.content.width - o_x,
+        (None, None) => padding_box.x,
+    };
+
+    // The height is only known after the children have been laid out.
+    this.dim.content.y = padding_box.y + this.offsets.top.unwrap_or(0f32);
+    compute_layout_height_and_position(this, max_height, parent_is_rtl);
+
+    if let (None, Some(bottom)) = (this.offsets.top, this.offsets.bottom) {
+        let dy = padding_box.y + padding_box.height - bottom
+            - this.dim.content.height - o_y - this.dim.content.y;
+        translate(this, 0f32, dy);
+    }
+}
+
+/// Give to an absolutely positioned node with both `top` and `bottom`
+/// the height left between them, `max_height`, margins included.
+fn stretch_between_top_and_bottom(this: &mut LayoutNode, max_height: f32)
+{
+    if !this.flags.has_height_fixed()
+        && this.offsets.top.is_some()
+        && this.offsets.bottom.is_some()
+    {
+        this.dim.content.height = max_height - vertical_extra(this);
+    }
+}
+
+/// Move a node and its children.
+fn translate(this: &mut LayoutNode, dx: f32, dy: f32)
+{
+    this.dim.content.x += dx;
+    this.dim.content.y += dy;
+
+    for child in this.children_mut() {
+        translate(child, dx, dy);
+    }
+}
+
+/// Position of the first child of a node.
+fn content_origin(this: &LayoutNode) -> (f32, f32)
+{
+    (this.dim.content.x + this.dim.padding.left + this.dim.margin.left + this.dim.border.left,
+     this.dim.content.y + this.dim.padding.top + this.dim.margin.top + this.dim.border.top)
+}
+
+/// Width of the margin box of a node, taken in a line, a row or a column.
+fn outer_width(this: &LayoutNode) -> f32
+{
+    this.dim.content.width + horizontal_extra(this)
+}
+
+/// Height of the margin box of a node, taken in a line, a row or a column.
+fn outer_height(this: &LayoutNode) -> f32
+{
+    this.dim.content.height + vertical_extra(this)
+}
+
+/// Everything around the content, horizontally.
+fn horizontal_extra(this: &LayoutNode) -> f32
+{
+    this.dim.padding.left + this.dim.padding.right
+        + this.dim.margin.left + this.dim.margin.right
+        + this.dim.border.left + this.dim.border.right
+}
+
+/// Everything around the content, vertically.
+fn vertical_extra(this: &LayoutNode) -> f32
+{
+    this.dim.padding.top + this.dim.padding.bottom
+        + this.dim.margin.top + this.dim.margin.bottom
+        + this.dim.border.top + this.dim.border.bottom
+}
+
+/// Scroll needed for the segment [`start`, `start + size`] to be
+/// inside [`view_start`, `view_start + view_size`]. If it's too big,
+/// its start is made visible.
+fn scroll_delta(start: f32, size: f32, view_start: f32, view_size: f32) -> f32
+{
+    if start < view_start {
+        start - view_start
+    } else if start + size > view_start + view_size {
+        (start + size - view_start - view_size).min(start - view_start)
+    } else {
+        0f32
+    }
 }
@@ -1,6 +1,9 @@
-use super::dim::{self, DimFlags};
+use super::dim::{self, DimFlags, Offsets, Flow, Direction, Align, Grid, Constraints, Overflow};
 use super::{Dimensions, EdgeSizes, Rect};
+use super::text::TextStyle;
+use oil_shared::asset::FontData;
 use oil_shared::properties::PropertyName;
+use oil_shared::style::KwValue;
 use state::StateData;
 
 /// Reexport
@@ -9,14 +12,62 @@ pub use self::buffer::LayoutNode;
 
 mod buffer;
 
-// The layout box kids are unsorted (defined by the markup)
-// except the one declared with absolute positioning. They will
-// end up at the end sorted by z-index.
+// The layout box kids are kept in the markup order, including the
+// ones declared with absolute positioning. The order in which they
+// are painted is given by `LayoutBuffer::paint_order`.
 #[derive(Default)]
 pub struct LayoutBox {
     dim: Dimensions,
     // Stores auto/fixed behaviors
     flags: DimFlags,
+    // `dim` and `flags` as given by the style,
+    // the layout starts from them.
+    style_dim: Dimensions,
+    style_flags: DimFlags,
+    // Used to layout again this box alone
+    last: LastLayout,
+    // Only used with absolute positioning
+    offsets: Offsets,
+    flow: Flow,
+    grid: Option<Grid>,
+    constraints: Constraints,
+    overflow: Overflow,
+    // Scroll offset, kept when the state changes.
+    // Only used with `overflow: scroll`
+    scroll_x: f32,
+    scroll_y: f32,
+    // Resolved direction, set by the layout
+    rtl: bool,
+    // Area the box is clipped to, set by the layout
+    clip: Option<Rect>,
+    // Text of a `Text` or binding node
+    text: Option<String>,
+    // Font given by the style to the text of the children
+    font: Option<FontData>,
+    font_size: Option<f32>,
+    // Font of the parent, set by the layout
+    inherited_font: TextStyle,
+    // Width of the text on a single line, set by the layout
+    text_width: f32,
+    // Painted above its siblings, see `LayoutBuffer::paint_order`
+    z_index: Option<i32>,
+}
+
+/// Space given to a box by its parent during the last layout,
+/// and the space it has eaten (see `compute_layout_defaut_width`).
+#[derive(Copy, Clone, Default)]
+struct LastLayout {
+    width: f32,
+    auto_width: f32,
+    height: f32,
+    parent_is_rtl: bool,
+    eaten: f32,
+    // Size imposed by the parent, margins included:
+    // the size of a grid cell, or the size given by
+    // `align-items: stretch` to the children of a row or a column.
+    cell: Option<(f32, f32)>,
+    stretched_width: Option<f32>,
+    stretched_height: Option<f32>,
 }
 
 // TODO:
@@ -33,12 +84,98 @@ pub struct LayoutBoxRepeat {
 
 impl LayoutBox {
 
+    /// Box of a `Text` or binding node.
+    pub fn new_text(text: String) -> LayoutBox {
+        LayoutBox {
+            text: Some(text),
+            .. LayoutBox::default()
+        }
+    }
+
     #[inline]
     pub fn dim(&self) -> Dimensions {
         self.dim
     }
 
-    pub fn update_from_state(&mut self, state: &StateData) {
+    /// Returns true if the children of this box are
+    /// laid out from right to left.
+    #[inline]
+    pub fn is_rtl(&self) -> bool {
+        self.rtl
+    }
+
+    /// Returns the rect of the content of this box.
+    pub fn content_box(&self) -> Rect {
+        let ref d = self.dim;
+        Rect {
+            x: d.content.x + d.margin.left + d.border.left + d.padding.left,
+            y: d.content.y + d.margin.top + d.border.top + d.padding.top,
+            width: d.content.width,
+            height: d.content.height,
+        }
+    }
+
+    /// Returns the rect of the content and the padding of this box.
+    pub fn padding_box(&self) -> Rect {
+        let ref d = self.dim;
+        Rect {
+            x: d.content.x + d.margin.left + d.border.left,
+            y: d.content.y + d.margin.top + d.border.top,
+            width: d.content.width + d.padding.left + d.padding.right,
+            height: d.content.height + d.padding.top + d.padding.bottom,
+        }
+    }
+
+    /// Same as `padding_box` with the borders.
+    pub fn border_box(&self) -> Rect {
+        let ref d = self.dim;
+        Rect {
+            x: d.content.x + d.margin.left,
+            y: d.content.y + d.margin.top,
+            width: d.content.width
+                + d.padding.left + d.padding.right
+                + d.border.left + d.border.right,
+            height: d.content.height
+                + d.padding.top + d.padding.bottom
+                + d.border.top + d.border.bottom,
+        }
+    }
+
+    /// Same as `border_box` with the margins.
+    pub fn margin_box(&self) -> Rect {
+        let ref d = self.dim;
+        Rect {
+            x: d.content.x,
+            y: d.content.y,
+            width: d.content.width
+                + d.padding.left + d.padding.right
+                + d.border.left + d.border.right
+                + d.margin.left + d.margin.right,
+            height: d.content.height
+                + d.padding.top + d.padding.bottom
+                + d.border.top + d.border.bottom
+                + d.margin.top + d.margin.bottom,
+        }
+    }
+
+    /// Returns the area outside of which this box must not be drawn,
+    /// if one of its parents has an `overflow` other than `visible`.
+    #[inline]
+    pub fn clip(&self) -> Option<Rect> {
+        self.clip
+    }
+
+    /// Returns the scroll offset of this box.
+    #[inline]
+    pub fn scroll(&self) -> (f32, f32) {
+        (self.scroll_x, self.scroll_y)
+    }
+
+    /// Returns true if the layout of this box has changed.
+    ///
+    /// The result of the last layout is kept until the layout is
+    /// computed again: laying out this box alone needs its position.
+    pub fn update_from_state(&mut self, state: &StateData) -> bool {
         let mut flags = DimFlags::empty();
 
         // Auto states
@@ -93,6 +230,76 @@ impl LayoutBox {
             flags = flags | dim::WIDTH_EXPAND;
         }
 
+        if state.has_property_auto(PropertyName::HEIGHT) {
+            flags = flags | dim::HEIGHT_AUTO;
+        }
+
+        if state.has_property_expand(PropertyName::HEIGHT) {
+            flags = flags | dim::HEIGHT_EXPAND;
+        }
+
+        // Stacking
+        let direction = match state.keyword_of_prop(PropertyName::DIRECTION) {
+            Some(KwValue::Row) => Direction::Row,
+            Some(KwValue::Column) => Direction::Column,
+            _ => Direction::Inline,
+        };
+        let flow = Flow {
+            direction: direction,
+            justify: align_of_prop(state, PropertyName::JUSTIFY_CONTENT),
+            align: align_of_prop(state, PropertyName::ALIGN_ITEMS),
+            gap: state.size_of_prop(PropertyName::GAP),
+        };
+
+        // Direction
+        if state.has_property_eq_kw(PropertyName::LAYOUT_MODE, KwValue::Rtl) {
+            flags = flags | dim::LAYOUT_RTL;
+        }
+
+        if state.has_property_eq_kw(PropertyName::LAYOUT_MODE, KwValue::Ltr) {
+            flags = flags | dim::LAYOUT_LTR;
+        }
+
+        // Grid
+        let grid = if state.has_property_eq_kw(PropertyName::DISPLAY, KwValue::Grid) {
+            let count = |prop_name| state.size_prop_as_opt(prop_name)
+                .map(|n| n.max(0f32) as usize);
+            Some(Grid {
+                columns: count(PropertyName::GRID_COLUMNS).unwrap_or(1).max(1),
+                rows: count(PropertyName::GRID_ROWS),
+                cell_width: state.size_prop_as_opt(PropertyName::CELL_WIDTH),
+                cell_height: state.size_prop_as_opt(PropertyName::CELL_HEIGHT),
+            })
+        } else {
+            None
+        };
+
+        // Overflow
+        let overflow = match state.keyword_of_prop(PropertyName::OVERFLOW) {
+            Some(KwValue::Hidden) => Overflow::Hidden,
+            Some(KwValue::Scroll) => Overflow::Scroll,
+            _ => Overflow::Visible,
+        };
+
+        // Absolute positioning
+        let offsets = Offsets {
+            left: state.size_prop_as_opt(PropertyName::LEFT),
+            right: state.size_prop_as_opt(PropertyName::RIGHT),
+            top: state.size_prop_as_opt(PropertyName::TOP),
+            bottom: state.size_prop_as_opt(PropertyName::BOTTOM),
+        };
+
+        if state.has_property_eq_kw(PropertyName::LAYOUT_MODE, KwValue::Absolute) {
+            flags = flags | dim::ABSOLUTE_POSITIONING;
+
+            // Stretched between left and right, unless the width is fixed
+            if offsets.left.is_some() && offsets.right.is_some()
+                && state.size_prop_as_opt(PropertyName::WIDTH).is_none()
+            {
+                flags = flags | dim::WIDTH_AUTO;
+            }
+        }
+
         // Sizes
         let padding_left = state.size_of_prop(PropertyName::PADDING_LEFT);
         let padding_right = state.size_of_prop(PropertyName::PADDING_RIGHT);
@@ -109,48 +316,147 @@ impl LayoutBox {
         let border_top = state.size_of_prop(PropertyName::BORDER_TOP);
         let border_bottom = state.size_of_prop(PropertyName::BORDER_BOTTOM);
 
+        // Constraints
+        let mut constraints = Constraints::default();
+        if let Some(w) = state.size_prop_as_opt(PropertyName::MIN_WIDTH) {
+            constraints.min_width = w;
+        }
+        if let Some(w) = state.size_prop_as_opt(PropertyName::MAX_WIDTH) {
+            constraints.max_width = w;
+        }
+        if let Some(h) = state.size_prop_as_opt(PropertyName::MIN_HEIGHT) {
+            constraints.min_height = h;
+        }
+        if let Some(h) = state.size_prop_as_opt(PropertyName::MAX_HEIGHT) {
+            constraints.max_height = h;
+        }
+        constraints.aspect_ratio = match state.size_prop_as_opt(PropertyName::ASPECT_RATIO) {
+            Some(r) if r > 0f32 => Some(r),
+            _ => None,
+        };
+
         let width = match state.size_prop_as_opt(PropertyName::WIDTH) {
             Some(w) => {
                 flags = flags | dim::WIDTH_FIXED;
-                w
+                constraints.clamp_width(w)
             }
             None => 0f32
         };
 
-        let height = match state.size_prop_as_opt(PropertyName::HEIGHT) {
+        let mut height = match state.size_prop_as_opt(PropertyName::HEIGHT) {
             Some(h) => {
                 flags = flags | dim::HEIGHT_FIXED;
-                h
+                constraints.clamp_height(h)
             }
             None => 0f32
         };
 
-        // TODO: Missing bit for left / right / top / bottom
-        //       We also need at some point the relative information
-        self.dim.content = Rect {
-            x: 0f32,
-            y: 0f32,
-            width: width,
-            height: height,
-        };
-        self.dim.padding = EdgeSizes {
-            left: padding_left,
-            right: padding_right,
-            top: padding_top,
-            bottom: padding_bottom,
-        };
-        self.dim.border = EdgeSizes {
-            left: border_left,
-            right: border_right,
-            top: border_top,
-            bottom: border_bottom
+        // With a fixed width the height is known now, with a fixed
+        // height the width is. Otherwise the height follows the width
+        // once it's computed.
+        let width = match constraints.aspect_ratio {
+            Some(ratio) if flags.has_width_fixed() && !flags.has_height_fixed() => {
+                flags = flags | dim::HEIGHT_FIXED;
+                height = constraints.clamp_height(width / ratio);
+                width
+            }
+            Some(ratio) if flags.has_height_fixed() && !flags.has_width_fixed() => {
+                flags = (flags & !(dim::WIDTH_AUTO | dim::WIDTH_EXPAND)) | dim::WIDTH_FIXED;
+                constraints.clamp_width(height * ratio)
+            }
+            _ => width
         };
-        self.dim.margin = EdgeSizes {
-            left: margin_left,
-            right: margin_right,
-            top: margin_top,
-            bottom: margin_bottom
+
+        let dim = Dimensions {
+            content: Rect {
+                x: 0f32,
+                y: 0f32,
+                width: width,
+                height: height,
+            },
+            padding: EdgeSizes {
+                left: padding_left,
+                right: padding_right,
+                top: padding_top,
+                bottom: padding_bottom,
+            },
+            border: EdgeSizes {
+                left: border_left,
+                right: border_right,
+                top: border_top,
+                bottom: border_bottom
+            },
+            margin: EdgeSizes {
+                left: margin_left,
+                right: margin_right,
+                top: margin_top,
+                bottom: margin_bottom
+            },
         };
-        self.flags = flags;
+
+        // Text
+        let font = state.get_font();
+        let font_size = state.size_prop_as_opt(PropertyName::FONT_SIZE);
+
+        // Stacking
+        let z_index = state.size_prop_as_opt(PropertyName::Z_INDEX).map(|z| z as i32);
+
+        let changed = self.style_dim != dim
+            || self.style_flags != flags
+            || self.offsets != offsets
+            || self.flow != flow
+            || self.grid != grid
+            || self.constraints != constraints
+            || self.overflow != overflow
+            || self.font != font
+            || self.font_size != font_size
+            || self.z_index != z_index;
+
+        if changed {
+            self.style_dim = dim;
+            self.style_flags = flags;
+            self.offsets = offsets;
+            self.flow = flow;
+            self.grid = grid;
+            self.constraints = constraints;
+            self.overflow = overflow;
+            self.font = font;
+            self.font_size = font_size;
+            self.z_index = z_index;
+        }
+
+        changed
+    }
+
+    /// Font of the text of this box and of its children.
+    fn text_style(&self) -> TextStyle {
+        TextStyle {
+            font: self.font.clone().or_else(|| self.inherited_font.font.clone()),
+            size: self.font_size.unwrap_or(self.inherited_font.size),
+        }
+    }
+
+    /// Forget the result of the last layout.
+    #[inline]
+    fn reset(&mut self) {
+        self.dim = self.style_dim;
+        self.flags = self.style_flags;
+        self.last.cell = None;
+        self.last.stretched_width = None;
+        self.last.stretched_height = None;
+    }
+}
+
+// ======================================== //
+//                  HELPERS                 //
+// ======================================== //
+
+fn align_of_prop(state: &StateData, prop_name: PropertyName) -> Align {
+    match state.keyword_of_prop(prop_name) {
+        Some(KwValue::Center) => Align::Center,
+        Some(KwValue::End) => Align::End,
+        Some(KwValue::SpaceBetween) => Align::SpaceBetween,
+        Some(KwValue::Stretch) => Align::Stretch,
+        _ => Align::Start,
     }
 }
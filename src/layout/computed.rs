@@ -0,0 +1,60 @@
+use std::rc::Rc;
+use markup::View;
+use oil_shared::style::Stylesheet;
+use state::StateBuffer;
+use Viewport;
+use super::{LayoutBuffer, LayoutNode, Rect, TextMeasurer};
+use super::text::NoTextMeasurer;
+
+/// The boxes of a node once laid out, with the
+/// boxes of its children in the markup order.
+///
+/// All the rects are relative to the viewport origin.
+#[derive(Clone, Debug)]
+pub struct BoxLayout {
+    pub content: Rect,
+    pub padding: Rect,
+    pub border: Rect,
+    pub margin: Rect,
+    pub children: Vec<BoxLayout>,
+}
+
+impl BoxLayout {
+
+    fn from_node(node: &LayoutNode) -> BoxLayout {
+        BoxLayout {
+            content: node.content_box(),
+            padding: node.padding_box(),
+            border: node.border_box(),
+            margin: node.margin_box(),
+            children: node.children().map(BoxLayout::from_node).collect(),
+        }
+    }
+}
+
+/// Layout a view in the given viewport, without any node focused.
+///
+/// Unlike `View`, this doesn't need a display: this can be
+/// used to test the layout of a view. The bindings of the view
+/// are not resolved and the text takes no space.
+pub fn layout_view(view: &View, stylesheet: &Stylesheet, vp: Viewport) -> BoxLayout {
+    layout_view_with_measurer(view, stylesheet, vp, Rc::new(NoTextMeasurer))
+}
+
+/// Same as `layout_view`, the text nodes are measured with `measurer`.
+/// See `FixedAdvanceMeasurer` to test a layout without any font.
+pub fn layout_view_with_measurer(
+    view: &View,
+    stylesheet: &Stylesheet,
+    vp: Viewport,
+    measurer: Rc<TextMeasurer>)
+    -> BoxLayout
+{
+    let state = StateBuffer::new(view, stylesheet);
+    let mut layout = LayoutBuffer::new(view);
+    layout.set_text_measurer(measurer);
+    layout.update_from_state(&state);
+    layout.compute_layout(vp.width, vp.height);
+
+    BoxLayout::from_node(&layout[0])
+}
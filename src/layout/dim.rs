@@ -1,11 +1,12 @@
 #![allow(dead_code)]
+use std::f32;
 use super::Rect;
 
 /// Dimensions for the box model.
 ///
 /// This code follows the css box model
 /// in naming and conventions. (all sizes are in pixels)
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, PartialEq)]
 pub struct Dimensions {
     // Position of the content area relative to the viewport origin
     pub content: Rect,
@@ -14,7 +15,7 @@ pub struct Dimensions {
     pub margin: EdgeSizes,
 }
 
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, PartialEq)]
 pub struct EdgeSizes {
     pub left: f32,
     pub right: f32,
@@ -22,9 +23,205 @@ pub struct EdgeSizes {
     pub bottom: f32,
 }
 
+/// Offsets of an absolutely positioned box, relative
+/// to the padding box of its parent.
+#[derive(Copy, Clone, Default, PartialEq)]
+pub struct Offsets {
+    pub left: Option<f32>,
+    pub right: Option<f32>,
+    pub top: Option<f32>,
+    pub bottom: Option<f32>,
+}
+
+impl Offsets {
+
+    /// Returns the width left for the box in a parent
+    /// whose padding box is `width` wide.
+    #[inline]
+    pub fn available_width(&self, width: f32) -> f32 {
+        width - self.left.unwrap_or(0f32) - self.right.unwrap_or(0f32)
+    }
+
+    /// Same as `available_width` for the height.
+    #[inline]
+    pub fn available_height(&self, height: f32) -> f32 {
+        height - self.top.unwrap_or(0f32) - self.bottom.unwrap_or(0f32)
+    }
+}
+
+/// Bounds of the content size of a box, and the ratio
+/// between its width and its height.
+#[derive(Copy, Clone, PartialEq)]
+pub struct Constraints {
+    pub min_width: f32,
+    pub max_width: f32,
+    pub min_height: f32,
+    pub max_height: f32,
+    pub aspect_ratio: Option<f32>,
+}
+
+impl Default for Constraints {
+    fn default() -> Constraints {
+        Constraints {
+            min_width: 0f32,
+            max_width: f32::INFINITY,
+            min_height: 0f32,
+            max_height: f32::INFINITY,
+            aspect_ratio: None,
+        }
+    }
+}
+
+impl Constraints {
+
+    /// Returns `width` within `min_width` and `max_width`.
+    /// The minimum wins over the maximum.
+    #[inline]
+    pub fn clamp_width(&self, width: f32) -> f32 {
+        width.min(self.max_width).max(self.min_width)
+    }
+
+    /// Same as `clamp_width` for the height.
+    #[inline]
+    pub fn clamp_height(&self, height: f32) -> f32 {
+        height.min(self.max_height).max(self.min_height)
+    }
+}
+
+/// How the children of a box are laid out.
+#[derive(Copy, Clone, Default, PartialEq)]
+pub struct Flow {
+    pub direction: Direction,
+    // Alignment along the direction
+    pub justify: Align,
+    // Alignment across the direction
+    pub align: Align,
+    // Space between two children of a row or a column
+    pub gap: f32,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Direction {
+    // Lines filled from left to right, wrapped when full
+    Inline,
+    // A single line, never wrapped
+    Row,
+    // One child per line
+    Column,
+}
+
+impl Default for Direction {
+    fn default() -> Direction {
+        Direction::Inline
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+    SpaceBetween,
+    Stretch,
+}
+
+impl Default for Align {
+    fn default() -> Align {
+        Align::Start
+    }
+}
+
+impl Align {
+
+    /// Returns the offset of a child in the `free` space
+    /// left across the direction.
+    #[inline]
+    pub fn offset(&self, free: f32) -> f32 {
+        match *self {
+            Align::Center => free / 2f32,
+            Align::End => free,
+            _ => 0f32,
+        }
+    }
+
+    /// Returns the offset of the first child and the space added
+    /// between the children to distribute the `free` space left
+    /// along the direction.
+    pub fn distribute(&self, free: f32, count: usize) -> (f32, f32) {
+        let free = free.max(0f32);
+        match *self {
+            Align::Center => (free / 2f32, 0f32),
+            Align::End => (free, 0f32),
+            Align::SpaceBetween if count > 1 => (0f32, free / (count - 1) as f32),
+            _ => (0f32, 0f32),
+        }
+    }
+}
+
+/// What happens to the children of a box that
+/// don't fit in its padding box.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Overflow {
+    // Drawn outside of the box
+    Visible,
+    // Clipped
+    Hidden,
+    // Clipped, and moved by the scroll offset of the box
+    Scroll,
+}
+
+impl Default for Overflow {
+    fn default() -> Overflow {
+        Overflow::Visible
+    }
+}
+
+/// Cells of a box with `display: grid`. The children of the
+/// box are placed in the cells, row by row.
+#[derive(Copy, Clone, PartialEq)]
+pub struct Grid {
+    pub columns: usize,
+    // By default, as many rows as needed
+    pub rows: Option<usize>,
+    // By default, computed from the size of the box
+    pub cell_width: Option<f32>,
+    pub cell_height: Option<f32>,
+}
+
+impl Grid {
+
+    /// Returns the size of `count` cells of `cell` size,
+    /// with a `gap` between each of them.
+    #[inline]
+    pub fn extent(count: usize, cell: f32, gap: f32) -> f32 {
+        if count == 0 {
+            0f32
+        } else {
+            count as f32 * cell + (count - 1) as f32 * gap
+        }
+    }
+
+    /// Inverse of `extent`: the size of a cell.
+    #[inline]
+    pub fn cell(count: usize, extent: f32, gap: f32) -> f32 {
+        if count == 0 {
+            0f32
+        } else {
+            (extent - (count - 1) as f32 * gap) / count as f32
+        }
+    }
+}
+
 bitflags! {
     #[derive(Default)]
     flags DimFlags: u16 {
+        const HEIGHT_AUTO           = 0b100_0000_0000_0000,
+        const HEIGHT_EXPAND         = 0b010_0000_0000_0000,
+
+        // Direction set by the style, otherwise inherited
+        const LAYOUT_LTR            = 0b1_0000_0000_0000,
+        const LAYOUT_RTL            = 0b0_1000_0000_0000,
+
         // A text node is WIDTH_FIXED,
         // A node with a style fixed width is naturally WIDTH_FIXED
         const ABSOLUTE_POSITIONING  = 0b0100_0000_0000,
@@ -66,6 +263,24 @@ impl DimFlags {
         self.is_x_auto() || self.is_x_expand()
     }
 
+    /// Absolutely positioned boxes are taken out of the line flow.
+    #[inline]
+    pub fn has_absolute_positioning(&self) -> bool {
+        self.contains(ABSOLUTE_POSITIONING)
+    }
+
+    /// Returns the direction of this node, given the one of its parent.
+    #[inline]
+    pub fn is_rtl(&self, parent_is_rtl: bool) -> bool {
+        if self.contains(LAYOUT_RTL) {
+            true
+        } else if self.contains(LAYOUT_LTR) {
+            false
+        } else {
+            parent_is_rtl
+        }
+    }
+
     #[inline]
     pub fn has_width_auto(&self) -> bool {
         self.contains(WIDTH_AUTO)
@@ -86,6 +301,16 @@ impl DimFlags {
         self.contains(HEIGHT_FIXED)
     }
 
+    #[inline]
+    pub fn has_height_auto(&self) -> bool {
+        self.contains(HEIGHT_AUTO)
+    }
+
+    #[inline]
+    pub fn has_height_expand(&self) -> bool {
+        self.contains(HEIGHT_EXPAND)
+    }
+
     #[inline]
     pub fn has_margin_top_or_bot_auto(&self) -> bool {
         self.intersects(MARGIN_Y_AUTO)
@@ -0,0 +1,211 @@
+use std::env;
+use std::fmt::Write;
+use std::fs::File;
+use std::io::{Read, Write as IoWrite};
+use std::path::Path;
+
+use markup::{Node, View};
+use super::{BoxLayout, Rect};
+
+/// Set this environment variable to write the snapshots
+/// instead of comparing them, see `assert_layout_snapshot`.
+pub const UPDATE_SNAPSHOTS_VAR: &'static str = "OIL_UPDATE_SNAPSHOTS";
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DumpFormat {
+    // One line per node, indented by depth
+    Text,
+    Json,
+}
+
+/// Dump a view laid out by `layout_view` as an indented document.
+/// For each node: its tag, its classes (sorted), its id and its rects.
+///
+/// The id of a node is its index in the view, in depth first order.
+pub fn dump_layout(view: &View, root: &BoxLayout, format: DumpFormat) -> String {
+    let mut out = String::new();
+    let mut id = 0;
+
+    match format {
+        DumpFormat::Text => dump_text(&mut out, view, root, &mut id, 0),
+        DumpFormat::Json => {
+            dump_json(&mut out, view, root, &mut id, 0);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// Compare `dump` with the snapshot stored at `path`.
+///
+/// If the `OIL_UPDATE_SNAPSHOTS` environment variable is set,
+/// the snapshot is written instead.
+///
+/// # Panics
+///
+/// Panics with the lines that differ if the dump doesn't match,
+/// or if the snapshot doesn't exist.
+pub fn assert_layout_snapshot<P: AsRef<Path>>(dump: &str, path: P) {
+    let path = path.as_ref();
+
+    if env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+        let mut file = File::create(path).unwrap();
+        file.write_all(dump.as_bytes()).unwrap();
+        return;
+    }
+
+    if !path.exists() {
+        panic!("No snapshot at `{}`.\nSet {} to write it.",
+               path.display(), UPDATE_SNAPSHOTS_VAR);
+    }
+
+    let mut expected = String::new();
+    File::open(path).unwrap().read_to_string(&mut expected).unwrap();
+
+    if let Some(diff) = diff_snapshot(&expected, dump) {
+        panic!("Layout of `{}` has changed (- snapshot, + now):\n{}\n\
+                Set {} to update the snapshot.",
+               path.display(), diff, UPDATE_SNAPSHOTS_VAR);
+    }
+}
+
+/// Returns the lines removed from `expected` (prefixed by `-`)
+/// and added in `actual` (prefixed by `+`), with the unchanged
+/// lines around them. Returns None if they are equal.
+pub fn diff_snapshot(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence, from the end.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            writeln!(out, "  {}", old[i]).unwrap();
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            writeln!(out, "- {}", old[i]).unwrap();
+            i += 1;
+        } else {
+            writeln!(out, "+ {}", new[j]).unwrap();
+            j += 1;
+        }
+    }
+
+    Some(out)
+}
+
+// ======================================== //
+//                  HELPERS                 //
+// ======================================== //
+
+fn sorted_classes(node: &Node) -> Vec<&str> {
+    let mut classes: Vec<&str> = node.classes().into_iter()
+        .filter(|c| !c.is_empty())
+        .collect();
+    classes.sort();
+    classes
+}
+
+fn dump_text(out: &mut String, node: &Node, lb: &BoxLayout, id: &mut usize, depth: usize) {
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+
+    out.push_str(node.node_type.tag_name());
+    for class in sorted_classes(node) {
+        write!(out, ".{}", class).unwrap();
+    }
+    write!(out, " #{}", *id).unwrap();
+    *id += 1;
+
+    for &(name, ref rect) in &rects(lb) {
+        write!(out, " {}({} {} {} {})", name, rect.x, rect.y, rect.width, rect.height).unwrap();
+    }
+    out.push('\n');
+
+    for (child, child_lb) in node.children.iter().zip(lb.children.iter()) {
+        dump_text(out, child, child_lb, id, depth + 1);
+    }
+}
+
+fn dump_json(out: &mut String, node: &Node, lb: &BoxLayout, id: &mut usize, depth: usize) {
+    let indent: String = (0..depth + 1).map(|_| "  ").collect();
+
+    out.push_str("{\n");
+    write!(out, "{}\"id\": {},\n", indent, *id).unwrap();
+    write!(out, "{}\"type\": \"{}\",\n", indent, node.node_type.tag_name()).unwrap();
+    *id += 1;
+
+    let classes: Vec<String> = sorted_classes(node).iter()
+        .map(|c| format!("\"{}\"", json_escape(c)))
+        .collect();
+    write!(out, "{}\"classes\": [{}],\n", indent, classes.join(", ")).unwrap();
+
+    for &(name, ref rect) in &rects(lb) {
+        write!(out, "{}\"{}\": {{ \"x\": {}, \"y\": {}, \"width\": {}, \"height\": {} }},\n",
+               indent, name, rect.x, rect.y, rect.width, rect.height).unwrap();
+    }
+
+    write!(out, "{}\"children\": [", indent).unwrap();
+    let mut first = true;
+    for (child, child_lb) in node.children.iter().zip(lb.children.iter()) {
+        if first {
+            out.push('\n');
+            first = false;
+        } else {
+            out.push_str(",\n");
+        }
+        out.push_str(&indent);
+        out.push_str("  ");
+        dump_json(out, child, child_lb, id, depth + 2);
+    }
+    if !first {
+        write!(out, "\n{}", indent).unwrap();
+    }
+    out.push_str("]\n");
+
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+    out.push('}');
+}
+
+fn rects(lb: &BoxLayout) -> [(&'static str, Rect); 4] {
+    [
+        ("content", lb.content),
+        ("padding", lb.padding),
+        ("border", lb.border),
+        ("margin", lb.margin),
+    ]
+}
+
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
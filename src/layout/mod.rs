@@ -5,12 +5,579 @@ pub use self::dim::EdgeSizes;
 pub use self::boxes::LayoutBuffer;
 pub use self::boxes::LayoutBox;
 pub use self::boxes::LayoutNode;
+pub use self::computed::{BoxLayout, layout_view, layout_view_with_measurer};
+pub use self::dump::{DumpFormat, dump_layout, diff_snapshot, assert_layout_snapshot};
+pub use self::text::{TextMeasurer, TextExtents, FixedAdvanceMeasurer};
 
 mod rect;
 mod boxes;
 mod dim;
+mod computed;
+mod dump;
+mod text;
 
 #[cfg(test)]
 mod test {
-    // TODO
+
+    use std::env;
+    use std::io::BufReader;
+    use std::rc::Rc;
+    use super::{LayoutBuffer, FixedAdvanceMeasurer, layout_view};
+    use super::{DumpFormat, dump_layout, diff_snapshot, assert_layout_snapshot};
+    use Viewport;
+    use markup;
+    use style;
+    use resource;
+    use state::StateBuffer;
+    use oil_shared::deps::StyleDefinitions;
+    use oil_shared::style::SelectorState;
+    use oil_parsers::EmptyErrorReporter;
+
+    fn buffers(mk: &str, st: &str, width: f32, height: f32) -> (StateBuffer, LayoutBuffer) {
+        let lib = markup::parse(EmptyErrorReporter, BufReader::new(mk.as_bytes()));
+        let (_, view) = lib.views.into_iter().next().unwrap();
+        let stylesheet = style::parse(
+            EmptyErrorReporter,
+            BufReader::new(st.as_bytes()),
+            &StyleDefinitions::new(),
+            &mut resource::create_null_manager());
+        let state = StateBuffer::new(&view, &stylesheet);
+        let mut layout = LayoutBuffer::new(&view);
+        layout.update_from_state(&state);
+        layout.compute_layout(width, height);
+        (state, layout)
+    }
+
+    fn layout(mk: &str, st: &str, width: f32, height: f32) -> LayoutBuffer {
+        buffers(mk, st, width, height).1
+    }
+
+    #[test]
+    fn absolute_nodes_should_be_out_of_the_line_flow() {
+        let layout = layout(
+            "<view>\
+                <group class=\"panel\">\
+                    <button class=\"badge\"></button>\
+                    <button class=\"btn\"></button>\
+                    <button class=\"bar\"></button>\
+                </group>\
+            </view>",
+            ".panel { width: 200px; height: 100px; padding: 10px; }
+             .btn { width: 50px; height: 30px; }
+             .badge { layout: absolute; right: 5px; top: 5px; width: 20px; height: 20px; }
+             .bar { layout: absolute; left: 10px; right: 10px; bottom: 0px; height: 4px; }",
+            300f32, 300f32);
+
+        let badge = layout[2].dim().content;
+        assert_eq!((badge.x, badge.y, badge.width, badge.height), (195f32, 5f32, 20f32, 20f32));
+
+        let btn = layout[3].dim().content;
+        assert_eq!((btn.x, btn.y), (10f32, 10f32));
+
+        let bar = layout[4].dim().content;
+        assert_eq!((bar.x, bar.y, bar.width, bar.height), (10f32, 116f32, 200f32, 4f32));
+    }
+
+    #[test]
+    fn rtl_should_mirror_the_children_and_be_inherited() {
+        let layout = layout(
+            "<view>\
+                <group class=\"panel\">\
+                    <button class=\"btn\"></button>\
+                    <group class=\"row\">\
+                        <button class=\"btn\"></button>\
+                    </group>\
+                    <button class=\"pin\"></button>\
+                </group>\
+            </view>",
+            ".panel { layout: rtl; width: 200px; }
+             .row { width: 100px; }
+             .btn { width: 50px; height: 30px; }
+             .pin { layout: absolute; width: 20px; height: 20px; }",
+            300f32, 300f32);
+
+        assert!(!layout[0].is_rtl());
+        assert!(layout[1].is_rtl());
+        assert!(layout[3].is_rtl());
+        assert_eq!(layout[2].dim().content.x, 150f32);
+        assert_eq!(layout[3].dim().content.x, 50f32);
+        assert_eq!(layout[4].dim().content.x, 100f32);
+        assert_eq!(layout[5].dim().content.x, 180f32);
+    }
+
+    #[test]
+    fn column_should_align_and_space_its_children() {
+        let layout = layout(
+            "<view>\
+                <group class=\"menu\">\
+                    <button class=\"btn\"></button>\
+                    <button class=\"btn\"></button>\
+                    <button class=\"btn\"></button>\
+                </group>\
+            </view>",
+            ".menu { direction: column; align-items: center; justify-content: center;
+                     gap: 10px; width: 200px; height: 200px; }
+             .btn { width: 100px; height: 40px; }",
+            400f32, 400f32);
+
+        let ys: Vec<_> = (2..5).map(|i| layout[i].dim().content.y).collect();
+        assert_eq!(ys, vec![30f32, 80f32, 130f32]);
+        assert_eq!(layout[2].dim().content.x, 50f32);
+    }
+
+    #[test]
+    fn padded_children_should_not_overlap_in_rows_and_columns() {
+        let layout = layout(
+            "<view>\
+                <group class=\"bar\">\
+                    <button class=\"btn\"></button>\
+                    <button class=\"btn\"></button>\
+                </group>\
+                <group class=\"menu\">\
+                    <button class=\"btn\"></button>\
+                    <button class=\"btn\"></button>\
+                </group>\
+            </view>",
+            ".bar { direction: row; justify-content: end; gap: 10px; width: 300px; }
+             .menu { direction: column; gap: 10px; width: 300px; }
+             .btn { width: 50px; height: 30px; padding: 5px; }",
+            400f32, 400f32);
+
+        let xs: Vec<_> = (2..4).map(|i| layout[i].dim().content.x).collect();
+        assert_eq!(xs, vec![170f32, 240f32]);
+        assert_eq!(layout[1].dim().content.height, 40f32);
+
+        let ys: Vec<_> = (5..7).map(|i| layout[i].dim().content.y).collect();
+        assert_eq!(ys, vec![40f32, 90f32]);
+        assert_eq!(layout[4].dim().content.height, 90f32);
+    }
+
+    #[test]
+    fn expand_children_should_share_the_space_left() {
+        let layout = layout(
+            "<view>\
+                <group class=\"bar\">\
+                    <button class=\"icon\"></button>\
+                    <button class=\"fill\"></button>\
+                    <button class=\"icon\"></button>\
+                </group>\
+                <group class=\"list\">\
+                    <button class=\"icon\"></button>\
+                    <group class=\"spacer\"></group>\
+                    <button class=\"icon\"></button>\
+                </group>\
+            </view>",
+            ".bar { direction: row; align-items: end; gap: 10px; width: 300px; }
+             .list { direction: column; width: 300px; height: 200px; }
+             .icon { width: 40px; height: 40px; }
+             .fill { width: expand; height: 20px; }
+             .spacer { width: 10px; height: expand; }",
+            400f32, 400f32);
+
+        let fill = layout[3].dim().content;
+        assert_eq!((fill.x, fill.y, fill.width), (50f32, 20f32, 200f32));
+        assert_eq!(layout[4].dim().content.x, 260f32);
+
+        let spacer = layout[7].dim().content;
+        assert_eq!((spacer.y, spacer.height), (80f32, 120f32));
+        assert_eq!(layout[8].dim().content.y, 200f32);
+    }
+
+    #[test]
+    fn grid_should_place_its_children_in_cells() {
+        let layout = layout(
+            "<view>\
+                <group class=\"inventory\">\
+                    <button class=\"slot\"></button>\
+                    <button class=\"slot\"></button>\
+                    <button class=\"slot\"></button>\
+                    <button class=\"slot\"></button>\
+                    <button class=\"slot\"></button>\
+                </group>\
+            </view>",
+            ".inventory { display: grid; grid-columns: 3; cell-width: 50px;
+                          cell-height: 40px; gap: 10px; }
+             .slot { margin: 5px; }",
+            400f32, 400f32);
+
+        let inventory = layout[1].dim().content;
+        assert_eq!((inventory.width, inventory.height), (170f32, 90f32));
+
+        let slot = layout[2].dim().content;
+        assert_eq!((slot.width, slot.height), (40f32, 30f32));
+
+        let pos: Vec<_> = (2..7)
+            .map(|i| (layout[i].dim().content.x, layout[i].dim().content.y))
+            .collect();
+        assert_eq!(pos, vec![(0f32, 0f32), (60f32, 0f32), (120f32, 0f32),
+                             (0f32, 50f32), (60f32, 50f32)]);
+    }
+
+    #[test]
+    fn grid_should_ignore_the_auto_sizes_of_its_children() {
+        let layout = layout(
+            "<view>\
+                <group class=\"inventory\">\
+                    <button class=\"slot\"></button>\
+                    <button class=\"slot\"></button>\
+                </group>\
+            </view>",
+            ".inventory { display: grid; grid-columns: 2; cell-width: 50px;
+                          cell-height: 40px; gap: 10px; }
+             .slot { width: auto; height: expand; margin: auto; }",
+            400f32, 400f32);
+
+        let pos: Vec<_> = (2..4)
+            .map(|i| {
+                let slot = layout[i].dim().content;
+                (slot.x, slot.width, slot.height)
+            })
+            .collect();
+        assert_eq!(pos, vec![(0f32, 50f32, 40f32), (60f32, 50f32, 40f32)]);
+    }
+
+    #[test]
+    fn grid_without_cell_width_should_share_its_width() {
+        let layout = layout(
+            "<view>\
+                <group class=\"inventory\">\
+                    <button class=\"slot\"></button>\
+                    <button class=\"slot\"></button>\
+                    <button class=\"slot\"></button>\
+                </group>\
+            </view>",
+            ".inventory { display: grid; grid-columns: 3; width: expand;
+                          cell-height: 40px; gap: 10px; }
+             .slot { margin: 5px; }",
+            320f32, 400f32);
+
+        assert_eq!(layout[1].dim().content.width, 320f32);
+
+        let pos: Vec<_> = (2..5)
+            .map(|i| (layout[i].dim().content.x, layout[i].dim().content.width))
+            .collect();
+        assert_eq!(pos, vec![(0f32, 90f32), (110f32, 90f32), (220f32, 90f32)]);
+    }
+
+    #[test]
+    fn sizes_should_respect_constraints_and_aspect_ratio() {
+        let layout = layout(
+            "<view>\
+                <group class=\"panel\">\
+                    <button class=\"icon\"></button>\
+                    <button class=\"wide\"></button>\
+                </group>\
+            </view>",
+            ".panel { width: expand; max-width: 300px; min-height: 60px; }
+             .icon { width: 40px; aspect-ratio: 1; }
+             .wide { width: expand; max-width: 100px; aspect-ratio: 2; }",
+            1000f32, 500f32);
+
+        let panel = layout[1].dim().content;
+        assert_eq!((panel.width, panel.height), (300f32, 60f32));
+
+        let icon = layout[2].dim().content;
+        assert_eq!((icon.width, icon.height), (40f32, 40f32));
+
+        let wide = layout[3].dim().content;
+        assert_eq!((wide.x, wide.width, wide.height), (40f32, 100f32, 50f32));
+    }
+
+    #[test]
+    fn scroll_containers_should_clip_and_move_their_children() {
+        let mut layout = layout(
+            "<view>\
+                <group class=\"list\">\
+                    <button class=\"item\"></button>\
+                    <button class=\"item\"></button>\
+                    <button class=\"item\"></button>\
+                    <button class=\"item\"></button>\
+                    <button class=\"item\"></button>\
+                </group>\
+            </view>",
+            ".list { overflow: scroll; width: 100px; height: 100px; }
+             .item { width: 100px; height: 40px; }",
+            400f32, 400f32);
+
+        let clip = layout[2].clip().unwrap();
+        assert_eq!((clip.x, clip.y, clip.width, clip.height), (0f32, 0f32, 100f32, 100f32));
+        assert!(layout[1].clip().is_none());
+
+        assert!(layout.scroll_by(2, 0f32, 70f32));
+        layout.compute_layout(400f32, 400f32);
+        assert_eq!(layout[2].dim().content.y, -70f32);
+
+        // Clamped to the size of the content
+        layout.scroll_by(1, 0f32, 1000f32);
+        layout.compute_layout(400f32, 400f32);
+        assert_eq!(layout[1].scroll(), (0f32, 100f32));
+        assert_eq!(layout[6].dim().content.y, 60f32);
+
+        assert!(layout.scroll_into_view(2));
+        layout.compute_layout(400f32, 400f32);
+        assert_eq!(layout[2].dim().content.y, 0f32);
+        assert!(!layout.scroll_into_view(3));
+    }
+
+    #[test]
+    fn state_change_should_only_layout_the_subtree_again() {
+        let (mut state, mut layout) = buffers(
+            "<view>\
+                <group class=\"bar\">\
+                    <button class=\"btn\"></button>\
+                    <button class=\"btn\"></button>\
+                </group>\
+                <group class=\"bar\">\
+                    <button class=\"btn\"></button>\
+                </group>\
+            </view>",
+            ".bar { width: 300px; height: 50px; }
+             .btn { width: 50px; height: 30px; }
+             .btn:focus { width: 70px; }",
+            400f32, 400f32);
+
+        layout.clear_dirty();
+        state.get_mut(2).unwrap().set_current_state(SelectorState::Focus);
+        layout.update_nodes_from_state(&[2], &state, 400f32, 400f32);
+
+        assert_eq!(layout[2].dim().content.width, 70f32);
+        assert_eq!(layout[3].dim().content.x, 70f32);
+
+        // The size of the bar hasn't changed: the rest is untouched.
+        let dirty: Vec<_> = layout.iter().map(|n| n.is_dirty()).collect();
+        assert_eq!(dirty, vec![false, true, true, true, false, false]);
+
+        // Same result as a full layout
+        let rects: Vec<_> = layout.iter().map(|n| n.dim().content).collect();
+        layout.update_from_state(&state);
+        layout.compute_layout(400f32, 400f32);
+        for (node, a) in layout.iter().zip(rects.iter()) {
+            let b = node.dim().content;
+            assert_eq!((a.x, a.y, a.width, a.height), (b.x, b.y, b.width, b.height));
+        }
+    }
+
+    #[test]
+    fn state_change_should_keep_the_size_given_by_the_parent() {
+        let (mut state, mut layout) = buffers(
+            "<view>\
+                <group class=\"menu\">\
+                    <button class=\"item\"></button>\
+                    <button class=\"item\"></button>\
+                </group>\
+                <group class=\"inventory\">\
+                    <button class=\"item\"></button>\
+                    <button class=\"item\"></button>\
+                </group>\
+            </view>",
+            ".menu { direction: column; align-items: stretch; width: 200px; }
+             .inventory { display: grid; grid-columns: 2; cell-width: 50px;
+                          cell-height: 50px; }
+             .item { height: 30px; }
+             .item:focus { z-index: 1; }",
+            400f32, 400f32);
+        assert_eq!(layout[2].dim().content.width, 200f32);
+
+        layout.clear_dirty();
+        state.get_mut(2).unwrap().set_current_state(SelectorState::Focus);
+        state.get_mut(5).unwrap().set_current_state(SelectorState::Focus);
+        layout.update_nodes_from_state(&[2, 5], &state, 400f32, 400f32);
+
+        assert_eq!(layout[2].dim().content.width, 200f32);
+        let slot = layout[5].dim().content;
+        assert_eq!((slot.width, slot.height), (50f32, 50f32));
+
+        // Only the items have been laid out again
+        let dirty: Vec<_> = layout.iter().map(|n| n.is_dirty()).collect();
+        assert_eq!(dirty, vec![false, false, true, false, false, true, false]);
+
+        // Same result as a full layout
+        let rects: Vec<_> = layout.iter().map(|n| n.dim().content).collect();
+        layout.update_from_state(&state);
+        layout.compute_layout(400f32, 400f32);
+        for (node, a) in layout.iter().zip(rects.iter()) {
+            let b = node.dim().content;
+            assert_eq!((a.x, a.y, a.width, a.height), (b.x, b.y, b.width, b.height));
+        }
+    }
+
+    #[test]
+    fn state_change_should_keep_the_position_of_the_node() {
+        let (mut state, mut layout) = buffers(
+            "<view>\
+                <group class=\"bar\">\
+                    <button class=\"btn\"></button>\
+                    <button class=\"btn\"></button>\
+                </group>\
+            </view>",
+            ".bar { width: 300px; height: 50px; }
+             .btn { width: 50px; height: 30px; }
+             .btn:focus { z-index: 1; }",
+            400f32, 400f32);
+        let before = layout[3].dim().content;
+
+        layout.clear_dirty();
+        state.get_mut(3).unwrap().set_current_state(SelectorState::Focus);
+        layout.update_nodes_from_state(&[3], &state, 400f32, 400f32);
+
+        let after = layout[3].dim().content;
+        assert_eq!(before.x, 50f32);
+        assert_eq!((after.x, after.y, after.width, after.height),
+                   (before.x, before.y, before.width, before.height));
+
+        // Only the node has been laid out again
+        let dirty: Vec<_> = layout.iter().map(|n| n.is_dirty()).collect();
+        assert_eq!(dirty, vec![false, false, false, true]);
+    }
+
+    #[test]
+    fn text_should_be_measured_with_the_font_of_its_parents() {
+        let (_, mut layout) = buffers(
+            "<view>\
+                <group class=\"bar\">\
+                    <button class=\"btn\">More Options</button>\
+                    <button class=\"btn narrow\">More Options</button>\
+                </group>\
+            </view>",
+            ".bar { width: 300px; font-size: 20px; }
+             .btn { padding: 5px; }
+             .narrow { width: 60px; }",
+            400f32, 400f32);
+        layout.set_text_measurer(Rc::new(FixedAdvanceMeasurer::default()));
+        layout.compute_layout(400f32, 400f32);
+
+        // 12 characters of 10px on a line of 25px
+        let text = layout[3].dim().content;
+        assert_eq!((text.x, text.y, text.width, text.height), (5f32, 5f32, 120f32, 25f32));
+        let btn = layout[2].dim().content;
+        assert_eq!((btn.width, btn.height), (120f32, 25f32));
+
+        // Wrapped: "Options" is alone on the second line
+        let text = layout[5].dim().content;
+        assert_eq!((text.width, text.height), (60f32, 50f32));
+        let btn = layout[4].dim().content;
+        assert_eq!((btn.width, btn.height), (60f32, 50f32));
+    }
+
+    #[test]
+    fn layers_should_be_painted_after_their_siblings() {
+        let layout = layout(
+            "<view>\
+                <group class=\"panel\">\
+                    <button class=\"badge\"></button>\
+                    <button class=\"btn\"></button>\
+                    <group class=\"popup\">\
+                        <button class=\"btn\"></button>\
+                    </group>\
+                    <group class=\"tooltip\">\
+                        <button class=\"btn\"></button>\
+                    </group>\
+                </group>\
+                <button class=\"btn\"></button>\
+            </view>",
+            ".badge { layout: absolute; width: 10px; height: 10px; }
+             .btn { width: 50px; height: 20px; }
+             .popup { z-index: 2; }
+             .tooltip { z-index: 1; }",
+            300f32, 300f32);
+
+        assert_eq!(layout.paint_order(), &[0, 1, 3, 8, 2, 6, 7, 4, 5]);
+    }
+
+    #[test]
+    fn negative_layers_should_be_painted_before_the_flow() {
+        let layout = layout(
+            "<view>\
+                <group class=\"card\">\
+                    <button class=\"back\"></button>\
+                    <button class=\"btn\"></button>\
+                </group>\
+                <group class=\"panel\">\
+                    <button class=\"back\"></button>\
+                    <button class=\"btn\"></button>\
+                </group>\
+            </view>",
+            ".back { z-index: -1; }
+             .btn { width: 50px; height: 20px; }
+             .card { z-index: 1; }",
+            300f32, 300f32);
+
+        // The back of the panel goes below the panel itself,
+        // the back of the card stays in the card.
+        assert_eq!(layout.paint_order(), &[0, 5, 4, 6, 1, 2, 3]);
+    }
+
+    #[test]
+    fn layout_view_should_return_the_boxes_of_every_node() {
+        let lib = markup::parse(EmptyErrorReporter, BufReader::new(
+            "<view>\
+                <group class=\"panel\">\
+                    <button class=\"btn\"></button>\
+                </group>\
+            </view>".as_bytes()));
+        let (_, view) = lib.views.into_iter().next().unwrap();
+        let stylesheet = style::parse(
+            EmptyErrorReporter,
+            BufReader::new(".panel { width: 200px; margin: 10px; border: 2px; padding: 5px; }
+                            .btn { width: 50px; height: 20px; }".as_bytes()),
+            &StyleDefinitions::new(),
+            &mut resource::create_null_manager());
+
+        let root = layout_view(&view, &stylesheet, Viewport { width: 400f32, height: 300f32 });
+        let ref panel = root.children[0];
+
+        let r = panel.margin;
+        assert_eq!((r.x, r.y, r.width, r.height), (0f32, 0f32, 234f32, 54f32));
+        let r = panel.border;
+        assert_eq!((r.x, r.y, r.width, r.height), (10f32, 10f32, 214f32, 34f32));
+        let r = panel.padding;
+        assert_eq!((r.x, r.y, r.width, r.height), (12f32, 12f32, 210f32, 30f32));
+        let r = panel.content;
+        assert_eq!((r.x, r.y, r.width, r.height), (17f32, 17f32, 200f32, 20f32));
+
+        let r = panel.children[0].margin;
+        assert_eq!((r.x, r.y, r.width, r.height), (17f32, 17f32, 50f32, 20f32));
+    }
+
+    #[test]
+    fn dump_should_list_every_node_with_its_rects() {
+        let lib = markup::parse(EmptyErrorReporter, BufReader::new(
+            "<view>\
+                <group class=\"panel dark\">\
+                    <button class=\"btn\"></button>\
+                </group>\
+            </view>".as_bytes()));
+        let (_, view) = lib.views.into_iter().next().unwrap();
+        let stylesheet = style::parse(
+            EmptyErrorReporter,
+            BufReader::new(".panel { width: 100px; padding: 5px; }
+                            .btn { width: 50px; height: 20px; }".as_bytes()),
+            &StyleDefinitions::new(),
+            &mut resource::create_null_manager());
+        let root = layout_view(&view, &stylesheet, Viewport { width: 400f32, height: 300f32 });
+
+        assert_eq!(dump_layout(&view, &root, DumpFormat::Text),
+            "view #0 content(0 0 110 30) padding(0 0 110 30) border(0 0 110 30) margin(0 0 110 30)\n\
+             \x20 group.dark.panel #1 content(5 5 100 20) padding(0 0 110 30) border(0 0 110 30) margin(0 0 110 30)\n\
+             \x20   button.btn #2 content(5 5 50 20) padding(5 5 50 20) border(5 5 50 20) margin(5 5 50 20)\n");
+
+        let json = dump_layout(&view, &root, DumpFormat::Json);
+        assert!(json.starts_with("{\n  \"id\": 0,\n  \"type\": \"view\",\n  \"classes\": [],\n"));
+        assert!(json.contains("    {\n      \"id\": 1,\n      \"type\": \"group\",\n      \"classes\": [\"dark\", \"panel\"],\n"));
+        assert!(json.ends_with("  ]\n}\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "No snapshot at")]
+    fn missing_snapshot_should_fail() {
+        let path = env::temp_dir().join("oil-missing-snapshot.txt");
+        assert_layout_snapshot("view #0\n", &path);
+    }
+
+    #[test]
+    fn diff_should_show_the_lines_that_changed() {
+        assert!(diff_snapshot("a\nb\n", "a\nb\n").is_none());
+        assert_eq!(diff_snapshot("a\nb\nc\n", "a\nx\nc\n").unwrap(),
+                   "  a\n- b\n+ x\n  c\n");
+    }
 }
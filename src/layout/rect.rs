@@ -2,7 +2,7 @@
 /// Rect is mainly used by
 /// layout and focus trees.
 ///
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
 pub struct Rect {
     pub x: f32,
     pub y: f32,
@@ -19,6 +19,29 @@ impl Rect {
     //     self.intersects_x(other) * self.intersects_y(other)
     // }
 
+    /// Returns the part of this rectangle inside `other`.
+    /// The result is empty (zero width or height) if they
+    /// don't overlap.
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+
+        Rect {
+            x: x,
+            y: y,
+            width: (right - x).max(0f32),
+            height: (bottom - y).max(0f32),
+        }
+    }
+
+    /// Returns true if the point (`x`, `y`) is inside this rectangle.
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.x + self.width
+            && y >= self.y && y < self.y + self.height
+    }
+
     pub fn intersects_x(&self, other: &Rect) -> f32 {
         if self.x < other.x {
             if self.x + self.width > other.x {
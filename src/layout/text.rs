@@ -0,0 +1,114 @@
+use std::f32;
+use oil_shared::asset::FontData;
+
+/// Font size of a text when none of its parents has one.
+pub const DEFAULT_FONT_SIZE: f32 = 16f32;
+
+/// Size taken by a text once measured.
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct TextExtents {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Gives its size to the text of the `Text` and binding nodes.
+///
+/// The layout calls it with the font of the closest parent that
+/// has one. The text must be wrapped if it's wider than `max_width`,
+/// which is infinite when the width of the text on a single line
+/// is asked.
+pub trait TextMeasurer {
+    fn measure(&self, text: &str, font: Option<&FontData>, font_size: f32, max_width: f32)
+        -> TextExtents;
+}
+
+/// Measure every character with the same advance, whatever the font.
+/// The lines are wrapped between words. Useful to test a layout.
+///
+/// Both `advance` and `line_height` are a fraction of the font size.
+#[derive(Copy, Clone, Debug)]
+pub struct FixedAdvanceMeasurer {
+    pub advance: f32,
+    pub line_height: f32,
+}
+
+impl Default for FixedAdvanceMeasurer {
+    fn default() -> FixedAdvanceMeasurer {
+        FixedAdvanceMeasurer {
+            advance: 0.5,
+            line_height: 1.25,
+        }
+    }
+}
+
+impl TextMeasurer for FixedAdvanceMeasurer {
+
+    fn measure(&self, text: &str, _: Option<&FontData>, font_size: f32, max_width: f32)
+        -> TextExtents
+    {
+        let advance = self.advance * font_size;
+        let mut width = 0f32;
+        let mut line_width = 0f32;
+        let mut lines = 0;
+
+        for word in text.split_whitespace() {
+            let word_width = word.chars().count() as f32 * advance;
+
+            if lines == 0 {
+                lines = 1;
+                line_width = word_width;
+            } else if line_width + advance + word_width <= max_width {
+                line_width += advance + word_width;
+            } else {
+                // A word too wide is alone on its line.
+                width = width.max(line_width);
+                line_width = word_width;
+                lines += 1;
+            }
+        }
+
+        TextExtents {
+            width: width.max(line_width),
+            height: lines as f32 * self.line_height * font_size,
+        }
+    }
+}
+
+/// Used until a measurer is given: the text takes no space.
+pub struct NoTextMeasurer;
+
+impl TextMeasurer for NoTextMeasurer {
+
+    fn measure(&self, _: &str, _: Option<&FontData>, _: f32, _: f32) -> TextExtents {
+        TextExtents::default()
+    }
+}
+
+/// Font of a box, inherited by the text of its children.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TextStyle {
+    pub font: Option<FontData>,
+    pub size: f32,
+}
+
+impl Default for TextStyle {
+    fn default() -> TextStyle {
+        TextStyle {
+            font: None,
+            size: DEFAULT_FONT_SIZE,
+        }
+    }
+}
+
+impl TextStyle {
+
+    /// Measure `text` with this font.
+    pub fn measure(&self, measurer: &TextMeasurer, text: &str, max_width: f32) -> TextExtents {
+        measurer.measure(text, self.font.as_ref(), self.size, max_width)
+    }
+
+    /// Measure `text` on a single line.
+    pub fn measure_line(&self, measurer: &TextMeasurer, text: &str) -> TextExtents {
+        self.measure(measurer, text, f32::INFINITY)
+    }
+}
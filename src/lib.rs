@@ -34,6 +34,9 @@ pub use self::rendering::View;
 pub use self::data_bindings::DefaultContextManager;
 pub use self::data_bindings::Store;
 pub use self::data_bindings::DataBindingsContext;
+pub use self::layout::{Rect, BoxLayout, layout_view, layout_view_with_measurer};
+pub use self::layout::{DumpFormat, dump_layout, diff_snapshot, assert_layout_snapshot};
+pub use self::layout::{TextMeasurer, TextExtents, FixedAdvanceMeasurer};
 
 mod layout;
 mod router;
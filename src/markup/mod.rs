@@ -3,7 +3,7 @@
 // Re-export
 pub use oil_shared::markup::Node;
 pub use oil_shared::markup::NodeType;
-pub use oil_shared::markup::{Template, View};
+pub use oil_shared::markup::{Template, View, Position};
 pub use oil_shared::markup::{
     ButtonData,
     LineInputData,
@@ -15,6 +15,7 @@ pub use oil_shared::markup::{
 pub use oil_parsers::markup::Library;
 pub use oil_parsers::markup::MAIN_VIEW_NAME;
 pub use oil_parsers::markup::parse;
+pub use oil_parsers::markup::parse_expression;
 
 use util::HasChildren;
 
@@ -17,6 +17,7 @@ pub struct GliumRenderer<'a> {
     index_buffer: glium::IndexBuffer,
     program: glium::Program,
     matrix: cgmath::Matrix4<f32>,
+    viewport: Viewport,
 }
 
 impl<'a> GliumRenderer<'a> {
@@ -53,7 +54,8 @@ impl<'a> GliumRenderer<'a> {
             index_buffer: glium::IndexBuffer::new(display,
                 glium::index::TriangleStrip(vec![1u32, 2, 0, 3])),
             program: program,
-            matrix: cgmath::Matrix4::zero()
+            matrix: cgmath::Matrix4::zero(),
+            viewport: Viewport { width: 0.0, height: 0.0 },
         }
     }
 }
@@ -67,6 +69,7 @@ impl<'a> RenderBackbend for GliumRenderer<'a> {
     {
         let mut f = self.display.draw();
         self.matrix = cgmath::ortho(0.0, vp.width, vp.height, 0.0, 0.0, 1.0);
+        self.viewport = vp;
         f.clear_color(0.0, 0.0, 0.0, 0.0);
         f
     }
@@ -86,12 +89,25 @@ impl<'a> RenderBackbend for GliumRenderer<'a> {
 
         let vb = data.vertex_coords_buffer.as_ref().unwrap();
 
+        // The scissor rect starts at the bottom left corner.
+        let scissor = data.scissor.map(|r| glium::Rect {
+            left: r.x.max(0.0) as u32,
+            bottom: (self.viewport.height - r.y - r.height).max(0.0) as u32,
+            width: r.width.max(0.0) as u32,
+            height: r.height.max(0.0) as u32,
+        });
+
+        let params = glium::DrawParameters {
+            scissor: scissor,
+            .. Default::default()
+        };
+
         frame.draw(
             (vb, &data.tex_coords_buffer),
             &self.index_buffer,
             &self.program,
             &uniforms,
-            &Default::default()).unwrap();
+            &params).unwrap();
     }
 
     fn flush_frame(&self, frame: <GliumRenderer as RenderBackbend>::Frame) {
@@ -24,7 +24,7 @@ use glium::Display;
 use image::{GenericImage};
 
 use oil_shared::asset::ImageData;
-use layout::LayoutBox;
+use layout::{LayoutBox, Rect};
 use resource::{ResourceManager, ResourceId};
 
 #[derive(Copy, Clone)]
@@ -45,6 +45,8 @@ pub struct RenderData {
     tex_coords_buffer: glium::VertexBuffer<TexCoords>,
     vertex_coords_buffer: Option<glium::VertexBuffer<Vertex>>,
     rule: TextureRule,
+    // Area outside of which nothing is drawn
+    scissor: Option<Rect>,
 }
 
 impl RenderData {
@@ -64,6 +66,7 @@ impl RenderData {
             tex_coords_buffer: buffer,
             vertex_coords_buffer: None,
             rule: rule,
+            scissor: None,
         }
     }
 
@@ -113,6 +116,8 @@ impl RenderData {
         let height = lb.dim().content.height;
         let width = lb.dim().content.width;
 
+        self.scissor = lb.clip();
+
         self.vertex_coords_buffer = Some(
             glium::VertexBuffer::new(display, vec![
                 Vertex { position: [         x, y         ]},
@@ -12,6 +12,8 @@ use super::TextureRule;
 
 pub struct RenderBuffer {
     render_data: BufferFromTree<RenderData>,
+    // Indices of `render_data`, back to front
+    paint_order: Vec<usize>,
 }
 
 impl Deref for RenderBuffer {
@@ -44,23 +46,29 @@ impl RenderBuffer {
             }
         };
 
+        let render_data = BufferFromTree::from_buffer(state_buffer, node_producer);
+
         RenderBuffer {
-            render_data: BufferFromTree::from_buffer(
-                state_buffer,
-                node_producer
-            )
+            paint_order: (0..render_data.len()).collect(),
+            render_data: render_data,
         }
     }
 
+    /// Update the nodes marked as dirty in the layout.
     pub fn update_from_state<R: ResourceManager>(
         &mut self,
         display: &Display,
         resource_manager: &R,
-        state_data: &StateBuffer)
+        state_data: &StateBuffer,
+        layout_data: &LayoutBuffer)
     {
 
         for (&i, data) in self.render_data.enumerate_lookup_indices_mut().unwrap() {
 
+            if !unsafe { layout_data.get_unchecked(i) }.is_dirty() {
+                continue;
+            }
+
             let state = unsafe { state_data.get_unchecked(i) };
 
             if let Some(img) = state.get_background_image() {
@@ -70,6 +78,7 @@ impl RenderBuffer {
         }
     }
 
+    /// Update the nodes marked as dirty in the layout.
     pub fn update_from_layout(&mut self, display: &Display, layout_data: &LayoutBuffer) {
 
         for (&i, data) in self.render_data.enumerate_lookup_indices_mut().unwrap() {
@@ -77,8 +86,30 @@ impl RenderBuffer {
             // ensure that:
             //       self.layout_data.len() >= self.render_data
             let boxi = unsafe { layout_data.get_unchecked(i) };
-            data.update_coords(display, &boxi);
+            if boxi.is_dirty() {
+                data.update_coords(display, &boxi);
+            }
+        }
+
+        self.update_paint_order(layout_data);
+    }
+
+    /// Returns the indices of the render data in the order
+    /// they must be drawn. See `LayoutBuffer::paint_order`.
+    pub fn paint_order(&self) -> &[usize] {
+        &self.paint_order
+    }
+
+    fn update_paint_order(&mut self, layout_data: &LayoutBuffer) {
+        let mut slots = vec![None; layout_data.len()];
+
+        for (slot, (&i, _)) in self.render_data.enumerate_lookup_indices().unwrap().enumerate() {
+            slots[i] = Some(slot);
         }
+
+        self.paint_order = layout_data.paint_order().iter()
+            .filter_map(|&i| slots[i])
+            .collect();
     }
 }
 
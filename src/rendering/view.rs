@@ -1,5 +1,6 @@
 use glium::Display;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use resource::ResourceManager;
 use layout::LayoutBuffer;
@@ -8,14 +9,20 @@ use focus::{FocusBuffer, FocusedElement};
 use super::render::RenderBuffer;
 use oil_shared::style::SelectorState;
 use oil_shared::style::Stylesheet;
-use data_bindings::DataBindingBuffer;
+use data_bindings::{DataBindingBuffer, UnresolvedKey};
 use DataBindingsContext;
+use TextMeasurer;
 use markup;
 use RenderBackbend;
 use Viewport;
 
 pub struct View {
+    // The whole view must be laid out again
     dirty_flags: bool,
+    // Nodes whose state has changed since the last update
+    dirty_nodes: Vec<usize>,
+    // The focused node must be scrolled into view
+    scroll_to_focus: bool,
     // Buffers
     state_data: StateBuffer,
     focus_data: FocusBuffer,
@@ -24,6 +31,8 @@ pub struct View {
     // Current state
     current_focused_node: FocusedElement,
     data_binding_buffer: DataBindingBuffer,
+    // Kept to build the buffers again
+    stylesheet: Stylesheet,
 }
 
 impl View {
@@ -37,20 +46,24 @@ impl View {
         -> View
         where R: ResourceManager
     {
-        let state_buffer = StateBuffer::new(view, stylesheet);
-        let focus_buffer = FocusBuffer::new(view);
-        let layout_buffer = LayoutBuffer::new(view);
+        let mut data_binding_buffer = DataBindingBuffer::new(view, templates);
+        let view = data_binding_buffer.expand();
+        let state_buffer = StateBuffer::new(&view, stylesheet);
+        let focus_buffer = FocusBuffer::new(&view);
+        let layout_buffer = LayoutBuffer::new(&view);
         let render_buffer = RenderBuffer::new(display, resource_manager, &state_buffer);
-        let data_binding_buffer = DataBindingBuffer::new(view, templates);
 
         View {
             dirty_flags: true,
+            dirty_nodes: Vec::new(),
+            scroll_to_focus: false,
             layout_data: layout_buffer,
             render_data: render_buffer,
             current_focused_node: focus_buffer.first_acceptor(),
             focus_data: focus_buffer,
             state_data: state_buffer,
             data_binding_buffer: data_binding_buffer,
+            stylesheet: stylesheet.clone(),
         }
     }
 
@@ -59,20 +72,109 @@ impl View {
         display: &Display,
         resource_manager: &R,
         vp: Viewport,
+        name: &String,
         context: &mut C)
         where R: ResourceManager,
               C: DataBindingsContext
     {
-        let updated_bindings = self.data_binding_buffer.update(context, &mut self.layout_data);
+        let mut updated_bindings = self.data_binding_buffer.update(name, context, &mut self.layout_data);
+        if self.data_binding_buffer.is_reshaped() {
+            self.rebuild(display, resource_manager);
+            // The bindings are given to the new layout.
+            self.data_binding_buffer.update(name, context, &mut self.layout_data);
+            updated_bindings = true;
+        }
         if self.dirty_flags || updated_bindings {
             self.set_state_for_focused_node();
             self.layout_data.update_from_state(&self.state_data);
             self.layout_data.compute_layout(vp.width, vp.height);
-            self.render_data.update_from_state(display, resource_manager, &self.state_data);
-            self.render_data.update_from_layout(display, &self.layout_data);
-            self.focus_data.update_nodes(&self.layout_data);
-            self.dirty_flags = false;
+        } else if !self.dirty_nodes.is_empty() {
+            // Only the state of a few nodes has changed (focus):
+            // their subtrees are laid out again.
+            self.layout_data.update_nodes_from_state(
+                &self.dirty_nodes,
+                &self.state_data,
+                vp.width,
+                vp.height);
+        } else {
+            return;
+        }
+
+        if self.scroll_to_focus {
+            self.scroll_focused_node_into_view();
+        }
+
+        // Only the nodes marked as dirty by the layout are updated.
+        self.render_data.update_from_state(display, resource_manager, &self.state_data, &self.layout_data);
+        self.render_data.update_from_layout(display, &self.layout_data);
+        self.focus_data.update_nodes(&self.layout_data);
+        self.layout_data.clear_dirty();
+        self.dirty_nodes.clear();
+        self.dirty_flags = false;
+    }
+
+    /// Build the buffers again once the instances of a repeat have been
+    /// added, removed or reordered. The focus and the scroll offsets
+    /// stay on the nodes coming from the same items.
+    fn rebuild<R>(&mut self, display: &Display, resource_manager: &R)
+        where R: ResourceManager
+    {
+        let focused = self.focus_data.global_index(&self.current_focused_node)
+            .and_then(|i| self.data_binding_buffer.origin(i).cloned());
+        let mut scrolled = Vec::new();
+        for (i, node) in self.layout_data.iter().enumerate() {
+            if node.scroll() != (0f32, 0f32) {
+                if let Some(origin) = self.data_binding_buffer.origin(i) {
+                    scrolled.push((origin.clone(), node.scroll()));
+                }
+            }
         }
+
+        let view = self.data_binding_buffer.expand();
+        let measurer = self.layout_data.text_measurer();
+        self.state_data = StateBuffer::new(&view, &self.stylesheet);
+        self.focus_data = FocusBuffer::new(&view);
+        self.layout_data = LayoutBuffer::new(&view);
+        self.layout_data.set_text_measurer(measurer);
+        self.render_data = RenderBuffer::new(display, resource_manager, &self.state_data);
+
+        for (origin, scroll) in scrolled {
+            if let Some(i) = self.data_binding_buffer.index_of(&origin) {
+                self.layout_data.set_scroll(i, scroll);
+            }
+        }
+        // If the focused item is gone, the focus goes back to the first acceptor.
+        let focused_node = match focused.and_then(|o| self.data_binding_buffer.index_of(&o)) {
+            Some(i) => self.focus_data.acceptor(i),
+            None => None,
+        };
+        self.current_focused_node = match focused_node {
+            Some(node) => node,
+            None => self.focus_data.first_acceptor(),
+        };
+        self.dirty_nodes.clear();
+        self.dirty_flags = true;
+    }
+
+    /// Give their size to the text and binding nodes with `measurer`.
+    /// Until then, the text takes no space.
+    pub fn set_text_measurer(&mut self, measurer: Rc<TextMeasurer>) {
+        self.layout_data.set_text_measurer(measurer);
+        self.dirty_flags = true;
+    }
+
+    /// Query every binding at the next update. Called when the view
+    /// is shown again, see `DataBindingBuffer::invalidate`.
+    pub fn invalidate_bindings(&mut self) {
+        self.data_binding_buffer.invalidate();
+    }
+
+    /// Returns the keys used by this view that can't
+    /// be resolved by the context. See `Router::validate`.
+    pub fn validate<C>(&self, name: &str, context: &C) -> Vec<UnresolvedKey>
+        where C: DataBindingsContext
+    {
+        self.data_binding_buffer.validate(name, context)
     }
 
     pub fn render<R, B>(
@@ -83,8 +185,8 @@ impl View {
         where B: RenderBackbend,
               R: ResourceManager
     {
-        for data in self.render_data.iter() {
-            backend.render_element(resource_manager, frame, data);
+        for &i in self.render_data.paint_order() {
+            backend.render_element(resource_manager, frame, &self.render_data[i]);
         }
     }
 
@@ -92,7 +194,8 @@ impl View {
         if let Some(new_focused_node) = self.focus_data.focus_up(&self.current_focused_node) {
             self.remove_state_for_focused_node();
             self.current_focused_node = new_focused_node;
-            self.dirty_flags = true;
+            self.set_state_for_focused_node();
+            self.scroll_to_focus = true;
         }
     }
 
@@ -100,7 +203,8 @@ impl View {
         if let Some(new_focused_node) = self.focus_data.focus_down(&self.current_focused_node) {
             self.remove_state_for_focused_node();
             self.current_focused_node = new_focused_node;
-            self.dirty_flags = true;
+            self.set_state_for_focused_node();
+            self.scroll_to_focus = true;
         }
     }
 
@@ -108,7 +212,8 @@ impl View {
         if let Some(new_focused_node) = self.focus_data.focus_right(&self.current_focused_node) {
             self.remove_state_for_focused_node();
             self.current_focused_node = new_focused_node;
-            self.dirty_flags = true;
+            self.set_state_for_focused_node();
+            self.scroll_to_focus = true;
         }
     }
 
@@ -116,8 +221,39 @@ impl View {
         if let Some(new_focused_node) = self.focus_data.focus_left(&self.current_focused_node) {
             self.remove_state_for_focused_node();
             self.current_focused_node = new_focused_node;
-            self.dirty_flags = true;
+            self.set_state_for_focused_node();
+            self.scroll_to_focus = true;
+        }
+    }
+
+    /// Move the focus to the node at the point (`x`, `y`),
+    /// the one on top if several nodes are there.
+    pub fn focus_at(&mut self, x: f32, y: f32) {
+        if let Some(new_focused_node) = self.focus_data.acceptor_at(x, y) {
+            self.remove_state_for_focused_node();
+            self.current_focused_node = new_focused_node;
+            self.set_state_for_focused_node();
+        }
+    }
+
+    /// Scroll the innermost scrollable container holding
+    /// the focused node (see `overflow: scroll`).
+    pub fn scroll_by(&mut self, dx: f32, dy: f32) {
+        if let Some(global_index) = self.focus_data.global_index(&self.current_focused_node) {
+            if self.layout_data.scroll_by(global_index, dx, dy) {
+                // Nothing to layout again, but the buffers
+                // must be updated on the next update.
+                self.dirty_nodes.push(global_index);
+            }
+        }
+    }
+
+    fn scroll_focused_node_into_view(&mut self) {
+
+        if let Some(global_index) = self.focus_data.global_index(&self.current_focused_node) {
+            self.layout_data.scroll_into_view(global_index);
         }
+        self.scroll_to_focus = false;
     }
 
     fn set_state_for_focused_node(&mut self) {
@@ -127,6 +263,7 @@ impl View {
             self.state_data.get_mut(global_index)
                 .unwrap()
                 .set_current_state(SelectorState::Focus);
+            self.dirty_nodes.push(global_index);
         }
     }
 
@@ -137,6 +274,7 @@ impl View {
             self.state_data.get_mut(global_index)
                 .unwrap()
                 .set_current_state(SelectorState::Default);
+            self.dirty_nodes.push(global_index);
         }
     }
 }
@@ -9,6 +9,9 @@ use markup::Library;
 use resource::ResourceManager;
 use oil_shared::style::Stylesheet;
 use DataBindingsContext;
+use ErrorReporter;
+use TextMeasurer;
+use data_bindings::{BindingError, ValidationReport};
 use RenderBackbend;
 use View;
 use Viewport;
@@ -33,9 +36,11 @@ impl Router {
         lib: Library<E>,
         style: &Stylesheet)
         -> Router
-        where R: ResourceManager
+        where R: ResourceManager,
+              E: ErrorReporter
     {
         let mut router = Router::new();
+        lib.check_repeats();
         for (name, view) in lib.views.into_iter() {
             router.add_view(name, View::new(display, resource_manager, &view, &lib.templates, style));
         }
@@ -54,7 +59,9 @@ impl Router {
                 if let Some(pos) = self.stack.iter().rposition(|&(ref n, _)| *n == name) {
                     self.stack.truncate(pos+1);
                 } else {
-                // If not found then add it to the stack
+                // If not found then add it to the stack. The keys
+                // modified while it was hidden aren't dirty anymore.
+                    view.borrow_mut().invalidate_bindings();
                     self.stack.push((name, view.clone()));
                 }
                 Ok(())
@@ -87,18 +94,64 @@ impl Router {
         }
     }
 
+    /// Focus the node at the point (`x`, `y`) in the view
+    /// on top of the stack, see `View::focus_at`.
+    pub fn focus_at(&mut self, x: f32, y: f32) {
+        if let Some(&mut (_, ref mut view)) = self.stack.last_mut() {
+            view.borrow_mut().focus_at(x, y);
+        }
+    }
+
+    /// Scroll the container holding the focused node of the
+    /// view on top of the stack, see `View::scroll_by`.
+    pub fn scroll_by(&mut self, dx: f32, dy: f32) {
+        if let Some(&mut (_, ref mut view)) = self.stack.last_mut() {
+            view.borrow_mut().scroll_by(dx, dy);
+        }
+    }
+
+    /// Measure the text of every view with `measurer`,
+    /// see `View::set_text_measurer`.
+    pub fn set_text_measurer(&mut self, measurer: Rc<TextMeasurer>) {
+        for view in self.views.values() {
+            view.borrow_mut().set_text_measurer(measurer.clone());
+        }
+    }
+
+    /// Apply the updates sent to the context from other threads,
+    /// then refresh the bindings of the views in the stack.
+    ///
+    /// Returns the errors of the updates that couldn't be applied.
     pub fn update<R, C>(
         &mut self,
         display: &Display,
         resource_manager: &R,
         vp: Viewport,
-        context: &mut C)
+        context: &mut C) -> Vec<BindingError>
         where R: ResourceManager,
               C: DataBindingsContext
     {
-        for &mut (_, ref mut v) in self.stack.iter_mut() {
-            v.borrow_mut().update(display, resource_manager, vp, context);
+        let errors = context.apply_updates();
+        for &mut (ref name, ref mut v) in self.stack.iter_mut() {
+            v.borrow_mut().update(display, resource_manager, vp, name, context);
+        }
+        context.clear_dirty_keys();
+        errors
+    }
+
+    /// Check that every key used by the views can be resolved by
+    /// the context: bindings, `iter` of the `repeat` tags and bound
+    /// attributes. Call it once the stores have been registered.
+    ///
+    /// To validate the markup without a display, see `validate_library`.
+    pub fn validate<C>(&self, context: &C) -> ValidationReport
+        where C: DataBindingsContext
+    {
+        let mut report = ValidationReport::new();
+        for (name, view) in self.views.iter() {
+            report.add(name.clone(), view.borrow().validate(name, context));
         }
+        report
     }
 
     pub fn add_view<S : ToString>(&mut self, name: S, view: View) {
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 
 use util::BufferFromTree;
-use oil_shared::asset::ImageData;
+use oil_shared::asset::{FontData, ImageData};
 use oil_shared::properties::PropertyName;
 use oil_shared::properties::STYLE_PROPERTIES;
 use oil_shared::style::Value;
@@ -152,6 +152,16 @@ impl StateData {
         }
     }
 
+    pub fn keyword_of_prop(&self, prop_name: PropertyName) -> Option<KwValue> {
+        match self.current_properties().get(&prop_name) {
+            Some(&Value::Keyword(v)) => Some(v),
+            _ => match self.default_properties.get(&prop_name) {
+                Some(&Value::Keyword(v)) => Some(v),
+                _ => None
+            }
+        }
+    }
+
     pub fn get_background_rule(&self) -> Option<TextureRule> {
         match self.current_properties().get(&PropertyName::BACKGROUND_IMAGE_RULE) {
             Some(&Value::Keyword(v)) => match v {
@@ -180,6 +190,16 @@ impl StateData {
         }
     }
 
+    pub fn get_font(&self) -> Option<FontData> {
+        match self.current_properties().get(&PropertyName::FONT) {
+            Some(&Value::Font(ref font)) => Some(font.clone()),
+            _ => match self.default_properties.get(&PropertyName::FONT) {
+                Some(&Value::Font(ref font)) => Some(font.clone()),
+                _ => None
+            }
+        }
+    }
+
     pub fn size_prop_as_opt(&self, prop_name: PropertyName) -> Option<f32> {
         match self.current_properties().get(&prop_name) {
             Some(v) => {
@@ -213,7 +233,8 @@ impl StateData {
             | PropertyName::TOP
             | PropertyName::BOTTOM
             | PropertyName::HEIGHT
-            | PropertyName::WIDTH => {
+            | PropertyName::WIDTH
+            | PropertyName::GAP => {
                 return_length_or_zero!(self try prop_name)
             }
             PropertyName::MARGIN_LEFT
@@ -118,6 +118,54 @@ impl<T> FlatTree<T> {
         (0..).zip(self.buffer.iter_mut())
     }
 
+    pub fn mark_all_dirty(&mut self) {
+        for node in self.buffer.iter_mut() {
+            node.dirty = true;
+        }
+    }
+
+    pub fn clear_dirty(&mut self) {
+        for node in self.buffer.iter_mut() {
+            node.dirty = false;
+        }
+    }
+
+    /// Returns the indices of the nodes going from a root
+    /// down to the node at `index` (included).
+    ///
+    /// The result is empty if `index` is out of bounds.
+    pub fn path_to(&self, index: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+
+        if index < self.buffer.len() {
+            for root in self.tree_iter() {
+                if self.find_path(root, index, &mut path) {
+                    break;
+                }
+            }
+        }
+
+        path
+    }
+
+    fn find_path(&self, node: &TreeNode<T>, index: usize, path: &mut Vec<usize>) -> bool {
+        let node_index = self.node_as_index(node) as usize;
+        path.push(node_index);
+
+        if node_index == index {
+            return true;
+        }
+
+        for child in node.children() {
+            if self.find_path(child, index, path) {
+                return true;
+            }
+        }
+
+        path.pop();
+        false
+    }
+
     pub fn tree_iter<'a>(&'a self) -> FlatTreeIter<'a, T> {
         FlatTreeIter::new(&self.buffer)
     }
@@ -11,6 +11,9 @@ mod buffer;
 pub struct TreeNode<T> {
     data: T,
     next_sibling: isize,
+    // Set when the node changes, cleared once the
+    // buffers depending on this tree are up to date.
+    dirty: bool,
 }
 
 impl<T> Deref for TreeNode<T> {
@@ -34,6 +37,7 @@ impl<T> TreeNode<T> {
         TreeNode {
             data: data,
             next_sibling: next_sibling,
+            dirty: true,
         }
     }
 
@@ -42,6 +46,25 @@ impl<T> TreeNode<T> {
         self.next_sibling = next_sibling;
     }
 
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    #[inline]
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Mark this node and all its descendants as dirty.
+    pub fn mark_subtree_dirty(&mut self) {
+        self.dirty = true;
+
+        for child in self.children_mut() {
+            child.mark_subtree_dirty();
+        }
+    }
+
     pub fn children<'a>(&'a self) -> FlatTreeIter<'a, T> {
         if self.next_sibling > 1 || self.next_sibling == -1 {
             unsafe { FlatTreeIter::new_with_firstchild(self) }